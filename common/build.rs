@@ -0,0 +1,10 @@
+// Generate the WebSocket protocol from its source
+fn main() {
+    protobuf_codegen::Codegen::new()
+        .pure()
+        .include("protos")
+        .input("protos/proto_package.proto")
+        .cargo_out_dir("protos")
+        .run_from_script();
+    println!("cargo:rerun-if-changed=protos");
+}
@@ -0,0 +1,246 @@
+use std::{collections::HashMap, env, fmt::Display, sync::Arc, time::Duration};
+use deadpool_redis::{redis::{self, ConnectionAddr, IntoConnectionInfo}, Pool};
+use futures::StreamExt;
+use protobuf::Message;
+use redis_async::{
+    client::{pubsub::PubsubStream, ConnectionBuilder, PubsubConnection},
+    resp::FromResp,
+};
+use tokio::{sync::Mutex, task::JoinHandle, time::sleep};
+use uuid::Uuid;
+
+use crate::domain::{
+    models::package_queue::PackageQueue,
+    protos_schemas::proto_package::{proto_package::Owner, ProtoPackage},
+    types::id::Id,
+};
+
+
+/// Packages received from other nodes waiting to be written to the local
+/// connections
+pub const INCOMING_QUEUE_SIZE: usize = 4096;
+
+/// Time before subscribing again when the connection to Redis is lost
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub enum Error {
+    ConnectionError(String),
+    InvalidPackage(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::ConnectionError(err) => write!(f, "Connection error: {}", err),
+            Error::InvalidPackage(err) => write!(f, "Invalid package: {}", err),
+        }
+    }
+}
+
+/// Channel the packages of a user are published to
+pub fn user_channel(user_id: &Id) -> String {
+    format!("ws:user:{}", user_id)
+}
+
+struct Subscription {
+    /// Local connections of the user
+    connections: usize,
+    task: Option<JoinHandle<()>>,
+}
+
+/// Delivery of packages between the nodes serving WebSocket connections.
+/// Every node subscribes to the channels of the users connected to it and
+/// publishes the packages of their connections on other nodes.
+#[derive(Clone)]
+pub struct Cluster {
+    /// Marks the packages published by this node, they aren't delivered twice
+    pub node_id: Uuid,
+    /// Packages published by other nodes for the users connected here
+    pub incoming: PackageQueue,
+    pubsub: PubsubConnection,
+    subscriptions: Arc<Mutex<HashMap<Id, Subscription>>>,
+}
+
+impl Cluster {
+    pub async fn connect(redis_url: &str) -> Result<Cluster, Error> {
+        let info = redis_url.into_connection_info()
+            .map_err(|err| Error::ConnectionError(err.to_string()))?;
+        let (host, port) = match info.addr {
+            ConnectionAddr::Tcp(host, port) => (host, port),
+            _ => return Err(Error::ConnectionError("Only TCP connections are supported".to_string())),
+        };
+        let mut builder = ConnectionBuilder::new(host, port)
+            .map_err(|err| Error::ConnectionError(err.to_string()))?;
+        if let Some(username) = info.redis.username {
+            builder.username(username);
+        }
+        if let Some(password) = info.redis.password {
+            builder.password(password);
+        }
+        let pubsub = builder.pubsub_connect().await
+            .map_err(|err| Error::ConnectionError(err.to_string()))?;
+
+        Ok(Cluster {
+            node_id: Uuid::new_v4(),
+            incoming: PackageQueue::new(INCOMING_QUEUE_SIZE),
+            pubsub,
+            subscriptions: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// Receive the packages published for a user, called once for every local
+    /// connection of the user
+    pub async fn subscribe(&self, user_id: Id) -> Result<(), Error> {
+        let mut subscriptions = self.subscriptions.lock().await;
+        let subscription = subscriptions.entry(user_id)
+            .or_insert(Subscription { connections: 0, task: None });
+        subscription.connections += 1;
+        if subscription.task.as_ref().is_some_and(|task| !task.is_finished()) {
+            return Ok(());
+        }
+        // Counted anyway, the next connection of the user tries again
+        let stream = self.pubsub.subscribe(&user_channel(&user_id)).await
+            .map_err(|err| Error::ConnectionError(err.to_string()))?;
+        subscription.task = Some(tokio::spawn(self.clone().receive(user_id, stream)));
+        Ok(())
+    }
+
+    /// Called when a local connection of the user closes, the channel is left
+    /// with the last one
+    pub async fn unsubscribe(&self, user_id: &Id) {
+        let mut subscriptions = self.subscriptions.lock().await;
+        let connections = match subscriptions.get_mut(user_id) {
+            Some(subscription) => {
+                subscription.connections = subscription.connections.saturating_sub(1);
+                subscription.connections
+            },
+            None => return,
+        };
+        if connections == 0 {
+            // Dropping the stream leaves the channel
+            if let Some(task) = subscriptions.remove(user_id).and_then(|s| s.task) {
+                task.abort();
+            }
+        }
+    }
+
+    /// Publish the packages to their recipients in one round trip, returns
+    /// how many other nodes received each package
+    pub async fn publish(&self, pool: &Pool, packages: &[ProtoPackage]) -> Result<Vec<usize>, Error> {
+        let mut pipe = redis::pipe();
+        let mut recipients = Vec::with_capacity(packages.len());
+        for package in packages {
+            let recipient = recipient_id(package)?;
+            pipe.cmd("PUBLISH").arg(user_channel(&recipient)).arg(encode(&self.node_id, package)?);
+            recipients.push(recipient);
+        }
+
+        let mut conn = pool.get().await
+            .map_err(|err| Error::ConnectionError(err.to_string()))?;
+        let received: Vec<usize> = pipe.query_async(&mut conn).await
+            .map_err(|err| Error::ConnectionError(err.to_string()))?;
+
+        // This node counts as a receiver of its own users
+        let subscriptions = self.subscriptions.lock().await;
+        Ok(received.into_iter()
+            .zip(recipients)
+            .map(|(received, recipient)| {
+                let subscribed = subscriptions.get(&recipient)
+                    .is_some_and(|subscription| subscription.task.is_some());
+                received.saturating_sub(subscribed as usize)
+            })
+            .collect())
+    }
+
+    /// Queue the packages published for the user until the channel is left
+    async fn receive(self, user_id: Id, stream: PubsubStream) {
+        let channel = user_channel(&user_id);
+        let mut stream = Some(stream);
+        loop {
+            if let Some(mut subscription) = stream.take() {
+                while let Some(Ok(payload)) = subscription.next().await {
+                    let payload = Vec::<u8>::from_resp(payload)
+                        .map_err(|err| Error::InvalidPackage(err.to_string()));
+                    match payload.and_then(|payload| decode(&self.node_id, &payload)) {
+                        Ok(Some(package)) => self.incoming.push(package).await,
+                        // Published by this node
+                        Ok(None) => (),
+                        Err(err) => eprintln!("Cluster error: {}", err),
+                    }
+                }
+                // Leaving before subscribing again, dropping it later would
+                // leave the new subscription
+                drop(subscription);
+                eprintln!("Lost the subscription to {}", channel);
+            }
+            sleep(RESUBSCRIBE_DELAY).await;
+            match self.pubsub.subscribe(&channel).await {
+                Ok(subscription) => stream = Some(subscription),
+                Err(err) => eprintln!("Cluster error: {}", err),
+            }
+        }
+    }
+}
+
+// Function to join the cluster through the cache server.
+pub async fn create_cluster() -> Cluster {
+    let redis_url = env::var("CACHE_URL").expect("CACHE_URL must be set");
+    Cluster::connect(&redis_url).await.expect("Error joining the cluster")
+}
+
+/// Packages are published behind the id of the node publishing them
+fn encode(node_id: &Uuid, package: &ProtoPackage) -> Result<Vec<u8>, Error> {
+    let mut payload = node_id.as_bytes().to_vec();
+    package.write_to_vec(&mut payload)
+        .map_err(|err| Error::InvalidPackage(err.to_string()))?;
+    Ok(payload)
+}
+
+/// Package published by another node, `None` for the packages of `node_id`
+fn decode(node_id: &Uuid, payload: &[u8]) -> Result<Option<ProtoPackage>, Error> {
+    if payload.len() < 16 {
+        return Err(Error::InvalidPackage("No node id".to_string()));
+    }
+    let (publisher, package) = payload.split_at(16);
+    if publisher == node_id.as_bytes() {
+        return Ok(None);
+    }
+    ProtoPackage::parse_from_bytes(package)
+        .map(Some)
+        .map_err(|err| Error::InvalidPackage(err.to_string()))
+}
+
+fn recipient_id(package: &ProtoPackage) -> Result<Id, Error> {
+    let recipient: Result<[u8; 16], _> = match package.owner.clone() {
+        Some(Owner::Recipient(recipient)) => recipient.value.try_into(),
+        _ => return Err(Error::InvalidPackage("No recipient found".to_string())),
+    };
+    match recipient.map(Uuid::from_bytes).map(Id::try_from) {
+        Ok(Ok(recipient)) => Ok(recipient),
+        _ => Err(Error::InvalidPackage("Invalid recipient".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::protos_schemas::proto_package::proto_package::Payload;
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        let recipient = Id::try_from(Uuid::new_v4()).unwrap();
+        let package = ProtoPackage {
+            owner: Some(Owner::Recipient(recipient.into())),
+            payload: Some(Payload::Message(Default::default())),
+            ..Default::default()
+        };
+        let node_id = Uuid::new_v4();
+        let payload = encode(&node_id, &package).unwrap();
+        // Skipped by the node publishing it
+        assert_eq!(decode(&node_id, &payload).unwrap(), None);
+        assert_eq!(decode(&Uuid::new_v4(), &payload).unwrap(), Some(package.clone()));
+        assert_eq!(recipient_id(&package).unwrap(), recipient);
+        assert!(decode(&Uuid::new_v4(), &payload[..8]).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use std::env;
+use std::{env, path::PathBuf, time::Duration};
 
 
 /// Debug only secret for JWT encoding & decoding.
@@ -9,16 +9,105 @@ pub const DATE_FORMAT: &'static str = "%Y-%m-%dT%H:%M:%S%.3fZ";
 
 pub const TOKEN_PREFIX: &'static str = "Bearer ";
 
+/// Seconds a sender has to edit or delete a message when not configured
+pub const MESSAGE_EDIT_WINDOW_SECS: u64 = 15 * 60;
+
+/// Seconds a client message id is remembered when not configured
+pub const MESSAGE_DEDUP_WINDOW_SECS: u64 = 24 * 60 * 60;
+
+/// Most messages kept for a recipient who is not connected when not
+/// configured, the oldest are dropped first
+pub const MAX_PENDING_MESSAGES: u64 = 1000;
+
+/// Seconds the pending messages of a recipient are kept after the last one
+/// was queued when not configured
+pub const PENDING_MESSAGES_TTL_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// Seconds between the pings sent to every WebSocket connection when not
+/// configured
+pub const WS_HEARTBEAT_INTERVAL_SECS: u64 = 30;
+
+/// Seconds without any frame after which a WebSocket connection is closed
+/// when not configured, long enough to miss a couple of heartbeats
+pub const WS_IDLE_TIMEOUT_SECS: u64 = 75;
+
+/// Directory media is stored in when not configured
+pub const MEDIA_ROOT: &str = "media";
+
+/// Directory chunked uploads are kept in until complete when not configured
+pub const UPLOAD_ROOT: &str = "uploads";
+
+/// Largest media of each kind accepted when not configured, in bytes
+pub const MAX_IMAGE_SIZE: u64 = 10 * 1024 * 1024;
+pub const MAX_VIDEO_SIZE: u64 = 100 * 1024 * 1024;
+pub const MAX_AUDIO_SIZE: u64 = 20 * 1024 * 1024;
+pub const MAX_FILE_SIZE: u64 = 50 * 1024 * 1024;
+
 #[derive(Clone)]
 pub enum Environment {
     Development,
     Production,
 }
 
+/// Where media blobs are stored, `MEDIA_STORAGE` is `local` or `s3`
+#[derive(Clone)]
+pub enum MediaStorage {
+    /// Directory on the server disk
+    Local(PathBuf),
+    /// S3 bucket, `endpoint` points to an S3 compatible server like MinIO
+    S3 { bucket: String, endpoint: Option<String> },
+}
+
+/// Largest media of each kind accepted, in bytes
+#[derive(Clone)]
+pub struct MediaLimits {
+    pub image: u64,
+    pub video: u64,
+    pub audio: u64,
+    pub file: u64,
+}
+
+impl MediaLimits {
+    /// Limit for a media path kind, nothing is accepted for unknown kinds
+    pub fn max_size(&self, kind: &str) -> u64 {
+        match kind {
+            "image" => self.image,
+            "video" => self.video,
+            "audio" => self.audio,
+            "file" => self.file,
+            _ => 0,
+        }
+    }
+}
+
+fn size_var(name: &str, default: u64) -> u64 {
+    match env::var(name) {
+        Ok(size) => size.parse()
+            .unwrap_or_else(|err| panic!("Invalid {}: {:?}", name, err)),
+        Err(_) => default,
+    }
+}
+
 #[derive(Clone)]
 pub struct Config {
     pub secret: Vec<u8>,
     pub environment: Environment,
+    /// Time after sending during which a message can be edited or deleted
+    pub message_edit_window: Duration,
+    /// Time during which a message resent with the same client id is not
+    /// stored again
+    pub message_dedup_window: Duration,
+    /// Most messages kept for a recipient who is not connected
+    pub max_pending_messages: u64,
+    /// Time the pending messages of a recipient are kept after the last one
+    /// was queued
+    pub pending_messages_ttl: Duration,
+    pub ws_heartbeat_interval: Duration,
+    pub ws_idle_timeout: Duration,
+    pub media_storage: MediaStorage,
+    /// Directory the chunks of the uploads in progress are kept in
+    pub upload_root: PathBuf,
+    pub media_limits: MediaLimits,
 }
 
 impl Config {
@@ -40,10 +129,71 @@ impl Config {
             s => panic!("Unknown environment: {}", s),
         };
 
-        
+        let message_edit_window = match env::var("MESSAGE_EDIT_WINDOW_SECS") {
+            Ok(secs) => secs.parse()
+                .unwrap_or_else(|err| panic!("Invalid MESSAGE_EDIT_WINDOW_SECS: {:?}", err)),
+            Err(_) => MESSAGE_EDIT_WINDOW_SECS,
+        };
+
+        let message_dedup_window = match env::var("MESSAGE_DEDUP_WINDOW_SECS") {
+            Ok(secs) => secs.parse()
+                .unwrap_or_else(|err| panic!("Invalid MESSAGE_DEDUP_WINDOW_SECS: {:?}", err)),
+            Err(_) => MESSAGE_DEDUP_WINDOW_SECS,
+        };
+
+        let pending_messages_ttl = match env::var("PENDING_MESSAGES_TTL_SECS") {
+            Ok(secs) => secs.parse()
+                .unwrap_or_else(|err| panic!("Invalid PENDING_MESSAGES_TTL_SECS: {:?}", err)),
+            Err(_) => PENDING_MESSAGES_TTL_SECS,
+        };
+
+        let ws_heartbeat_interval = match env::var("WS_HEARTBEAT_INTERVAL_SECS") {
+            Ok(secs) => secs.parse()
+                .unwrap_or_else(|err| panic!("Invalid WS_HEARTBEAT_INTERVAL_SECS: {:?}", err)),
+            Err(_) => WS_HEARTBEAT_INTERVAL_SECS,
+        };
+
+        let ws_idle_timeout = match env::var("WS_IDLE_TIMEOUT_SECS") {
+            Ok(secs) => secs.parse()
+                .unwrap_or_else(|err| panic!("Invalid WS_IDLE_TIMEOUT_SECS: {:?}", err)),
+            Err(_) => WS_IDLE_TIMEOUT_SECS,
+        };
+
+        let media_storage = match env::var("MEDIA_STORAGE")
+            .unwrap_or_else(|_| "local".to_string())
+            .as_str()
+        {
+            "local" => MediaStorage::Local(PathBuf::from(
+                env::var("MEDIA_ROOT").unwrap_or_else(|_| MEDIA_ROOT.to_string())
+            )),
+            "s3" => MediaStorage::S3 {
+                bucket: env::var("S3_BUCKET").expect("S3_BUCKET must be set"),
+                endpoint: env::var("S3_ENDPOINT").ok().filter(|endpoint| !endpoint.is_empty()),
+            },
+            s => panic!("Unknown media storage: {}", s),
+        };
+
+        let upload_root = env::var("UPLOAD_ROOT").unwrap_or_else(|_| UPLOAD_ROOT.to_string());
+
+        let media_limits = MediaLimits {
+            image: size_var("MAX_IMAGE_SIZE", MAX_IMAGE_SIZE),
+            video: size_var("MAX_VIDEO_SIZE", MAX_VIDEO_SIZE),
+            audio: size_var("MAX_AUDIO_SIZE", MAX_AUDIO_SIZE),
+            file: size_var("MAX_FILE_SIZE", MAX_FILE_SIZE),
+        };
+
         Config {
             secret: secret.into_bytes(),
             environment,
+            message_edit_window: Duration::from_secs(message_edit_window),
+            message_dedup_window: Duration::from_secs(message_dedup_window),
+            max_pending_messages: size_var("MAX_PENDING_MESSAGES", MAX_PENDING_MESSAGES),
+            pending_messages_ttl: Duration::from_secs(pending_messages_ttl),
+            ws_heartbeat_interval: Duration::from_secs(ws_heartbeat_interval),
+            ws_idle_timeout: Duration::from_secs(ws_idle_timeout),
+            media_storage,
+            upload_root: PathBuf::from(upload_root),
+            media_limits,
         }
     }
 }
@@ -0,0 +1,64 @@
+use mongodb::{bson::{doc, Document}, options::IndexOptions, Client, IndexModel};
+use std::env;
+
+
+// Function to create the document database client.
+pub async fn create_client() -> Client {
+    let db_url = env::var("DOCUMENT_DB_URL").expect("DOCUMENT_DB_URL must be set");
+    let client = Client::with_uri_str(&db_url)
+        .await
+        .expect("Failed to create document database client.");
+    create_indexes(&client).await;
+    client
+}
+
+/// Indexes backing the lookups by message id and the conversation pages,
+/// which are read newest first by sender, recipient or group member, and the
+/// inbox, a conversation summary per user and peer read newest first, and the
+/// uploaders of each media.
+/// Creating an index that already exists does nothing.
+async fn create_indexes(client: &Client) {
+    let database = client.database("chat_app");
+    let messages = database.collection::<Document>("messages");
+    let indexes = vec![
+        IndexModel::builder()
+            .keys(doc! { "id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build(),
+        IndexModel::builder()
+            .keys(doc! { "sender.User": 1, "created_at": -1, "id": -1 })
+            .build(),
+        IndexModel::builder()
+            .keys(doc! { "recipient.User": 1, "created_at": -1, "id": -1 })
+            .build(),
+        IndexModel::builder()
+            .keys(doc! { "recipient.Group.members": 1, "created_at": -1, "id": -1 })
+            .build(),
+    ];
+    messages.create_indexes(indexes, None)
+        .await
+        .expect("Failed to create document database indexes.");
+
+    let conversations = database.collection::<Document>("conversations");
+    let indexes = vec![
+        IndexModel::builder()
+            .keys(doc! { "user_id": 1, "peer": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build(),
+        IndexModel::builder()
+            .keys(doc! { "user_id": 1, "last.created_at": -1, "last.id": -1 })
+            .build(),
+    ];
+    conversations.create_indexes(indexes, None)
+        .await
+        .expect("Failed to create document database indexes.");
+
+    let media_owners = database.collection::<Document>("media_owners");
+    let index = IndexModel::builder()
+        .keys(doc! { "user_id": 1, "path": 1 })
+        .options(IndexOptions::builder().unique(true).build())
+        .build();
+    media_owners.create_index(index, None)
+        .await
+        .expect("Failed to create document database indexes.");
+}
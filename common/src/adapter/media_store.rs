@@ -0,0 +1,39 @@
+use std::path::PathBuf;
+
+use aws_config::SdkConfig;
+use aws_sdk_s3::{config::Builder, Client};
+
+use super::config::MediaStorage;
+
+
+/// Bucket media are stored in and the client to reach it
+#[derive(Clone)]
+pub struct S3Bucket {
+    pub client: Client,
+    pub bucket: String,
+}
+
+/// Connection to the configured media storage
+#[derive(Clone)]
+pub enum MediaStore {
+    Local(PathBuf),
+    S3(S3Bucket),
+}
+
+// Function to create the connection to the media storage.
+pub fn create_store(storage: &MediaStorage, shared_config: &SdkConfig) -> MediaStore {
+    match storage {
+        MediaStorage::Local(root) => MediaStore::Local(root.clone()),
+        MediaStorage::S3 { bucket, endpoint } => {
+            let mut builder = Builder::from(shared_config);
+            // S3 compatible servers are usually not reachable by virtual host
+            if let Some(endpoint) = endpoint {
+                builder = builder.endpoint_url(endpoint).force_path_style(true);
+            }
+            MediaStore::S3(S3Bucket {
+                client: Client::from_conf(builder.build()),
+                bucket: bucket.clone(),
+            })
+        },
+    }
+}
@@ -1,6 +1,9 @@
 pub mod utils;
 pub mod config;
 pub mod db;
+pub mod document_db;
 pub mod cache;
+pub mod media_store;
+pub mod cluster;
 pub mod state;
 pub mod response_schemas;
\ No newline at end of file
@@ -1,25 +1,33 @@
-use std::{sync::Arc, collections::{HashMap, VecDeque}};
+use std::{sync::Arc, collections::HashMap};
 use aws_config::{meta::region::RegionProviderChain, Region};
-use axum::extract::ws::{WebSocket, Message};
+use axum::extract::ws::Message;
 use deadpool_redis::Pool;
-use futures_util::stream::SplitSink;
+pub use mongodb::Client as DocumentClient;
 use sqlx::PgPool;
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, RwLock};
 use aws_sdk_sesv2::Client;
 
-use crate::domain::{models::client::Clients, protos_schemas::proto_package::ProtoPackage};
-use super::{config::Config, db, cache};
+use crate::domain::models::client::Clients;
+use super::{config::Config, db, document_db, cache, cluster::{self, Cluster}, media_store::{self, MediaStore}};
 
 
-pub type PackageQueue = Arc<RwLock<VecDeque<ProtoPackage>>>;
+pub use crate::domain::models::package_queue::PackageQueue;
+
+/// Packages queued for dispatching before producers have to wait
+pub const PACKAGE_QUEUE_SIZE: usize = 4096;
 
 #[derive(Clone)]
 pub struct AppState {
-    pub clients: Clients<SplitSink<WebSocket, Message>>,
+    /// Every connection is written through its bounded outbound queue
+    pub clients: Clients<mpsc::Sender<Message>>,
     pub package_queue: PackageQueue,
+    /// Reaches the connections held by other nodes
+    pub cluster: Cluster,
     pub db_sql_pool: PgPool,
+    pub db_document_client: DocumentClient,
     pub cache_pool: Pool,
     pub email_conn: Client,
+    pub media_store: MediaStore,
     pub config: Config,
 }
 
@@ -30,12 +38,17 @@ impl AppState {
             .or_default_provider();
         let shared_config = aws_config::from_env().region(region_provider).load().await;
 
+        let config = Config::new();
+
         AppState {
             db_sql_pool: db::create_pool().await,
+            db_document_client: document_db::create_client().await,
             cache_pool: cache::create_pool().await,
-            config: Config::new(),
+            cluster: cluster::create_cluster().await,
+            media_store: media_store::create_store(&config.media_storage, &shared_config),
+            config,
             clients: Arc::new(RwLock::new(HashMap::new())),
-            package_queue: Arc::new(RwLock::new(VecDeque::new())),
+            package_queue: PackageQueue::new(PACKAGE_QUEUE_SIZE),
             email_conn: Client::new(&shared_config),
         }
     }
@@ -77,15 +77,16 @@ impl TryFrom<ProtoRecipient2> for Recipient {
 impl From<Id> for ProtoUuid {
     fn from(id: Id) -> Self {
         let id: Uuid = id.into();
-        let id = id.to_bytes_le();
-        protobuf::Message::parse_from_bytes(&id).unwrap()
+        id.into()
     }
 }
 
 impl From<Uuid> for ProtoUuid {
     fn from(uuid: Uuid) -> Self {
-        let id = uuid.to_bytes_le();
-        protobuf::Message::parse_from_bytes(&id).unwrap()
+        Self {
+            value: uuid.as_bytes().to_vec(),
+            special_fields: protobuf::SpecialFields::default(),
+        }
     }
 }
 
@@ -5,11 +5,16 @@ use uuid::Uuid;
 use crate::domain::types::id::Id;
 
 
-pub type Clients<T> = Arc<RwLock<HashMap<Uuid, Client<T>>>>;
+/// Live connections indexed by user id, every user can have several
+/// connections (phone, web, desktop) indexed by connection id.
+pub type Clients<T> = Arc<RwLock<HashMap<Id, HashMap<Uuid, Client<T>>>>>;
 
 #[derive(Debug)]
 pub struct Client<T> {
     pub user_id: Id,
+    /// Device of the key directory the connection is opened from, it gets
+    /// the envelopes of encrypted messages sealed for that device
+    pub device_id: Option<Id>,
     pub sender: Option<T>,
     pub task: tokio::task::JoinHandle<()>,
 }
\ No newline at end of file
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+
+use crate::domain::protos_schemas::proto_package::ProtoPackage;
+
+
+/// Packages waiting to be dispatched to their recipients. The channel is
+/// bounded so producers slow down when dispatching falls behind.
+#[derive(Clone)]
+pub struct PackageQueue {
+    sender: mpsc::Sender<ProtoPackage>,
+    receiver: Arc<Mutex<mpsc::Receiver<ProtoPackage>>>,
+}
+
+impl PackageQueue {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, receiver) = mpsc::channel(capacity);
+        Self { sender, receiver: Arc::new(Mutex::new(receiver)) }
+    }
+
+    /// Queue a package, waits while the queue is full
+    pub async fn push(&self, package: ProtoPackage) {
+        // The queue holds its own receiver, it is never closed
+        let _ = self.sender.send(package).await;
+    }
+
+    pub async fn extend(&self, packages: impl IntoIterator<Item = ProtoPackage>) {
+        for package in packages {
+            self.push(package).await;
+        }
+    }
+
+    /// Packages waiting to be dispatched
+    pub fn len(&self) -> usize {
+        self.sender.max_capacity() - self.sender.capacity()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Next package to dispatch, waits until one is queued. Meant for a
+    /// single dispatcher, others wait for it to release the receiver.
+    pub async fn pop(&self) -> Option<ProtoPackage> {
+        self.receiver.lock().await.recv().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_package_queue() {
+        let queue = PackageQueue::new(2);
+        let package = |client_id: &str| ProtoPackage {
+            client_id: client_id.to_string(),
+            ..Default::default()
+        };
+        queue.extend([package("A"), package("B")]).await;
+        assert_eq!(queue.len(), 2);
+        // Full, the producer waits for the dispatcher
+        let producer = queue.clone();
+        let pushed = tokio::spawn(async move { producer.push(package("C")).await });
+        assert_eq!(queue.pop().await.unwrap().client_id, "A");
+        pushed.await.unwrap();
+        assert_eq!(queue.pop().await.unwrap().client_id, "B");
+        assert_eq!(queue.pop().await.unwrap().client_id, "C");
+        assert!(queue.is_empty());
+    }
+}
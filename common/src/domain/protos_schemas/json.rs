@@ -0,0 +1,225 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use protobuf::{
+    reflect::{ReflectValueBox, ReflectValueRef, RuntimeFieldType, RuntimeType},
+    MessageDyn,
+    MessageFull,
+};
+use serde_json::{Map, Value};
+
+use crate::domain::types::error::ErrorMsg;
+
+
+/// JSON representation of a message following the proto3 JSON mapping:
+/// lowerCamelCase field names, bytes in base64, 64 bit integers as strings,
+/// enums by name and default values left out
+pub fn to_json<M: MessageFull>(message: &M) -> Value {
+    message_to_json(message)
+}
+
+/// Message from its JSON representation, fields are accepted by their proto
+/// or JSON name and numbers as numbers or strings
+pub fn from_json<M: MessageFull>(json: &Value) -> Result<M, ErrorMsg> {
+    let mut message = M::new();
+    merge_json(&mut message, json)?;
+    Ok(message)
+}
+
+fn message_to_json(message: &dyn MessageDyn) -> Value {
+    let mut object = Map::new();
+    for field in message.descriptor_dyn().fields() {
+        let value = match field.runtime_field_type() {
+            RuntimeFieldType::Singular(_) => match field.get_singular(message) {
+                Some(value) => value_to_json(value),
+                None => continue,
+            },
+            RuntimeFieldType::Repeated(_) => {
+                let values = field.get_repeated(message);
+                if values.is_empty() {
+                    continue;
+                }
+                Value::Array(values.into_iter().map(value_to_json).collect())
+            },
+            // Not used by the protocol
+            RuntimeFieldType::Map(..) => continue,
+        };
+        object.insert(field.json_name().to_string(), value);
+    }
+    Value::Object(object)
+}
+
+fn value_to_json(value: ReflectValueRef) -> Value {
+    match value {
+        ReflectValueRef::U32(value) => value.into(),
+        ReflectValueRef::I32(value) => value.into(),
+        // Not every 64 bit integer fits a JSON number
+        ReflectValueRef::U64(value) => value.to_string().into(),
+        ReflectValueRef::I64(value) => value.to_string().into(),
+        ReflectValueRef::F32(value) => f64::from(value).into(),
+        ReflectValueRef::F64(value) => value.into(),
+        ReflectValueRef::Bool(value) => value.into(),
+        ReflectValueRef::String(value) => value.into(),
+        ReflectValueRef::Bytes(value) => STANDARD.encode(value).into(),
+        ReflectValueRef::Enum(descriptor, number) => match descriptor.value_by_number(number) {
+            Some(value) => value.name().into(),
+            None => number.into(),
+        },
+        ReflectValueRef::Message(message) => message_to_json(&*message),
+    }
+}
+
+fn merge_json(message: &mut dyn MessageDyn, json: &Value) -> Result<(), ErrorMsg> {
+    let object = json.as_object()
+        .ok_or_else(|| ErrorMsg(format!("Expected an object, found {}", json)))?;
+    let descriptor = message.descriptor_dyn();
+    // A field given by both its names, or two fields of a oneof, would
+    // silently override each other
+    let mut fields_set = Vec::new();
+    let mut oneofs_set = Vec::new();
+    for (name, value) in object {
+        let field = descriptor.field_by_name_or_json_name(name)
+            .ok_or_else(|| ErrorMsg(format!("Unknown field {}", name)))?;
+        if value.is_null() {
+            continue;
+        }
+        if fields_set.contains(&field) {
+            return Err(ErrorMsg(format!("Field {} set more than once", field.name())));
+        }
+        if let Some(oneof) = field.containing_oneof() {
+            if oneofs_set.contains(&oneof) {
+                return Err(ErrorMsg(format!("Several fields of {} set", oneof.name())));
+            }
+            oneofs_set.push(oneof);
+        }
+        fields_set.push(field.clone());
+        match field.runtime_field_type() {
+            RuntimeFieldType::Singular(runtime_type) => {
+                field.set_singular_field(message, value_from_json(&runtime_type, value)?);
+            },
+            RuntimeFieldType::Repeated(runtime_type) => {
+                let values = value.as_array()
+                    .ok_or_else(|| ErrorMsg(format!("Expected an array for {}", name)))?;
+                let mut repeated = field.mut_repeated(message);
+                for value in values {
+                    repeated.push(value_from_json(&runtime_type, value)?);
+                }
+            },
+            RuntimeFieldType::Map(..) => return Err(ErrorMsg(format!("Unsupported field {}", name))),
+        }
+    }
+    Ok(())
+}
+
+fn value_from_json(runtime_type: &RuntimeType, value: &Value) -> Result<ReflectValueBox, ErrorMsg> {
+    let invalid = || ErrorMsg(format!("Invalid value {}", value));
+    Ok(match runtime_type {
+        RuntimeType::I32 => ReflectValueBox::I32(integer(value).ok_or_else(invalid)?),
+        RuntimeType::I64 => ReflectValueBox::I64(integer(value).ok_or_else(invalid)?),
+        RuntimeType::U32 => ReflectValueBox::U32(integer(value).ok_or_else(invalid)?),
+        RuntimeType::U64 => ReflectValueBox::U64(integer(value).ok_or_else(invalid)?),
+        RuntimeType::F32 => ReflectValueBox::F32(value.as_f64().ok_or_else(invalid)? as f32),
+        RuntimeType::F64 => ReflectValueBox::F64(value.as_f64().ok_or_else(invalid)?),
+        RuntimeType::Bool => ReflectValueBox::Bool(value.as_bool().ok_or_else(invalid)?),
+        RuntimeType::String => ReflectValueBox::String(value.as_str().ok_or_else(invalid)?.to_string()),
+        RuntimeType::VecU8 => {
+            let encoded = value.as_str().ok_or_else(invalid)?;
+            ReflectValueBox::Bytes(STANDARD.decode(encoded).map_err(|_| invalid())?)
+        },
+        RuntimeType::Enum(descriptor) => {
+            let number = match value {
+                Value::String(name) => descriptor.value_by_name(name).map(|value| value.value()),
+                value => integer(value),
+            };
+            ReflectValueBox::Enum(descriptor.clone(), number.ok_or_else(invalid)?)
+        },
+        RuntimeType::Message(descriptor) => {
+            let mut message = descriptor.new_instance();
+            merge_json(&mut *message, value)?;
+            ReflectValueBox::Message(message)
+        },
+    })
+}
+
+fn integer<T: std::str::FromStr>(value: &Value) -> Option<T> {
+    match value {
+        Value::Number(number) => number.to_string().parse().ok(),
+        Value::String(number) => number.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use protobuf::MessageField;
+    use serde_json::json;
+
+    use crate::domain::protos_schemas::proto_package::{
+        proto_package::{Owner, Payload},
+        proto_receipt::Kind,
+        ProtoMessage,
+        ProtoPackage,
+        ProtoReceipt,
+        ProtoUuid,
+    };
+    use super::*;
+
+    #[test]
+    fn test_json_round_trip() {
+        let id = ProtoUuid { value: vec![1; 16], ..Default::default() };
+        let package = ProtoPackage {
+            owner: Some(Owner::Recipient(id.clone())),
+            client_id: "c1".to_string(),
+            payload: Some(Payload::Message(ProtoMessage {
+                id: MessageField::some(id.clone()),
+                message_type: "TEXT".to_string(),
+                content: b"hello".to_vec(),
+                timestamp: 1_700_000_000_000,
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let json = to_json(&package);
+        assert_eq!(json["clientId"], "c1");
+        assert_eq!(json["message"]["content"], STANDARD.encode("hello"));
+        assert_eq!(json["message"]["timestamp"], "1700000000000");
+        // Unset fields are left out
+        assert!(json["message"].get("sender").is_none());
+        assert_eq!(from_json::<ProtoPackage>(&json).unwrap(), package);
+
+        let package = ProtoPackage {
+            payload: Some(Payload::Receipt(ProtoReceipt {
+                kind: Kind::READ.into(),
+                message_id: MessageField::some(id),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let json = to_json(&package);
+        assert_eq!(json["receipt"]["kind"], "READ");
+        assert_eq!(from_json::<ProtoPackage>(&json).unwrap(), package);
+    }
+
+    #[test]
+    fn test_from_json() {
+        let json = json!({
+            "client_id": "c1",
+            "typing": { "typing": true, "timestamp": 1700000000000i64 },
+        });
+        let package: ProtoPackage = from_json(&json).unwrap();
+        assert_eq!(package.client_id, "c1");
+        assert!(package.typing().typing);
+        assert_eq!(package.typing().timestamp, 1_700_000_000_000);
+
+        assert!(from_json::<ProtoPackage>(&json!({ "unknown": 1 })).is_err());
+        assert!(from_json::<ProtoPackage>(&json!({ "message": { "content": "not base64!" } })).is_err());
+        assert!(from_json::<ProtoPackage>(&json!([])).is_err());
+    }
+
+    #[test]
+    fn test_from_json_oneof() {
+        let typing = json!({ "typing": true });
+        let receipt = json!({ "kind": "READ" });
+        assert!(from_json::<ProtoPackage>(&json!({ "typing": typing, "receipt": receipt })).is_err());
+        assert!(from_json::<ProtoPackage>(&json!({ "typing": typing, "receipt": null })).unwrap().has_typing());
+        assert!(from_json::<ProtoPackage>(&json!({ "client_id": "c1", "clientId": "c2" })).is_err());
+    }
+}
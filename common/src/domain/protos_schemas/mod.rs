@@ -1 +1,4 @@
-pub mod proto_package;
\ No newline at end of file
+// Generated at build time from protos/proto_package.proto
+include!(concat!(env!("OUT_DIR"), "/protos/mod.rs"));
+
+pub mod json;
@@ -5,7 +5,7 @@ use uuid::Uuid;
 use super::error::ErrorMsg;
 
 
-#[derive(PartialEq, Clone, Debug, Copy)]
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Copy)]
 pub struct Id(Uuid);
 
 impl TryFrom<Uuid> for Id {
@@ -3,18 +3,74 @@ use serde::{Deserialize, Serialize};
 use super::error::ErrorMsg;
 
 
-#[derive(Clone, Deserialize, Serialize)]
+/// Kinds of media a path can point to
+pub const MEDIA_KINDS: [&str; 4] = ["image", "video", "audio", "file"];
+
+/// Stable path of a stored media, `<kind>/<sha256 of the content in hex>`
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct MediaPath(String);
 
+impl MediaPath {
+    pub fn new(kind: &str, hash: &str) -> Result<Self, ErrorMsg> {
+        format!("{}/{}", kind, hash).try_into()
+    }
+
+    pub fn kind(&self) -> &str {
+        self.0.split_once('/').map(|(kind, _)| kind).unwrap_or_default()
+    }
+
+    /// SHA-256 of the content, lowercase hex
+    pub fn hash(&self) -> &str {
+        self.0.split_once('/').map(|(_, hash)| hash).unwrap_or_default()
+    }
+}
 
 impl TryFrom<String> for MediaPath {
     type Error = ErrorMsg;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        if value.len() > 0 {
+        let (kind, hash) = match value.split_once('/') {
+            Some(parts) => parts,
+            None => return Err(ErrorMsg("Invalid media path".to_string())),
+        };
+        let valid_hash = hash.len() == 64
+            && hash.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c));
+        if MEDIA_KINDS.contains(&kind) && valid_hash {
             Ok(Self(value))
         } else {
-            Err(ErrorMsg("Invalid text".to_string()))
+            Err(ErrorMsg("Invalid media path".to_string()))
         }
     }
-}
\ No newline at end of file
+}
+
+impl From<MediaPath> for String {
+    fn from(value: MediaPath) -> Self {
+        value.0
+    }
+}
+
+impl std::fmt::Display for MediaPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests_media_path {
+    use super::*;
+
+    #[test]
+    fn test_media_path() {
+        let hash = "a".repeat(64);
+        let path = MediaPath::new("image", &hash).unwrap();
+        assert_eq!(path.kind(), "image");
+        assert_eq!(path.hash(), hash);
+        assert_eq!(String::from(path), format!("image/{}", hash));
+
+        assert!(MediaPath::new("sticker", &hash).is_err());
+        assert!(MediaPath::new("image", &"A".repeat(64)).is_err());
+        assert!(MediaPath::new("image", "abc").is_err());
+        assert!(MediaPath::try_from(format!("image/../{}", "a".repeat(61))).is_err());
+        assert!(MediaPath::try_from(hash).is_err());
+    }
+}
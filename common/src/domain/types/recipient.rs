@@ -10,6 +10,16 @@ pub enum Recipient {
     Group(Group),
 }
 
+impl Recipient {
+    /// Whether `id` is the recipient or one of the members of the recipient group
+    pub fn includes(&self, id: &Id) -> bool {
+        match self {
+            Self::User(user_id) => user_id == id,
+            Self::Group(group) => group.members.contains(id),
+        }
+    }
+}
+
 impl TryFrom<Uuid> for Recipient {
     type Error = &'static str;
 
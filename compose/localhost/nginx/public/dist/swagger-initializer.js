@@ -19,6 +19,18 @@ window.onload = function() {
       {
         name: "Contact",
         url: `${origin}/api/openapi-files/contact/openapi.yml`
+      },
+      {
+        name: "Group",
+        url: `${origin}/api/openapi-files/group/openapi.yml`
+      },
+      {
+        name: "Key",
+        url: `${origin}/api/openapi-files/key/openapi.yml`
+      },
+      {
+        name: "Message",
+        url: `${origin}/api/openapi-files/message/openapi.yml`
       }
     ],
     dom_id: '#swagger-ui',
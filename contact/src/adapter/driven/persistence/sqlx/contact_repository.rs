@@ -33,6 +33,25 @@ impl ContactRepositoryTrait<Pool<Postgres>> for ContactRepository {
         }
     }
 
+    async fn find_blocking(&self, conn: &Pool<Postgres>, user_ids: &[Id], id: Id) -> Result<Vec<Id>, contact_repository::Error> {
+        let user_ids: Vec<Uuid> = user_ids.iter().copied().map(Uuid::from).collect();
+        let blocking = sqlx::query_scalar!(
+            r#"
+                SELECT user_id FROM contacts
+                WHERE user_id = ANY($1::UUID[]) AND id = $2 AND is_blocked;
+            "#,
+            &user_ids,
+            &Uuid::from(id)
+        ).fetch_all(conn).await;
+
+        match blocking {
+            Ok(blocking) => blocking.into_iter()
+                .map(|user_id| user_id.try_into().map_err(|_| contact_repository::Error::DatabaseError))
+                .collect(),
+            Err(_) => Err(contact_repository::Error::DatabaseError)
+        }
+    }
+
     async fn find_by_user_id(&self, conn: &Pool<Postgres>, user_id: Id) -> Result<Vec<Contact>, contact_repository::Error> {
         let contacts = sqlx::query_as!(
             ContactDB,
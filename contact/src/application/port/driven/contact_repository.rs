@@ -27,6 +27,18 @@ pub trait ContactRepositoryTrait<T> {
     /// - `Err(Error)` - An error occurred
     fn find_by_id(&self, conn: &T, user_id: Id, id: Id) -> impl std::future::Future<Output = Result<Contact, Error>> + Send;
 
+    /// Get the users among `user_ids` who blocked `id` in their contacts
+    /// 
+    /// # Parameters
+    /// - `conn` - The connection to the database
+    /// - `user_ids` - The ids of the users
+    /// - `id` - The id of the contact
+    /// 
+    /// # Returns
+    /// - `Ok(Vec<Id>)` - The users who blocked the contact
+    /// - `Err(Error)` - An error occurred
+    fn find_blocking(&self, conn: &T, user_ids: &[Id], id: Id) -> impl std::future::Future<Output = Result<Vec<Id>, Error>> + Send;
+
     /// Get all contacts of a user
     /// 
     /// # Parameters
@@ -0,0 +1,61 @@
+use crate::application::port::driven::contact_repository::ContactRepositoryTrait;
+use common::domain::types::id::Id;
+
+pub enum Error {
+    DatabaseError,
+}
+
+pub struct Payload {
+    /// The users whose contacts are checked
+    pub user_ids: Vec<Id>,
+    /// The user that may be blocked
+    pub contact_id: Id,
+}
+
+/// Users among `user_ids` who blocked `contact_id`, in a single lookup
+pub async fn execute<T>(
+    conn: &T,
+    repo: &impl ContactRepositoryTrait<T>,
+    payload: Payload,
+) -> Result<Vec<Id>, Error> {
+    repo.find_blocking(conn, &payload.user_ids, payload.contact_id)
+        .await
+        .map_err(|_| Error::DatabaseError)
+}
+
+#[cfg(test)]
+mod test {
+    use common::adapter::db::create_pool;
+    use sqlx::{Pool, Postgres};
+    use uuid::Uuid;
+
+    use crate::{
+        adapter::driven::persistence::sqlx::contact_repository::ContactRepository,
+        domain::contact::NewContact,
+    };
+    use super::*;
+
+    async fn new_user(pool: &Pool<Postgres>) -> Id {
+        let user_id: Uuid = sqlx::query_scalar("INSERT INTO auths (hashed_password) VALUES ('') RETURNING user_id")
+            .fetch_one(pool).await.unwrap();
+        user_id.try_into().unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Postgres at DATABASE_URL"]
+    async fn test_blocked_by() {
+        let pool = create_pool().await;
+        let repo = ContactRepository();
+        let sender = new_user(&pool).await;
+        let (blocking, unblocked, stranger) = (new_user(&pool).await, new_user(&pool).await, new_user(&pool).await);
+        for (user_id, is_blocked) in [(blocking, true), (unblocked, false)] {
+            let new_contact = NewContact { id: sender, user_id, alias: None, is_blocked };
+            assert!(repo.create(&pool, new_contact).await.is_ok());
+        }
+
+        let payload = Payload { user_ids: vec![blocking, unblocked, stranger], contact_id: sender };
+        let blocked_by = execute(&pool, &repo, payload).await
+            .unwrap_or_else(|_| panic!("Error checking the contacts"));
+        assert_eq!(blocked_by, vec![blocking]);
+    }
+}
@@ -4,38 +4,32 @@ use crate::application::port::driven::contact_repository::{
 use common::domain::types::id::Id;
 
 pub enum Error {
-    NotFound,
     DatabaseError,
     Unauthorized,
 }
 
 pub struct Payload {
-    user_id: Id,
-    contact_id: Id,
+    /// The user whose contacts are checked
+    pub user_id: Id,
+    /// The user that may be blocked
+    pub contact_id: Id,
 }
 
+/// Whether `user_id` blocked `contact_id`, users that are not in the contacts
+/// are not blocked
 pub async fn execute<T>(
     conn: &T,
     repo: &impl ContactRepositoryTrait<T>,
     payload: Payload,
 ) -> Result<bool, Error> {
     match repo
-        .find_by_id(
-            conn,
-            payload.user_id.try_into().unwrap(),
-            payload.contact_id.try_into().unwrap(),
-        )
+        .find_by_id(conn, payload.user_id, payload.contact_id)
         .await
     {
-        Ok(contact) => {
-            if contact.is_blocked {
-                return Ok(true);
-            }
-            return Ok(false);
-        }
+        Ok(contact) => Ok(contact.is_blocked),
         Err(e) => match e {
             ContactRepositoryError::DatabaseError => Err(Error::DatabaseError),
-            ContactRepositoryError::NotFound => Err(Error::NotFound),
+            ContactRepositoryError::NotFound => Ok(false),
         },
     }
 }
@@ -0,0 +1,34 @@
+use crate::application::port::driven::contact_repository::{
+    ContactRepositoryTrait, Error as ContactRepositoryError,
+};
+use common::domain::types::id::Id;
+
+pub enum Error {
+    DatabaseError,
+}
+
+pub struct Payload {
+    /// The user whose contacts are checked
+    pub user_id: Id,
+    /// The user that may be in the contacts
+    pub contact_id: Id,
+}
+
+/// Whether `user_id` has `contact_id` in their contacts, blocked contacts
+/// don't count
+pub async fn execute<T>(
+    conn: &T,
+    repo: &impl ContactRepositoryTrait<T>,
+    payload: Payload,
+) -> Result<bool, Error> {
+    match repo
+        .find_by_id(conn, payload.user_id, payload.contact_id)
+        .await
+    {
+        Ok(contact) => Ok(!contact.is_blocked),
+        Err(e) => match e {
+            ContactRepositoryError::DatabaseError => Err(Error::DatabaseError),
+            ContactRepositoryError::NotFound => Ok(false),
+        },
+    }
+}
@@ -2,4 +2,6 @@ pub mod add_contact;
 pub mod remove_contact;
 pub mod update_contact;
 pub mod get_contacts;
-pub mod is_blocked;
\ No newline at end of file
+pub mod is_blocked;
+pub mod blocked_by;
+pub mod is_contact;
\ No newline at end of file
@@ -4,8 +4,9 @@ mod adapter;
 
 // Adapter layer
 pub use adapter::driving::http::{handlers, schemas};
+pub use adapter::driven::persistence::sqlx::contact_repository::ContactRepository;
 // pub use adapter::driven::cache::redis::token_cache::TokenCache;
 // Application layer
 // pub use application::port::driven::token_cache::TokenCacheTrait;
 
-pub use application::use_cases::is_blocked;
+pub use application::use_cases::{blocked_by, is_blocked, is_contact};
@@ -0,0 +1,2 @@
+DROP TABLE groups_members;
+DROP TABLE groups;
\ No newline at end of file
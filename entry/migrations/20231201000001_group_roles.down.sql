@@ -0,0 +1 @@
+ALTER TABLE groups_members DROP COLUMN role;
\ No newline at end of file
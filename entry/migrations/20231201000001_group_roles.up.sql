@@ -0,0 +1,11 @@
+ALTER TABLE groups_members
+ADD COLUMN role TEXT NOT NULL DEFAULT 'MEMBER'
+CONSTRAINT role_check CHECK (role IN ('OWNER', 'ADMIN', 'MEMBER'));
+
+-- The oldest member of every existing group owns it
+UPDATE groups_members SET role = 'OWNER'
+WHERE (group_id, user_id) IN (
+    SELECT DISTINCT ON (group_id) group_id, user_id
+    FROM groups_members
+    ORDER BY group_id, created_at
+);
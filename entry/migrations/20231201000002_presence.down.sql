@@ -0,0 +1 @@
+DROP TABLE presences;
\ No newline at end of file
@@ -0,0 +1,8 @@
+CREATE TABLE presences(
+    user_id UUID NOT NULL,
+    last_seen TIMESTAMPTZ,
+    hide_last_seen BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT fk_user FOREIGN KEY(user_id) REFERENCES auths(user_id) ON DELETE CASCADE,
+    PRIMARY KEY (user_id)
+);
@@ -0,0 +1,2 @@
+DROP TABLE one_time_prekeys;
+DROP TABLE devices_keys;
\ No newline at end of file
@@ -0,0 +1,21 @@
+CREATE TABLE devices_keys(
+    user_id UUID NOT NULL,
+    device_id UUID NOT NULL,
+    identity_key BYTEA NOT NULL,
+    signed_prekey_id BIGINT NOT NULL,
+    signed_prekey BYTEA NOT NULL,
+    signed_prekey_signature BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT fk_user FOREIGN KEY(user_id) REFERENCES auths(user_id) ON DELETE CASCADE,
+    PRIMARY KEY (user_id, device_id)
+);
+
+CREATE TABLE one_time_prekeys(
+    user_id UUID NOT NULL,
+    device_id UUID NOT NULL,
+    key_id BIGINT NOT NULL,
+    public_key BYTEA NOT NULL,
+    CONSTRAINT fk_device FOREIGN KEY(user_id, device_id) REFERENCES devices_keys(user_id, device_id) ON DELETE CASCADE,
+    PRIMARY KEY (user_id, device_id, key_id)
+);
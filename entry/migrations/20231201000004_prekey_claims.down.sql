@@ -0,0 +1 @@
+DROP TABLE one_time_prekey_claims;
\ No newline at end of file
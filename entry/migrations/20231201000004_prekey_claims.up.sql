@@ -0,0 +1,9 @@
+CREATE TABLE one_time_prekey_claims(
+    requester_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    device_id UUID NOT NULL,
+    claimed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT fk_requester FOREIGN KEY(requester_id) REFERENCES auths(user_id) ON DELETE CASCADE,
+    CONSTRAINT fk_device FOREIGN KEY(user_id, device_id) REFERENCES devices_keys(user_id, device_id) ON DELETE CASCADE,
+    PRIMARY KEY (requester_id, user_id, device_id)
+);
@@ -0,0 +1,13 @@
+-- Claims are kept per requesting device, every device of a requester starts
+-- its own sessions
+DROP TABLE one_time_prekey_claims;
+CREATE TABLE one_time_prekey_claims(
+    requester_id UUID NOT NULL,
+    requester_device_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    device_id UUID NOT NULL,
+    claimed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT fk_requester_device FOREIGN KEY(requester_id, requester_device_id) REFERENCES devices_keys(user_id, device_id) ON DELETE CASCADE,
+    CONSTRAINT fk_device FOREIGN KEY(user_id, device_id) REFERENCES devices_keys(user_id, device_id) ON DELETE CASCADE,
+    PRIMARY KEY (requester_id, requester_device_id, user_id, device_id)
+);
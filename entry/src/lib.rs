@@ -24,7 +24,10 @@ mod ws;
 use auth::handlers as auth_handlers;
 use profile::handlers as profile_handlers;
 use contact::handlers as contact_handlers;
-use ws::handler::{run_consumer_event_queue, ws_handler};
+use group::handlers as group_handlers;
+use key::handlers as key_handlers;
+use message::{handlers as message_handlers, GroupHistory};
+use ws::handler::{run_consumer_event_queue, run_heartbeat, ws_handler};
 
 
 pub async fn router() -> Router {
@@ -35,10 +38,10 @@ pub async fn router() -> Router {
     run_migrations(&app_state.db_sql_pool).await;
 
     // new thread to listen to event queue
-    run_consumer_event_queue(
-        app_state.package_queue.clone(), 
-        app_state.clients.clone()
-    ).await;
+    run_consumer_event_queue(app_state.clone()).await;
+
+    // new thread to ping the websocket clients
+    run_heartbeat(app_state.clone()).await;
 
     // new thread to get metrics
     run_geting_metricts(sys);
@@ -109,8 +112,74 @@ pub async fn router() -> Router {
                 .delete(contact_handlers::handle_delete_contact)
             )
         )
+        // group
+        .nest(
+            "/group",
+            Router::new()
+            .route(
+                "/group",
+                get(group_handlers::handle_get_groups)
+                .post(group_handlers::handle_create_group)
+            )
+            .route(
+                "/group/:id",
+                get(group_handlers::handle_get_group)
+                .put(group_handlers::handle_rename_group)
+            )
+            .route("/group/:id/members", post(group_handlers::handle_add_member))
+            .route(
+                "/group/:id/members/:user_id",
+                put(group_handlers::handle_change_role::<GroupHistory>)
+                .delete(group_handlers::handle_remove_member::<GroupHistory>)
+            )
+            .route(
+                "/group/:id/owner",
+                put(group_handlers::handle_transfer_ownership::<GroupHistory>)
+            )
+            .route("/group/:id/leave", post(group_handlers::handle_leave_group::<GroupHistory>))
+        )
+        // key
+        .nest(
+            "/key",
+            Router::new()
+            .route("/devices", get(key_handlers::handle_get_devices))
+            .route(
+                "/devices/:device_id",
+                put(key_handlers::handle_upload_keys)
+                .delete(key_handlers::handle_delete_device)
+            )
+            .route(
+                "/devices/:device_id/one-time-prekeys",
+                post(key_handlers::handle_add_one_time_prekeys)
+            )
+            .route("/users/:user_id/bundles", get(key_handlers::handle_get_prekey_bundles))
+        )
         // message
-        .nest("/message", Router::new().route("/ws", get(ws_handler)));
+        .nest(
+            "/message",
+            Router::new()
+            .route("/ws", get(ws_handler))
+            .route("/conversations", get(message_handlers::handle_get_conversations))
+            .route(
+                "/conversations/:peer_id/messages",
+                get(message_handlers::handle_get_conversation_messages)
+            )
+            .route(
+                "/conversations/:peer_id/messages/:message_id",
+                get(message_handlers::handle_get_conversation_message)
+            )
+            .route("/media/:kind/:hash", get(message_handlers::handle_get_media))
+            .route("/media/:kind/:hash/upload-url", post(message_handlers::handle_get_upload_url))
+            .route("/uploads", post(message_handlers::handle_create_upload))
+            .route("/uploads/:id", get(message_handlers::handle_get_upload))
+            .route("/uploads/:id/chunks/:index", put(message_handlers::handle_upload_chunk))
+            .route("/uploads/:id/complete", post(message_handlers::handle_complete_upload))
+            .route(
+                "/presence/settings",
+                get(message_handlers::handle_get_presence_settings)
+                    .put(message_handlers::handle_update_presence_settings)
+            )
+        );
 
     // Return a `Router`
     Router::new()
@@ -9,10 +9,10 @@ use prometheus::{
     HistogramVec, 
     IntCounter, 
     // IntCounterVec, 
-    // IntGauge,
+    IntGauge,
     // Opts, 
     register_int_counter, 
-    // register_int_gauge, 
+    register_int_gauge, 
     // register_int_counter_vec, 
     register_histogram_vec, 
     Gauge, 
@@ -27,10 +27,11 @@ lazy_static! {
         "Total number of HTTP requests"
     ).unwrap();
 
-    // pub static ref CONNECTED_CLIENTS: IntGauge = register_int_gauge!(
-    //     "connected_clients", 
-    //     "Connected Clients"
-    // ).unwrap();
+    /// Open WebSocket connections, a user with several devices counts once per device
+    pub static ref CONNECTED_CLIENTS: IntGauge = register_int_gauge!(
+        "connected_clients", 
+        "Connected Clients"
+    ).unwrap();
 
     // pub static ref RESPONSE_CODE_COLLECTOR: IntCounterVec = register_int_counter_vec!(
     //     Opts::new("response_code", "Response Codes"),
@@ -1,3 +1,4 @@
+use common::domain::types::id::Id;
 use serde::{Deserialize, Serialize};
 
 
@@ -5,4 +6,6 @@ use serde::{Deserialize, Serialize};
 #[serde(rename_all = "camelCase")]
 pub struct AuthWebSocket {
     pub auth_token: String,
+    /// Device in the key directory, needed to receive encrypted messages
+    pub device_id: Option<Id>,
 }
\ No newline at end of file
@@ -1,25 +1,41 @@
 use axum::extract::ws::{WebSocket, Message};
-use protobuf;
-use futures_util::{StreamExt, stream::SplitSink};
+use futures_util::{SinkExt, StreamExt, stream::{SplitSink, SplitStream}};
+use tokio::{sync::mpsc, time::{timeout, Duration}};
 use uuid::Uuid;
 
 use common::{
-    adapter::state::PackageQueue, 
+    adapter::state::AppState, 
     domain::{
         models::client::{Client, Clients}, 
         types::id::Id
     }
 };
-use common::domain::protos_schemas::proto_package::ProtoPackage;
+use common::domain::protos_schemas::proto_package::{
+    proto_package::Payload,
+    proto_receipt::Kind,
+    ProtoPackage,
+};
+use message::ws_handlers;
+use crate::metrics::CONNECTED_CLIENTS;
+use super::encoding::Encoding;
+
+
+/// Messages queued for a connection before it is dropped as a slow consumer
+pub const OUTBOUND_QUEUE_SIZE: usize = 256;
 
+/// Time a write to a socket can take before the connection is dropped as a
+/// slow consumer
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub async fn execute(
-    clients: Clients<SplitSink<WebSocket, Message>>,
-    package_queue: PackageQueue,
+    state: AppState,
     sender_id: Uuid,
+    device_id: Option<Id>,
     socket: WebSocket,
 ) {
-    let (sender, mut receiver) = socket.split();
+    // Negotiated in the upgrade
+    let encoding = Encoding::from_subprotocol(socket.protocol());
+    let (mut sender, mut receiver) = socket.split();
     // Create a new client id
     let client_id = Uuid::new_v4();
 
@@ -29,50 +45,322 @@ pub async fn execute(
         eprintln!("Error converting Uuid to Id");
         return;
     };
+
+    // Agree on the protocol version before anything else is sent
+    if let Err(err) = handshake(&mut sender, &mut receiver, encoding, state.config.ws_idle_timeout).await {
+        eprintln!("Handshake error: {}", err);
+        let _ = sender.close().await;
+        return;
+    }
+
+    // Replay what was missed while offline before live traffic resumes
+    let replayed = match replay_pending(&state, user_id, device_id, &mut sender, encoding).await {
+        Ok(replayed) => replayed,
+        Err(err) => {
+            eprintln!("Replay error: {}", err);
+            return;
+        }
+    };
     
+    // Packages published by other nodes from now on reach this node, the
+    // ones before the connection is registered are queued as pending
+    if let Err(err) = state.cluster.subscribe(user_id).await {
+        eprintln!("Cluster error: {}", err);
+    }
+
+    // Counted on every node, only the first connection brings the user
+    // online. Counted before the connection can close and be uncounted.
+    notify_presence(&state, user_id, true).await;
+
+    // Packages reach the socket through a bounded queue, so a slow socket
+    // only holds up its own connection
+    let (outbound, outbound_receiver) = mpsc::channel(OUTBOUND_QUEUE_SIZE);
+    let mut writer = tokio::spawn(write_messages(sender, outbound_receiver, encoding, user_id));
+
     // Create events
+    let task_state = state.clone();
+    let idle_timeout = state.config.ws_idle_timeout;
     let task = async move {
-        while let Some(message) = receiver.next().await {
+        loop {
+            let message = tokio::select! {
+                // Live clients answer the heartbeats, silence means a dead connection
+                message = timeout(idle_timeout, receiver.next()) => match message {
+                    Ok(Some(message)) => message,
+                    Ok(None) => break,
+                    Err(_) => {
+                        eprintln!("Idle connection of {}", user_id);
+                        break;
+                    }
+                },
+                // Dropped as a slow consumer or the socket failed
+                _ = &mut writer => break,
+            };
             println!("Message: {:?}", message);
             let message = if let Ok(message) = message {
                 message
             } else {
                 eprintln!("Message error");
-                return;
+                break;
             };
 
             let proto_package: ProtoPackage = match message {
-                Message::Binary(bytes) => {
-                    if let Ok (proto_message) = protobuf::Message::parse_from_bytes(&bytes) {
-                        proto_message
-                    } else {
-                        eprintln!("Message error");
+                Message::Binary(_) | Message::Text(_) => match encoding.decode(&message) {
+                    Ok(proto_package) => proto_package,
+                    Err(err) => {
+                        eprintln!("Message error: {}", err);
                         continue;
                     }
                 },
-                _ => {
-                    eprintln!("Message error");
-                    continue;
-                }
+                // Pings are answered by the socket itself, both only keep the
+                // connection alive
+                Message::Ping(_) | Message::Pong(_) => continue,
+                Message::Close(_) => break,
             };
 
-            if proto_package.package_type == String::from("MESSAGE") {
-                package_queue.write().await.push_back(proto_package);
-            } else {
-                eprintln!("Message error");
-                continue;
+            match &proto_package.payload {
+                Some(Payload::Message(_)) => {
+                    // Persist the message before handing it to delivery, the
+                    // sender gets an ack or error
+                    match ws_handlers::handle_message_package(&task_state, user_id, proto_package).await {
+                        Ok(packages) => task_state.package_queue.extend(packages).await,
+                        Err(err) => eprintln!("Message error: {}", err),
+                    }
+                },
+                Some(Payload::Receipt(receipt)) if receipt.kind.enum_value() == Ok(Kind::RECEIVED) => {
+                    // Let the sender know the message reached the recipient
+                    match ws_handlers::handle_received_package(&task_state, user_id, proto_package).await {
+                        Ok(Some(receipt)) => task_state.package_queue.push(receipt).await,
+                        // Blocked by the recipient, nothing to deliver
+                        Ok(None) => (),
+                        Err(err) => eprintln!("Message error: {}", err),
+                    }
+                },
+                Some(Payload::Receipt(receipt)) if receipt.kind.enum_value() == Ok(Kind::READ) => {
+                    match ws_handlers::handle_read_package(&task_state, user_id, proto_package).await {
+                        Ok(Some(receipt)) => task_state.package_queue.push(receipt).await,
+                        // Blocked by the recipient, nothing to deliver
+                        Ok(None) => (),
+                        Err(err) => eprintln!("Message error: {}", err),
+                    }
+                },
+                Some(Payload::Edit(_)) => {
+                    match ws_handlers::handle_edit_package(&task_state, user_id, proto_package).await {
+                        Ok(packages) => task_state.package_queue.extend(packages).await,
+                        Err(err) => eprintln!("Message error: {}", err),
+                    }
+                },
+                Some(Payload::Delete(_)) => {
+                    match ws_handlers::handle_delete_package(&task_state, user_id, proto_package).await {
+                        Ok(packages) => task_state.package_queue.extend(packages).await,
+                        Err(err) => eprintln!("Message error: {}", err),
+                    }
+                },
+                Some(Payload::Reaction(_)) => {
+                    match ws_handlers::handle_reaction_package(&task_state, user_id, proto_package).await {
+                        Ok(packages) => task_state.package_queue.extend(packages).await,
+                        Err(err) => eprintln!("Message error: {}", err),
+                    }
+                },
+                Some(Payload::Typing(_)) => {
+                    // Relayed, nothing is stored
+                    match ws_handlers::handle_typing_package(&task_state, user_id, proto_package).await {
+                        Ok(Some(package)) => task_state.package_queue.push(package).await,
+                        // Blocked by the recipient, nothing to deliver
+                        Ok(None) => (),
+                        Err(err) => eprintln!("Message error: {}", err),
+                    }
+                },
+                Some(Payload::PresenceSubscription(_)) => {
+                    match ws_handlers::handle_presence_subscription_package(&task_state, user_id, proto_package).await {
+                        Ok(Some(package)) => task_state.package_queue.push(package).await,
+                        // Unsubscribed or not shared with this user
+                        Ok(None) => (),
+                        Err(err) => eprintln!("Message error: {}", err),
+                    }
+                },
+                // Sent by the server only, or another handshake
+                _ => {
+                    let error = ws_handlers::handle_unexpected_package(user_id, &proto_package);
+                    task_state.package_queue.push(error).await;
+                }
             }
         }
+        // The socket is closed or idle, drop the connection from the registry
+        // Dropping its outbound queue lets the writer flush and close the socket
+        if remove_client(&task_state.clients, user_id, client_id).await.is_some() {
+            task_state.cluster.unsubscribe(&user_id).await;
+            // Still online while connected to another node
+            notify_presence(&task_state, user_id, false).await;
+        }
     };
 
+    // Hold the lock while spawning so the task can't remove the client
+    // before it is registered
+    let mut clients = state.clients.write().await;
     let client = Client {
         user_id,
-        sender: Some(sender),
+        device_id,
+        sender: Some(outbound),
         task: tokio::spawn(task),
     };
-    // Add the client to the hashmap
-    clients.write().await.insert(client_id, client);
+    // Add the connection to the user's connections
+    clients.entry(user_id).or_default().insert(client_id, client);
+    CONNECTED_CLIENTS.inc();
+    drop(clients);
+
+    // Messages queued while replaying didn't find this connection yet
+    match ws_handlers::handle_pending_packages(&state, user_id).await {
+        Ok(packages) => {
+            let packages = packages.into_iter()
+                .filter(|(id, _)| !replayed.contains(id))
+                .map(|(_, package)| package);
+            state.package_queue.extend(packages).await;
+        },
+        Err(err) => eprintln!("Replay error: {}", err),
+    }
+    match ws_handlers::handle_pending_receipts(&state, user_id).await {
+        Ok(packages) => state.package_queue.extend(packages).await,
+        Err(err) => eprintln!("Replay error: {}", err),
+    }
+}
+
+/// Answer the hello the client opens the connection with, the connection is
+/// closed when it doesn't come in time or no version is shared
+async fn handshake(
+    sender: &mut SplitSink<WebSocket, Message>,
+    receiver: &mut SplitStream<WebSocket>,
+    encoding: Encoding,
+    wait: Duration,
+) -> Result<(), String> {
+    let message = loop {
+        match timeout(wait, receiver.next()).await {
+            Ok(Some(Ok(Message::Ping(_) | Message::Pong(_)))) => continue,
+            Ok(Some(Ok(Message::Close(_)))) | Ok(Some(Err(_))) | Ok(None) => {
+                return Err("Connection closed".to_string())
+            },
+            Ok(Some(Ok(message))) => break message,
+            Err(_) => return Err("No hello".to_string()),
+        }
+    };
+    // Anything else than a hello is answered with an error
+    let package = encoding.decode(&message).unwrap_or_default();
+    let reply = ws_handlers::handle_hello_package(&package);
+    sender.send(encoding.encode(&reply)?).await
+        .map_err(|_| "Error sending package".to_string())?;
+    if reply.has_hello() {
+        Ok(())
+    } else {
+        Err("No protocol version shared".to_string())
+    }
+}
+
+/// Send the pending packages of the user straight to the socket, then the
+/// receipts missed while offline, returns the ids of the replayed messages
+async fn replay_pending(
+    state: &AppState,
+    user_id: Id,
+    device_id: Option<Id>,
+    sender: &mut SplitSink<WebSocket, Message>,
+    encoding: Encoding,
+) -> Result<Vec<Uuid>, String> {
+    let packages = ws_handlers::handle_pending_packages(state, user_id).await?;
+    let mut replayed = Vec::with_capacity(packages.len());
+    for (id, package) in packages {
+        // Encrypted messages without an envelope for the device are skipped
+        if let Some(package) = ws_handlers::handle_device_package(&package, device_id) {
+            sender.send(encoding.encode(&package)?).await
+                .map_err(|_| "Error sending package".to_string())?;
+        }
+        replayed.push(id);
+    }
+    for package in ws_handlers::handle_pending_receipts(state, user_id).await? {
+        sender.send(encoding.encode(&package)?).await
+            .map_err(|_| "Error sending package".to_string())?;
+    }
+    Ok(replayed)
+}
+
+/// Write the messages queued for a connection to its socket in the encoding
+/// of the connection, ends once the queue is dropped or when the socket fails
+/// or is too slow
+async fn write_messages(
+    mut sender: SplitSink<WebSocket, Message>,
+    mut outbound: mpsc::Receiver<Message>,
+    encoding: Encoding,
+    user_id: Id,
+) {
+    while let Some(message) = outbound.recv().await {
+        let message = match encoding.transcode(message) {
+            Ok(message) => message,
+            Err(err) => {
+                eprintln!("Message error: {}", err);
+                continue;
+            }
+        };
+        match timeout(WRITE_TIMEOUT, sender.send(message)).await {
+            Ok(Ok(_)) => (),
+            Ok(Err(_)) => return,
+            Err(_) => {
+                eprintln!("Slow connection of {}", user_id);
+                return;
+            }
+        }
+    }
+    // Close handshake, fails when the socket is already gone
+    let _ = sender.close().await;
+}
+
+/// Returns the removed connection and whether it was the last one of the
+/// user, none when it was already removed
+async fn remove_client<T>(
+    clients: &Clients<T>,
+    user_id: Id,
+    client_id: Uuid,
+) -> Option<(Client<T>, bool)> {
+    let mut clients = clients.write().await;
+    let connections = clients.get_mut(&user_id)?;
+    let client = connections.remove(&client_id)?;
+    CONNECTED_CLIENTS.dec();
+    let last = connections.is_empty();
+    if last {
+        clients.remove(&user_id);
+    }
+    Some((client, last))
+}
+
+/// Count a connection of `user_id` opening or closing, its subscribers are
+/// told when it came online or went offline
+async fn notify_presence(state: &AppState, user_id: Id, online: bool) {
+    match ws_handlers::handle_presence_change(state, user_id, online).await {
+        Ok(packages) => state.package_queue.extend(packages).await,
+        Err(err) => eprintln!("Presence error: {}", err),
+    }
 }
 
 #[cfg(test)]
-mod tests {}
\ No newline at end of file
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+    use tokio::sync::RwLock;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_remove_client() {
+        let clients: Clients<mpsc::Sender<Message>> = Arc::new(RwLock::new(HashMap::new()));
+        let user_id: Id = Uuid::new_v4().try_into().unwrap();
+        let client_ids = [Uuid::new_v4(), Uuid::new_v4()];
+        for client_id in client_ids {
+            let (sender, _) = mpsc::channel(1);
+            clients.write().await
+                .entry(user_id)
+                .or_default()
+                .insert(client_id, Client { user_id, device_id: None, sender: Some(sender), task: tokio::spawn(async {}) });
+        }
+
+        assert!(matches!(remove_client(&clients, user_id, client_ids[0]).await, Some((_, false))));
+        // Already removed
+        assert!(remove_client(&clients, user_id, client_ids[0]).await.is_none());
+        assert!(matches!(remove_client(&clients, user_id, client_ids[1]).await, Some((_, true))));
+        assert!(clients.read().await.is_empty());
+    }
+}
@@ -0,0 +1,218 @@
+use axum::extract::ws::Message;
+use deadpool_redis::Pool;
+use tokio::sync::mpsc;
+
+use common::{
+    adapter::{cluster::Cluster, state::AppState},
+    domain::{models::client::Clients, protos_schemas::proto_package::ProtoPackage},
+};
+use message::ws_handlers;
+use super::consume_event::{self, send_package, store_undelivered};
+
+
+/// Packages waiting to be published before dispatching has to wait
+pub const PUBLISH_QUEUE_SIZE: usize = 4096;
+
+/// Packages published in a single round trip to Redis
+const PUBLISH_BATCH_SIZE: usize = 256;
+
+/// Package dispatched to the local connections of its recipient, published
+/// for the connections on other nodes
+pub struct Outgoing {
+    pub package: ProtoPackage,
+    /// Whether a local connection got it
+    pub delivered: bool,
+}
+
+pub async fn execute(state: AppState, outgoing: mpsc::Receiver<Outgoing>) {
+    // Spawn a task publishing the dispatched packages
+    let publisher = state.clone();
+    tokio::spawn(publish_packages(
+        state.cluster.clone(),
+        state.cache_pool.clone(),
+        outgoing,
+        move |package| store_undelivered(&publisher, package),
+    ));
+    // Spawn a task keeping the connections of this node counted
+    tokio::spawn(refresh_node(state.clone()));
+    // Spawn a task writing the packages published by other nodes
+    let (cluster, clients) = (state.cluster.clone(), state.clients.clone());
+    tokio::spawn(relay_packages(
+        cluster,
+        clients,
+        move |package| store_undelivered(&state, package),
+    ));
+}
+
+/// Publish the packages for the connections of their recipients on other
+/// nodes, the ones no node could deliver are handed to `undelivered`
+async fn publish_packages(
+    cluster: Cluster,
+    pool: Pool,
+    mut outgoing: mpsc::Receiver<Outgoing>,
+    undelivered: impl Fn(ProtoPackage),
+) {
+    let mut batch = Vec::with_capacity(PUBLISH_BATCH_SIZE);
+    // Packages dispatched during a round trip go together in the next one
+    while outgoing.recv_many(&mut batch, PUBLISH_BATCH_SIZE).await > 0 {
+        let (packages, delivered): (Vec<_>, Vec<_>) = batch.drain(..)
+            .map(|outgoing| (outgoing.package, outgoing.delivered))
+            .unzip();
+        let received = match cluster.publish(&pool, &packages).await {
+            Ok(received) => received,
+            Err(err) => {
+                eprintln!("Cluster error: {}", err);
+                vec![0; packages.len()]
+            },
+        };
+        for ((package, delivered), received) in packages.into_iter().zip(delivered).zip(received) {
+            if !delivered && received == 0 {
+                undelivered(package);
+            }
+        }
+    }
+}
+
+/// Refresh this node for as long as it runs, once it stops the other nodes
+/// no longer count the connections it held as online
+async fn refresh_node(state: AppState) {
+    let mut interval = tokio::time::interval(ws_handlers::NODE_REFRESH_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(err) = ws_handlers::handle_node_refresh(&state).await {
+            eprintln!("Cluster error: {}", err);
+        }
+    }
+}
+
+/// Write the packages published by other nodes to the local connections
+async fn relay_packages(
+    cluster: Cluster,
+    clients: Clients<mpsc::Sender<Message>>,
+    undelivered: impl Fn(ProtoPackage),
+) {
+    while let Some(package) = cluster.incoming.pop().await {
+        match send_package(package.clone(), &clients).await {
+            Ok(_) => (),
+            // The recipient left this node since it was published
+            Err(consume_event::Error::ClientNotFound) => undelivered(package),
+            Err(err) => println!("Error sending message: {:?}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use futures_util::{SinkExt, StreamExt};
+    use protobuf::{Message as ProtoMessage, MessageField};
+    use tokio::{net::{TcpListener, TcpStream}, time::{sleep, timeout}};
+    use tokio_tungstenite::{
+        connect_async,
+        tungstenite::Message as WsMessage,
+        MaybeTlsStream,
+        WebSocketStream,
+    };
+    use uuid::Uuid;
+
+    use auth::{create_single_use_token, TokenCache, TokenData};
+    use common::{
+        adapter::{cache, config::Config},
+        domain::{
+            protos_schemas::proto_package::{
+                proto_package::Payload,
+                ProtoHello,
+                ProtoMessage as ProtoChatMessage,
+                ProtoRecipient,
+            },
+            types::{id::Id, recipient::Recipient},
+        },
+    };
+    use super::*;
+
+    type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+    /// Serve a router with its own state, as another node would, returns the
+    /// url of its WebSocket endpoint
+    async fn serve_node() -> String {
+        let app = crate::router().await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app.into_make_service()).await.unwrap();
+        });
+        format!("ws://{}/api/message/ws", addr)
+    }
+
+    /// Open a connection of `user_id` to a node and shake hands
+    async fn connect(url: &str, user_id: Id) -> Socket {
+        let secret = Config::new().secret;
+        let token = TokenData::new(&user_id.into()).token(&secret);
+        let auth_token = create_single_use_token::execute(
+            &secret,
+            &cache::create_pool().await,
+            &TokenCache(),
+            token,
+            create_single_use_token::Payload::default(),
+        ).await.unwrap();
+        let (mut socket, _) = connect_async(format!("{}?auth_token={}", url, auth_token)).await.unwrap();
+
+        let hello = ProtoPackage {
+            payload: Some(Payload::Hello(ProtoHello { versions: vec![1], ..Default::default() })),
+            ..Default::default()
+        };
+        socket.send(WsMessage::Binary(hello.write_to_bytes().unwrap())).await.unwrap();
+        assert!(next_package(&mut socket).await.has_hello());
+        socket
+    }
+
+    /// Next package received, skipping the heartbeats
+    async fn next_package(socket: &mut Socket) -> ProtoPackage {
+        loop {
+            let message = timeout(Duration::from_secs(5), socket.next()).await
+                .expect("No package in time")
+                .unwrap()
+                .unwrap();
+            if let WsMessage::Binary(data) = message {
+                return ProtoPackage::parse_from_bytes(&data).unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Postgres, MongoDB and Redis at DATABASE_URL, DOCUMENT_DB_URL and CACHE_URL"]
+    async fn test_two_nodes() {
+        let (node_a, node_b) = (serve_node().await, serve_node().await);
+        let sender: Id = Uuid::new_v4().try_into().unwrap();
+        let recipient: Id = Uuid::new_v4().try_into().unwrap();
+
+        let mut recipient_socket = connect(&node_b, recipient).await;
+        let mut sender_socket = connect(&node_a, sender).await;
+        // The recipient's node subscribes once the pending messages are replayed
+        sleep(Duration::from_millis(200)).await;
+
+        let message = ProtoPackage {
+            client_id: "two-nodes".to_string(),
+            payload: Some(Payload::Message(ProtoChatMessage {
+                recipient: MessageField::some(ProtoRecipient {
+                    recipient: Some(Recipient::User(recipient).into()),
+                    ..Default::default()
+                }),
+                message_type: "TEXT".to_string(),
+                content: b"hello from node A".to_vec(),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        sender_socket.send(WsMessage::Binary(message.write_to_bytes().unwrap())).await.unwrap();
+        let ack = next_package(&mut sender_socket).await;
+        assert!(ack.has_ack());
+        assert_eq!(ack.client_id, "two-nodes");
+
+        // Sent on node A, received on node B
+        let received = next_package(&mut recipient_socket).await;
+        assert!(received.has_message());
+        assert_eq!(received.message().content, b"hello from node A".to_vec());
+        assert_eq!(received.message().id, ack.ack().message_id);
+    }
+}
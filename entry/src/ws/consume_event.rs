@@ -1,72 +1,634 @@
-use axum::extract::ws::{Message, WebSocket};
-use futures::SinkExt;
-use futures_util::stream::SplitSink;
+use axum::extract::ws::Message;
 use protobuf::Message as ProtoMessage;
-use tokio::time::sleep;
+use tokio::sync::mpsc::{self, error::TrySendError};
 use uuid::Uuid;
-use std::time::Duration;
 
 use common::{
-    adapter::state::PackageQueue, 
+    adapter::state::AppState,
     domain::{
-        models::client::Clients, protos_schemas::proto_package::{proto_package::Owner, ProtoPackage},
+        models::client::Clients,
+        protos_schemas::proto_package::{proto_package::Owner, ProtoPackage},
+        types::id::Id,
     }
 };
+use message::ws_handlers;
+use super::cluster::{self, Outgoing, PUBLISH_QUEUE_SIZE};
 
 
-pub async fn execute(
-    clients: Clients<SplitSink<WebSocket, Message>>,
-    event_queue: PackageQueue,
-) {
-    // Spawn a task to listen for updates to the event queue
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    InvalidPackage(String),
+    ClientNotFound,
+}
+
+
+pub async fn execute(state: AppState) {
+    // Every dispatched package is published for the connections on other
+    // nodes, the publisher stores the ones no node could deliver
+    let (outgoing, published) = mpsc::channel(PUBLISH_QUEUE_SIZE);
+    cluster::execute(state.clone(), published).await;
+
+    // Spawn a task dispatching the packages as soon as they are queued
     tokio::spawn(async move {
-        loop {
-            // Wait for the next event to be pushed to the queue
-            let option_proto_package = {
-                let mut queue = event_queue.write().await;
-                queue.pop_front()
-            };
+        while let Some(proto_package) = state.package_queue.pop().await {
+            dispatch(&state, proto_package, &outgoing).await;
+        }
+    });
+}
 
-            if let Some(proto_package) = option_proto_package {
-                match send_package(proto_package.clone(), clients.clone()).await {
-                    Ok(_) => println!("Message sent"),
-                    Err(_) => println!("Error sending message"),
-                }
-            }
-            // Sleep for a bit
-            sleep(Duration::from_millis(100)).await;
+async fn dispatch(state: &AppState, proto_package: ProtoPackage, outgoing: &mpsc::Sender<Outgoing>) {
+    // Messages to a group go to every member
+    let proto_packages = match ws_handlers::handle_recipient_packages(proto_package) {
+        Ok(proto_packages) => proto_packages,
+        Err(err) => {
+            println!("Error sending message: {}", err);
+            Vec::new()
+        },
+    };
+    for proto_package in proto_packages {
+        let delivered = match send_package(proto_package.clone(), &state.clients).await {
+            Ok(_) => true,
+            Err(Error::ClientNotFound) => false,
+            Err(err) => {
+                println!("Error sending message: {:?}", err);
+                continue;
+            },
+        };
+        // The publisher runs on its own, so it holds up no recipient
+        let _ = outgoing.send(Outgoing { package: proto_package, delivered }).await;
+    }
+}
+
+/// Keep the package until the recipient connects again, without holding up
+/// the other recipients
+pub(super) fn store_undelivered(state: &AppState, proto_package: ProtoPackage) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        match ws_handlers::handle_undelivered_package(&state, proto_package).await {
+            Ok(_) => println!("Message queued"),
+            Err(err) => println!("Error queueing message: {}", err),
         }
     });
 }
 
+/// Queue the package to every live connection of the recipient user without
+/// waiting on any socket, encrypted messages to the devices they have an
+/// envelope for. Connections whose queue is full are dropped as slow
+/// consumers.
 pub async fn send_package(
-    package: ProtoPackage, 
-    clients: Clients<SplitSink<WebSocket, Message>>
-) -> Result<(), String> {
+    package: ProtoPackage,
+    clients: &Clients<mpsc::Sender<Message>>,
+) -> Result<(), Error> {
     let recipient: Result<[u8; 16], _> = match package.owner.clone() {
         Some(Owner::Recipient(r)) => r.value.try_into(),
-        _ => return Err("No recipient found".to_string()),
+        _ => return Err(Error::InvalidPackage("No recipient found".to_string())),
     };
 
-    let recipient = if let Ok(recipient) = recipient {
-        Uuid::from_bytes(recipient)
-    } else {
-        return Err("Invalid recipient".to_string());
+    let recipient: Id = match recipient.map(Uuid::from_bytes).map(Id::try_from) {
+        Ok(Ok(recipient)) => recipient,
+        _ => return Err(Error::InvalidPackage("Invalid recipient".to_string())),
     };
 
-    let mut clients = clients.write().await;
-    let client = match clients.get_mut(&recipient) {
-        Some(c) => c,
-        None => return Err("Client not found".to_string()),
+    let encode = |package: &ProtoPackage| package.write_to_bytes()
+        .map(Message::Binary)
+        .map_err(|_| Error::InvalidPackage("Error encoding package".to_string()));
+    // Only encrypted messages differ from a device to the other
+    let shared = match ws_handlers::handle_device_package(&package, None) {
+        Some(package) => Some(encode(&package)?),
+        None => None,
     };
 
-    if let Some(sender) = client.sender.as_mut() {
-        sender.send(Message::Binary(package.write_to_bytes().unwrap())).await
-            .map_err(|_| "".to_string())?;
+    // Fan out to all the user's devices
+    let mut delivered = false;
+    let mut slow_clients = Vec::new();
+    {
+        let clients = clients.read().await;
+        let connections = match clients.get(&recipient) {
+            Some(c) if !c.is_empty() => c,
+            _ => return Err(Error::ClientNotFound),
+        };
+        for (client_id, client) in connections {
+            let message = match &shared {
+                Some(message) => message.clone(),
+                None => match ws_handlers::handle_device_package(&package, client.device_id) {
+                    Some(package) => encode(&package)?,
+                    // Nothing this device can read
+                    None => continue,
+                },
+            };
+            if let Some(sender) = client.sender.as_ref() {
+                match sender.try_send(message) {
+                    Ok(_) => delivered = true,
+                    Err(TrySendError::Full(_)) => slow_clients.push(*client_id),
+                    // The connection is closing
+                    Err(TrySendError::Closed(_)) => (),
+                }
+            }
+        }
+    }
+
+    if !slow_clients.is_empty() {
+        drop_slow_clients(clients, recipient, &slow_clients).await;
     }
 
-    Ok(())
+    if delivered {
+        Ok(())
+    } else {
+        Err(Error::ClientNotFound)
+    }
+}
+
+/// Stop queueing to connections that can't keep up, their writer flushes
+/// what is queued and closes the socket, then the connection is removed
+/// like any closed one
+async fn drop_slow_clients(
+    clients: &Clients<mpsc::Sender<Message>>,
+    user_id: Id,
+    client_ids: &[Uuid],
+) {
+    let mut clients = clients.write().await;
+    if let Some(connections) = clients.get_mut(&user_id) {
+        for client_id in client_ids {
+            if let Some(client) = connections.get_mut(client_id) {
+                eprintln!("Dropping slow connection of {}", user_id);
+                client.sender = None;
+            }
+        }
+    }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use std::{collections::{HashMap, VecDeque}, sync::Arc, time::{Duration, Instant}};
+    use futures::{SinkExt, StreamExt};
+    use tokio::{sync::RwLock, time::{interval, sleep}};
+
+    use protobuf::MessageField;
+
+    use common::{
+        adapter::state::{PackageQueue, PACKAGE_QUEUE_SIZE},
+        domain::{
+            models::client::Client,
+            protos_schemas::proto_package::{
+                proto_package::Payload,
+                proto_receipt::Kind,
+                ProtoEnvelope,
+                ProtoMessage as ProtoChatMessage,
+                ProtoReceipt,
+                ProtoRecipient,
+            },
+            types::recipient::Recipient,
+        },
+    };
+    use crate::ws::client_connect::OUTBOUND_QUEUE_SIZE;
+    use super::*;
+
+    fn package_for(recipient: Id) -> ProtoPackage {
+        ProtoPackage {
+            owner: Some(Owner::Recipient(recipient.into())),
+            payload: Some(Payload::Message(Default::default())),
+            ..Default::default()
+        }
+    }
+
+    fn new_client<T>(clients: &mut HashMap<Id, HashMap<Uuid, Client<T>>>, user_id: Id, sender: T) {
+        clients.entry(user_id)
+            .or_default()
+            .insert(Uuid::new_v4(), Client {
+                user_id,
+                device_id: None,
+                sender: Some(sender),
+                task: tokio::spawn(async {}),
+            });
+    }
+
+    /// Connection of a device of `user_id`, returns what is queued to it
+    async fn new_device_client(
+        clients: &Clients<mpsc::Sender<Message>>,
+        user_id: Id,
+        device_id: Id,
+    ) -> mpsc::Receiver<Message> {
+        let (sender, receiver) = mpsc::channel(OUTBOUND_QUEUE_SIZE);
+        clients.write().await
+            .entry(user_id)
+            .or_default()
+            .insert(Uuid::new_v4(), Client {
+                user_id,
+                device_id: Some(device_id),
+                sender: Some(sender),
+                task: tokio::spawn(async {}),
+            });
+        receiver
+    }
+
+    /// Packages queued to a connection so far
+    fn queued_packages(receiver: &mut mpsc::Receiver<Message>) -> Vec<ProtoPackage> {
+        let mut packages = Vec::new();
+        while let Ok(Message::Binary(data)) = receiver.try_recv() {
+            packages.push(ProtoPackage::parse_from_bytes(&data).unwrap());
+        }
+        packages
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Postgres, MongoDB and Redis at DATABASE_URL, DOCUMENT_DB_URL and CACHE_URL"]
+    async fn test_two_device_exchange() {
+        let state = AppState::new().await;
+        let alice: Id = Uuid::new_v4().try_into().unwrap();
+        let bob: Id = Uuid::new_v4().try_into().unwrap();
+        let device = || -> Id { Uuid::new_v4().try_into().unwrap() };
+        let (alice_phone, alice_laptop, bob_phone, bob_laptop) = (device(), device(), device(), device());
+        let mut alice_phone_queue = new_device_client(&state.clients, alice, alice_phone).await;
+        let mut alice_laptop_queue = new_device_client(&state.clients, alice, alice_laptop).await;
+        let mut bob_phone_queue = new_device_client(&state.clients, bob, bob_phone).await;
+        let mut bob_laptop_queue = new_device_client(&state.clients, bob, bob_laptop).await;
+
+        // Alice's phone seals the message for every other device
+        let sealed = [
+            (alice_laptop, vec![0x00, 0xa1, 0xff]),
+            (bob_phone, vec![0xb0, 0x00, 0x01, 0x9c]),
+            (bob_laptop, vec![0xb1]),
+        ];
+        let envelopes = sealed.iter()
+            .map(|(device_id, ciphertext)| ProtoEnvelope {
+                device_id: MessageField::some((*device_id).into()),
+                ciphertext: ciphertext.clone(),
+                ..Default::default()
+            })
+            .collect();
+        let package = ProtoPackage {
+            client_id: "sealed".to_string(),
+            payload: Some(Payload::Message(ProtoChatMessage {
+                recipient: MessageField::some(ProtoRecipient {
+                    recipient: Some(Recipient::User(bob).into()),
+                    ..Default::default()
+                }),
+                message_type: "ENCRYPTED".to_string(),
+                envelopes,
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let packages = ws_handlers::handle_message_package(&state, alice, package).await.unwrap();
+        for package in packages {
+            send_package(package, &state.clients).await.unwrap();
+        }
+
+        // Each device reads its envelope as sealed, the sending one only
+        // gets the ack
+        let received = [
+            (alice_laptop, queued_packages(&mut alice_laptop_queue)),
+            (bob_phone, queued_packages(&mut bob_phone_queue)),
+            (bob_laptop, queued_packages(&mut bob_laptop_queue)),
+        ];
+        for ((device_id, packages), (_, ciphertext)) in received.into_iter().zip(sealed) {
+            let message = packages.iter().find(|package| package.has_message()).unwrap().message();
+            assert_eq!(message.message_type, "ENCRYPTED");
+            assert!(message.content.is_empty());
+            assert_eq!(message.envelopes.len(), 1);
+            assert_eq!(message.envelopes[0].device_id.0.as_deref(), Some(&device_id.into()));
+            assert_eq!(message.envelopes[0].ciphertext, ciphertext);
+        }
+        let packages = queued_packages(&mut alice_phone_queue);
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].client_id, "sealed");
+        assert!(packages[0].has_ack());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Postgres, MongoDB and Redis at DATABASE_URL, DOCUMENT_DB_URL and CACHE_URL"]
+    async fn test_receipt_for_offline_sender() {
+        let state = AppState::new().await;
+        let alice: Id = Uuid::new_v4().try_into().unwrap();
+        let bob: Id = Uuid::new_v4().try_into().unwrap();
+        let mut bob_queue = new_device_client(&state.clients, bob, Uuid::new_v4().try_into().unwrap()).await;
+
+        let package = ProtoPackage {
+            payload: Some(Payload::Message(ProtoChatMessage {
+                recipient: MessageField::some(ProtoRecipient {
+                    recipient: Some(Recipient::User(bob).into()),
+                    ..Default::default()
+                }),
+                message_type: "TEXT".to_string(),
+                content: b"hello".to_vec(),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        // Alice goes offline once the message is sent
+        for package in ws_handlers::handle_message_package(&state, alice, package).await.unwrap() {
+            let _ = send_package(package, &state.clients).await;
+        }
+        let message = queued_packages(&mut bob_queue).into_iter()
+            .find(|package| package.has_message())
+            .unwrap();
+        let message_id = message.message().id.clone();
+
+        let receipt = ProtoPackage {
+            payload: Some(Payload::Receipt(ProtoReceipt {
+                kind: Kind::RECEIVED.into(),
+                message_id: message_id.clone(),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let receipt = ws_handlers::handle_received_package(&state, bob, receipt).await
+            .unwrap()
+            .unwrap();
+        assert_eq!(send_package(receipt.clone(), &state.clients).await, Err(Error::ClientNotFound));
+        ws_handlers::handle_undelivered_package(&state, receipt).await.unwrap();
+
+        // Replayed once when Alice connects again
+        let receipts = ws_handlers::handle_pending_receipts(&state, alice).await.unwrap();
+        assert_eq!(receipts.len(), 1);
+        let receipt = receipts[0].receipt();
+        assert_eq!(receipt.kind.enum_value(), Ok(Kind::RECEIVED));
+        assert_eq!(receipt.message_id, message_id);
+        assert_eq!(receipt.user.0.as_deref(), Some(&bob.into()));
+        assert!(ws_handlers::handle_pending_receipts(&state, alice).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_send_package_fan_out() {
+        let clients: Clients<mpsc::Sender<Message>> = Arc::new(RwLock::new(HashMap::new()));
+        let user_id: Id = Uuid::new_v4().try_into().unwrap();
+        let mut receivers = Vec::new();
+        for _ in 0..2 {
+            let (sender, receiver) = mpsc::channel(1);
+            receivers.push(receiver);
+            new_client(&mut *clients.write().await, user_id, sender);
+        }
+
+        assert!(send_package(package_for(user_id), &clients).await.is_ok());
+        for receiver in receivers.iter_mut() {
+            assert!(matches!(receiver.recv().await, Some(Message::Binary(_))));
+        }
+
+        let other_user: Id = Uuid::new_v4().try_into().unwrap();
+        assert_eq!(
+            send_package(package_for(other_user), &clients).await,
+            Err(Error::ClientNotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_package_envelopes() {
+        let clients: Clients<mpsc::Sender<Message>> = Arc::new(RwLock::new(HashMap::new()));
+        let user_id: Id = Uuid::new_v4().try_into().unwrap();
+        let (phone, laptop): (Id, Id) = (Uuid::new_v4().try_into().unwrap(), Uuid::new_v4().try_into().unwrap());
+        let mut phone_queue = new_device_client(&clients, user_id, phone).await;
+        let mut laptop_queue = new_device_client(&clients, user_id, laptop).await;
+        let (sender, mut unknown_queue) = mpsc::channel(1);
+        new_client(&mut *clients.write().await, user_id, sender);
+
+        let package = ProtoPackage {
+            payload: Some(Payload::Message(ProtoChatMessage {
+                message_type: "ENCRYPTED".to_string(),
+                envelopes: vec![ProtoEnvelope {
+                    device_id: MessageField::some(phone.into()),
+                    ciphertext: vec![0xff, 0x00],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            })),
+            ..package_for(user_id)
+        };
+        assert!(send_package(package.clone(), &clients).await.is_ok());
+        assert_eq!(queued_packages(&mut phone_queue), vec![package]);
+        // Nothing the other connections could read
+        assert!(queued_packages(&mut laptop_queue).is_empty());
+        assert!(unknown_queue.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_send_package_slow_consumer() {
+        let clients: Clients<mpsc::Sender<Message>> = Arc::new(RwLock::new(HashMap::new()));
+        let user_id: Id = Uuid::new_v4().try_into().unwrap();
+        let (sender, mut receiver) = mpsc::channel(1);
+        let (slow_sender, mut slow_receiver) = mpsc::channel(1);
+        new_client(&mut *clients.write().await, user_id, sender);
+        new_client(&mut *clients.write().await, user_id, slow_sender);
+
+        assert!(send_package(package_for(user_id), &clients).await.is_ok());
+        assert!(receiver.recv().await.is_some());
+        // The slow connection still holds the first package and is dropped
+        assert!(send_package(package_for(user_id), &clients).await.is_ok());
+        assert!(receiver.recv().await.is_some());
+        assert!(slow_receiver.recv().await.is_some());
+        assert!(slow_receiver.recv().await.is_none());
+
+        let connections = clients.read().await;
+        assert_eq!(connections[&user_id].values().filter(|c| c.sender.is_some()).count(), 1);
+    }
+
+    /// Fast recipients and a slow one reading a message every 10ms
+    const FAST_USERS: usize = 100;
+    const SLOW_EVERY: usize = 10;
+
+    /// Both designs get the same packages arriving at the same pace
+    const BENCH_PACKAGES: usize = 1000;
+    const ARRIVAL_INTERVAL: Duration = Duration::from_millis(2);
+
+    /// Outcome of a benchmark run, measured on the packages delivered to the
+    /// fast recipients
+    struct Run {
+        design: &'static str,
+        delivered: usize,
+        elapsed: Duration,
+        p99: Duration,
+    }
+
+    impl Run {
+        /// `elapsed` runs from the first arrival to the last reception
+        fn new(design: &'static str, start: Instant, sent: &[Instant], received: Vec<(usize, Instant)>) -> Run {
+            let mut latencies: Vec<Duration> = received.iter()
+                .map(|(seq, at)| at.duration_since(sent[*seq]))
+                .collect();
+            latencies.sort();
+            let last = received.iter().map(|(_, at)| *at).max().unwrap_or(start);
+            Run {
+                design,
+                delivered: latencies.len(),
+                elapsed: last.duration_since(start),
+                p99: latencies.get((latencies.len() * 99 / 100).min(latencies.len().saturating_sub(1)))
+                    .copied()
+                    .unwrap_or_default(),
+            }
+        }
+    }
+
+    fn report(runs: &[Run]) {
+        println!(
+            "{} packages, one every {:?}, {} of them to a slow recipient",
+            BENCH_PACKAGES,
+            ARRIVAL_INTERVAL,
+            BENCH_PACKAGES / SLOW_EVERY,
+        );
+        println!("{:<10} {:>10} {:>12} {:>12} {:>14}", "design", "delivered", "elapsed", "packages/s", "p99 latency");
+        for run in runs {
+            println!(
+                "{:<10} {:>10} {:>12} {:>12.0} {:>14}",
+                run.design,
+                run.delivered,
+                format!("{:.2?}", run.elapsed),
+                run.delivered as f64 / run.elapsed.as_secs_f64(),
+                format!("{:.2?}", run.p99),
+            );
+        }
+    }
+
+    fn bench_package(recipient: Id, seq: usize) -> ProtoPackage {
+        ProtoPackage {
+            client_id: seq.to_string(),
+            ..package_for(recipient)
+        }
+    }
+
+    fn seq_of(message: Message) -> Option<usize> {
+        match message {
+            Message::Binary(bytes) => {
+                let package = ProtoPackage::parse_from_bytes(&bytes).ok()?;
+                package.client_id.parse().ok()
+            },
+            _ => None,
+        }
+    }
+
+    fn recipient_of(users: &[Id], slow_user: Id, seq: usize) -> Id {
+        if seq.is_multiple_of(SLOW_EVERY) { slow_user } else { users[seq % users.len()] }
+    }
+
+    async fn bench_channels() -> Run {
+        let clients: Clients<mpsc::Sender<Message>> = Arc::new(RwLock::new(HashMap::new()));
+        let users: Vec<Id> = (0..FAST_USERS).map(|_| Uuid::new_v4().try_into().unwrap()).collect();
+        let slow_user: Id = Uuid::new_v4().try_into().unwrap();
+        let mut readers = Vec::new();
+        for user_id in &users {
+            let (sender, mut receiver) = mpsc::channel(OUTBOUND_QUEUE_SIZE);
+            new_client(&mut *clients.write().await, *user_id, sender);
+            readers.push(tokio::spawn(async move {
+                let mut received = Vec::new();
+                while let Some(message) = receiver.recv().await {
+                    received.extend(seq_of(message).map(|seq| (seq, Instant::now())));
+                }
+                received
+            }));
+        }
+        let (sender, mut receiver) = mpsc::channel(OUTBOUND_QUEUE_SIZE);
+        new_client(&mut *clients.write().await, slow_user, sender);
+        tokio::spawn(async move {
+            while receiver.recv().await.is_some() {
+                sleep(Duration::from_millis(10)).await;
+            }
+        });
+
+        let queue = PackageQueue::new(PACKAGE_QUEUE_SIZE);
+        let dispatcher_queue = queue.clone();
+        let dispatcher_clients = clients.clone();
+        tokio::spawn(async move {
+            while let Some(package) = dispatcher_queue.pop().await {
+                let _ = send_package(package, &dispatcher_clients).await;
+            }
+        });
+
+        let mut arrivals = interval(ARRIVAL_INTERVAL);
+        let start = Instant::now();
+        let mut sent = Vec::with_capacity(BENCH_PACKAGES);
+        for seq in 0..BENCH_PACKAGES {
+            arrivals.tick().await;
+            sent.push(Instant::now());
+            queue.push(bench_package(recipient_of(&users, slow_user, seq), seq)).await;
+        }
+        // Wait for the queue to drain, then close the fast connections
+        while !queue.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        sleep(Duration::from_millis(10)).await;
+        clients.write().await.clear();
+        let mut received = Vec::new();
+        for reader in readers {
+            received.extend(reader.await.unwrap());
+        }
+        Run::new("channels", start, &sent, received)
+    }
+
+    /// The previous design: a polled queue under a lock, every package
+    /// delivered by awaiting each socket in turn under the registry lock
+    async fn bench_polling() -> Run {
+        type Sender = futures::channel::mpsc::Sender<Message>;
+        let clients: Clients<Sender> = Arc::new(RwLock::new(HashMap::new()));
+        let users: Vec<Id> = (0..FAST_USERS).map(|_| Uuid::new_v4().try_into().unwrap()).collect();
+        let slow_user: Id = Uuid::new_v4().try_into().unwrap();
+        let mut readers = Vec::new();
+        for user_id in &users {
+            let (sender, mut receiver) = futures::channel::mpsc::channel(OUTBOUND_QUEUE_SIZE);
+            new_client(&mut *clients.write().await, *user_id, sender);
+            readers.push(tokio::spawn(async move {
+                let mut received = Vec::new();
+                while let Some(message) = receiver.next().await {
+                    received.extend(seq_of(message).map(|seq| (seq, Instant::now())));
+                }
+                received
+            }));
+        }
+        let (sender, mut receiver) = futures::channel::mpsc::channel(OUTBOUND_QUEUE_SIZE);
+        new_client(&mut *clients.write().await, slow_user, sender);
+        tokio::spawn(async move {
+            while receiver.next().await.is_some() {
+                sleep(Duration::from_millis(10)).await;
+            }
+        });
+
+        let queue: Arc<RwLock<VecDeque<ProtoPackage>>> = Arc::new(RwLock::new(VecDeque::new()));
+        let dispatcher_queue = queue.clone();
+        let dispatcher_clients = clients.clone();
+        tokio::spawn(async move {
+            loop {
+                let package = dispatcher_queue.write().await.pop_front();
+                if let Some(package) = package {
+                    let recipient: Id = match package.owner.clone() {
+                        Some(Owner::Recipient(r)) => r.try_into().unwrap(),
+                        _ => continue,
+                    };
+                    let bytes = package.write_to_bytes().unwrap();
+                    let mut clients = dispatcher_clients.write().await;
+                    for client in clients.get_mut(&recipient).into_iter().flat_map(|c| c.values_mut()) {
+                        if let Some(sender) = client.sender.as_mut() {
+                            let _ = sender.send(Message::Binary(bytes.clone())).await;
+                        }
+                    }
+                }
+                sleep(Duration::from_millis(100)).await;
+            }
+        });
+
+        let mut arrivals = interval(ARRIVAL_INTERVAL);
+        let start = Instant::now();
+        let mut sent = Vec::with_capacity(BENCH_PACKAGES);
+        for seq in 0..BENCH_PACKAGES {
+            arrivals.tick().await;
+            sent.push(Instant::now());
+            queue.write().await.push_back(bench_package(recipient_of(&users, slow_user, seq), seq));
+        }
+        while !queue.read().await.is_empty() {
+            sleep(Duration::from_millis(10)).await;
+        }
+        sleep(Duration::from_millis(10)).await;
+        clients.write().await.clear();
+        let mut received = Vec::new();
+        for reader in readers {
+            received.extend(reader.await.unwrap());
+        }
+        Run::new("polling", start, &sent, received)
+    }
+
+    /// Throughput and p99 latency of the dispatcher against the previous
+    /// polling design on the same workload. The polling run delivers ten
+    /// packages per second at best, so it takes close to two minutes.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    #[ignore = "benchmark, run with cargo test --release -p entry bench_dispatch -- --ignored --nocapture"]
+    async fn bench_dispatch() {
+        let runs = [bench_polling().await, bench_channels().await];
+        report(&runs);
+    }
+}
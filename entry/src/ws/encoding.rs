@@ -0,0 +1,100 @@
+use axum::{extract::ws::Message, http::HeaderValue};
+use protobuf::Message as _;
+
+use common::domain::protos_schemas::{json, proto_package::ProtoPackage};
+
+
+/// Subprotocol of the protobuf binary frames, used when the client asks for
+/// none
+pub const PROTO_SUBPROTOCOL: &str = "chat.proto.v1";
+
+/// Subprotocol of the JSON text frames
+pub const JSON_SUBPROTOCOL: &str = "chat.json.v1";
+
+/// Subprotocols accepted in `Sec-WebSocket-Protocol`, by order of preference
+pub const SUBPROTOCOLS: [&str; 2] = [PROTO_SUBPROTOCOL, JSON_SUBPROTOCOL];
+
+/// Encoding of the packages on a connection. Packages are handled and
+/// dispatched as protobuf whatever the encoding, JSON connections translate
+/// them at the socket.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Proto,
+    Json,
+}
+
+impl Encoding {
+    /// Encoding of the subprotocol agreed on in the upgrade
+    pub fn from_subprotocol(subprotocol: Option<&HeaderValue>) -> Self {
+        match subprotocol {
+            Some(subprotocol) if subprotocol == JSON_SUBPROTOCOL => Encoding::Json,
+            _ => Encoding::Proto,
+        }
+    }
+
+    /// Frame carrying the package
+    pub fn encode(&self, package: &ProtoPackage) -> Result<Message, String> {
+        match self {
+            Encoding::Proto => package.write_to_bytes()
+                .map(Message::Binary)
+                .map_err(|_| "Error encoding package".to_string()),
+            Encoding::Json => Ok(Message::Text(json::to_json(package).to_string())),
+        }
+    }
+
+    /// Package carried by a data frame, frames of the other encoding are
+    /// rejected
+    pub fn decode(&self, message: &Message) -> Result<ProtoPackage, String> {
+        match (self, message) {
+            (Encoding::Proto, Message::Binary(bytes)) => ProtoPackage::parse_from_bytes(bytes)
+                .map_err(|_| "Error decoding package".to_string()),
+            (Encoding::Json, Message::Text(text)) => {
+                let value = serde_json::from_str(text).map_err(|err| err.to_string())?;
+                json::from_json(&value).map_err(|err| err.to_string())
+            },
+            _ => Err("Unexpected frame".to_string()),
+        }
+    }
+
+    /// Frame queued for the connection in its encoding, packages are queued
+    /// as protobuf binary frames
+    pub fn transcode(&self, message: Message) -> Result<Message, String> {
+        match (self, message) {
+            (Encoding::Json, message @ Message::Binary(_)) => self.encode(&Encoding::Proto.decode(&message)?),
+            (_, message) => Ok(message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::domain::protos_schemas::proto_package::{proto_package::Payload, ProtoTyping};
+    use super::*;
+
+    #[test]
+    fn test_encoding() {
+        let package = ProtoPackage {
+            client_id: "c1".to_string(),
+            payload: Some(Payload::Typing(ProtoTyping { typing: true, ..Default::default() })),
+            ..Default::default()
+        };
+        let subprotocol = HeaderValue::from_static(JSON_SUBPROTOCOL);
+        let json = Encoding::from_subprotocol(Some(&subprotocol));
+        assert_eq!(json, Encoding::Json);
+        assert_eq!(Encoding::from_subprotocol(None), Encoding::Proto);
+
+        // Both encodings carry the same package
+        let frame = json.encode(&package).unwrap();
+        assert!(matches!(frame, Message::Text(_)));
+        assert_eq!(json.decode(&frame).unwrap(), package);
+        let frame = Encoding::Proto.encode(&package).unwrap();
+        assert_eq!(Encoding::Proto.decode(&frame).unwrap(), package);
+        assert!(json.decode(&frame).is_err());
+
+        // Queued packages are translated for JSON connections only
+        let text = json.transcode(frame.clone()).unwrap();
+        assert_eq!(json.decode(&text).unwrap(), package);
+        assert_eq!(Encoding::Proto.transcode(frame.clone()).unwrap(), frame);
+        assert_eq!(json.transcode(Message::Ping(Vec::new())).unwrap(), Message::Ping(Vec::new()));
+    }
+}
@@ -1,14 +1,13 @@
 use auth::{authenticate_single_use_token, TokenCache};
 use axum::{
-    extract::{ws::{Message, WebSocket}, Query, State, WebSocketUpgrade}, 
+    extract::{Query, State, WebSocketUpgrade}, 
     http::StatusCode, 
     response::{IntoResponse, Response}
 };
-use futures::stream::SplitSink;
 
-use common::{adapter::state::{AppState, PackageQueue}, domain::models::client::Clients};
+use common::adapter::state::AppState;
 use crate::schemas::AuthWebSocket;
-use super::{client_connect, consume_event};
+use super::{client_connect, consume_event, encoding::SUBPROTOCOLS, heartbeat};
 
 
 // Websocket handlers
@@ -27,16 +26,20 @@ pub async fn ws_handler(
     } else {
         return StatusCode::UNAUTHORIZED.into_response();
     };
-    ws.on_upgrade(move |socket| {
-        client_connect::execute(state.clients, state.package_queue, user_id, socket)
+    let device_id = auth_websocket.device_id;
+    // The encoding of the packages, protobuf unless JSON is asked for
+    ws.protocols(SUBPROTOCOLS).on_upgrade(move |socket| {
+        client_connect::execute(state, user_id, device_id, socket)
     })
 }
 
 
 // Event queue
-pub async fn run_consumer_event_queue(
-    event_queue: PackageQueue,
-    clients: Clients<SplitSink<WebSocket, Message>>,
-) {
-    consume_event::execute(clients, event_queue).await;
+pub async fn run_consumer_event_queue(state: AppState) {
+    consume_event::execute(state).await;
+}
+
+// Heartbeat
+pub async fn run_heartbeat(state: AppState) {
+    heartbeat::execute(state).await;
 }
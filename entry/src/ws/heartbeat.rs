@@ -0,0 +1,68 @@
+use axum::extract::ws::Message;
+use tokio::{sync::mpsc, time::sleep};
+
+use common::{adapter::state::AppState, domain::models::client::Clients};
+
+
+pub async fn execute(state: AppState) {
+    // Spawn a task pinging every connection, the receive task of a
+    // connection that stops answering closes it after the idle timeout
+    tokio::spawn(async move {
+        loop {
+            sleep(state.config.ws_heartbeat_interval).await;
+            ping_clients(&state.clients).await;
+        }
+    });
+}
+
+/// Queue a ping to every live connection, returns how many were pinged.
+/// Connections with a full queue are skipped, the dispatcher drops them.
+pub async fn ping_clients(clients: &Clients<mpsc::Sender<Message>>) -> usize {
+    let mut pinged = 0;
+    let clients = clients.read().await;
+    for (user_id, connections) in clients.iter() {
+        for client in connections.values() {
+            if let Some(sender) = client.sender.as_ref() {
+                match sender.try_send(Message::Ping(Vec::new())) {
+                    Ok(_) => pinged += 1,
+                    Err(_) => eprintln!("Error pinging a connection of {}", user_id),
+                }
+            }
+        }
+    }
+    pinged
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+    use tokio::sync::RwLock;
+    use uuid::Uuid;
+
+    use common::domain::{models::client::Client, types::id::Id};
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ping_clients() {
+        let clients: Clients<mpsc::Sender<Message>> = Arc::new(RwLock::new(HashMap::new()));
+        let user_id: Id = Uuid::new_v4().try_into().unwrap();
+        let (sender, mut receiver) = mpsc::channel(1);
+        let (dead_sender, dead_receiver) = mpsc::channel(1);
+        drop(dead_receiver);
+        for sender in [sender, dead_sender] {
+            clients.write().await
+                .entry(user_id)
+                .or_default()
+                .insert(Uuid::new_v4(), Client {
+                    user_id,
+                    device_id: None,
+                    sender: Some(sender),
+                    task: tokio::spawn(async {}),
+                });
+        }
+
+        // The dead connection is left to its receive task
+        assert_eq!(ping_clients(&clients).await, 1);
+        assert!(matches!(receiver.recv().await, Some(Message::Ping(_))));
+    }
+}
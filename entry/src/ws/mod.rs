@@ -1,3 +1,6 @@
 pub mod client_connect;
+pub mod cluster;
 pub mod consume_event;
-pub mod handler;
\ No newline at end of file
+pub mod encoding;
+pub mod handler;
+pub mod heartbeat;
\ No newline at end of file
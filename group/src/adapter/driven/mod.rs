@@ -0,0 +1 @@
+pub mod persistence;
\ No newline at end of file
@@ -0,0 +1 @@
+pub mod sqlx;
\ No newline at end of file
@@ -0,0 +1,311 @@
+use sqlx::{PgExecutor, Postgres, Pool, Transaction};
+use uuid::Uuid;
+
+use common::domain::types::id::Id;
+use group_repository::GroupRepositoryTrait;
+use crate::application::port::driven::group_repository::{self, UpdateGroup};
+use crate::domain::{group::{Departure, Group, Member, NewGroup}, types::role::Role};
+use super::models::group::{GroupDB, GroupMemberDB};
+
+
+pub struct GroupRepository();
+
+impl GroupRepositoryTrait<Pool<Postgres>> for GroupRepository {
+    async fn find_by_id(&self, conn: &Pool<Postgres>, id: Id) -> Result<Group, group_repository::Error> {
+        let group = sqlx::query_as!(
+            GroupDB,
+            r#"
+                SELECT * FROM groups WHERE id = $1;
+            "#,
+            &Uuid::from(id)
+        ).fetch_one(conn).await;
+
+        let group = match group {
+            Ok(group) => group,
+            Err(sqlx::Error::RowNotFound) => return Err(group_repository::Error::NotFound),
+            Err(_) => return Err(group_repository::Error::DatabaseError),
+        };
+
+        match find_members(conn, group.id).await {
+            Ok(members) => Ok(group.into_group(members)),
+            Err(_) => Err(group_repository::Error::DatabaseError),
+        }
+    }
+
+    async fn find_by_member(&self, conn: &Pool<Postgres>, user_id: Id) -> Result<Vec<Group>, group_repository::Error> {
+        let groups = sqlx::query_as!(
+            GroupDB,
+            r#"
+                SELECT g.* FROM groups AS g
+                JOIN groups_members AS gm ON gm.group_id = g.id
+                WHERE gm.user_id = $1
+                ORDER BY g.created_at;
+            "#,
+            &Uuid::from(user_id)
+        ).fetch_all(conn).await;
+
+        let groups = match groups {
+            Ok(groups) => groups,
+            Err(_) => return Err(group_repository::Error::DatabaseError),
+        };
+
+        let mut result = Vec::with_capacity(groups.len());
+        for group in groups {
+            match find_members(conn, group.id).await {
+                Ok(members) => result.push(group.into_group(members)),
+                Err(_) => return Err(group_repository::Error::DatabaseError),
+            }
+        }
+        Ok(result)
+    }
+
+    async fn create(&self, conn: &Pool<Postgres>, new_group: NewGroup) -> Result<Group, group_repository::Error> {
+        let mut tx = conn.begin().await
+            .map_err(|_| group_repository::Error::DatabaseError)?;
+
+        let group = sqlx::query_as!(
+            GroupDB,
+            r#"
+                INSERT INTO groups (id, name)
+                VALUES ($1, $2)
+                RETURNING *;
+            "#,
+            Uuid::new_v4(),
+            String::from(new_group.name)
+        ).fetch_one(&mut *tx).await
+            .map_err(|_| group_repository::Error::DatabaseError)?;
+
+        sqlx::query!(
+            r#"
+                INSERT INTO groups_members (group_id, user_id, role)
+                VALUES ($1, $2, 'OWNER');
+            "#,
+            group.id,
+            &Uuid::from(new_group.owner)
+        ).execute(&mut *tx).await
+            .map_err(to_repository_error)?;
+
+        let members: Vec<Uuid> = new_group.members.into_iter().map(Uuid::from).collect();
+        sqlx::query!(
+            r#"
+                INSERT INTO groups_members (group_id, user_id)
+                SELECT $1, * FROM UNNEST($2::UUID[])
+                ON CONFLICT DO NOTHING;
+            "#,
+            group.id,
+            &members
+        ).execute(&mut *tx).await
+            .map_err(to_repository_error)?;
+
+        tx.commit().await
+            .map_err(|_| group_repository::Error::DatabaseError)?;
+
+        self.find_by_id(conn, group.id.try_into().unwrap()).await
+    }
+
+    async fn update(&self, conn: &Pool<Postgres>, update_group: UpdateGroup) -> Result<Group, group_repository::Error> {
+        let result = sqlx::query!(
+            r#"
+                UPDATE groups
+                SET name = COALESCE($2, name), updated_at = NOW()
+                WHERE id = $1;
+            "#,
+            &Uuid::from(update_group.id),
+            update_group.name.map(String::from)
+        ).execute(conn).await;
+
+        match result {
+            Ok(result) if result.rows_affected() > 0 => self.find_by_id(conn, update_group.id).await,
+            Ok(_) => Err(group_repository::Error::NotFound),
+            Err(_) => Err(group_repository::Error::DatabaseError),
+        }
+    }
+
+    async fn add_member(&self, conn: &Pool<Postgres>, id: Id, user_id: Id) -> Result<Group, group_repository::Error> {
+        sqlx::query!(
+            r#"
+                INSERT INTO groups_members (group_id, user_id)
+                VALUES ($1, $2)
+                ON CONFLICT DO NOTHING;
+            "#,
+            &Uuid::from(id),
+            &Uuid::from(user_id)
+        ).execute(conn).await
+            .map_err(to_repository_error)?;
+
+        self.find_by_id(conn, id).await
+    }
+
+    async fn remove_member(&self, conn: &Pool<Postgres>, id: Id, user_id: Id, removed_by: Id) -> Result<Departure, group_repository::Error> {
+        let mut tx = conn.begin().await
+            .map_err(|_| group_repository::Error::DatabaseError)?;
+        let group = find_locked(&mut tx, id).await?;
+
+        let role = match group.member(&user_id) {
+            Some(member) => member.role,
+            None => return Err(group_repository::Error::NotFound),
+        };
+        // Anybody can remove themselves, that is leaving the group
+        if removed_by != user_id {
+            match group.member(&removed_by) {
+                Some(member) if member.role.can_remove(&role) => (),
+                Some(_) => return Err(group_repository::Error::Forbidden),
+                None => return Err(group_repository::Error::NotFound),
+            }
+        }
+        // Promote the successor first so the group always has an owner
+        let new_owner = match group.next_owner() {
+            Some(next_owner) if role == Role::Owner => Some(next_owner.user_id),
+            _ => None,
+        };
+        if let Some(new_owner) = new_owner {
+            sqlx::query!(
+                r#"
+                    UPDATE groups_members SET role = $3
+                    WHERE group_id = $1 AND user_id = $2;
+                "#,
+                &Uuid::from(id),
+                &Uuid::from(new_owner),
+                String::from(Role::Owner)
+            ).execute(&mut *tx).await
+                .map_err(|_| group_repository::Error::DatabaseError)?;
+        }
+
+        sqlx::query!(
+            r#"
+                DELETE FROM groups_members WHERE group_id = $1 AND user_id = $2;
+            "#,
+            &Uuid::from(id),
+            &Uuid::from(user_id)
+        ).execute(&mut *tx).await
+            .map_err(|_| group_repository::Error::DatabaseError)?;
+
+        let left_empty = group.members.len() == 1;
+        if left_empty {
+            sqlx::query!(
+                r#"
+                    DELETE FROM groups WHERE id = $1;
+                "#,
+                &Uuid::from(id)
+            ).execute(&mut *tx).await
+                .map_err(|_| group_repository::Error::DatabaseError)?;
+        }
+
+        tx.commit().await
+            .map_err(|_| group_repository::Error::DatabaseError)?;
+
+        if left_empty {
+            return Ok(Departure { group: None, new_owner: None });
+        }
+        let group = self.find_by_id(conn, id).await?;
+        Ok(Departure { group: Some(group), new_owner })
+    }
+
+    async fn change_role(&self, conn: &Pool<Postgres>, id: Id, changed_by: Id, member: Member) -> Result<bool, group_repository::Error> {
+        let mut tx = conn.begin().await
+            .map_err(|_| group_repository::Error::DatabaseError)?;
+        let group = find_locked(&mut tx, id).await?;
+
+        let (role, from) = match (group.member(&changed_by), group.member(&member.user_id)) {
+            (Some(changer), Some(changed)) => (changer.role, changed.role),
+            _ => return Err(group_repository::Error::NotFound),
+        };
+        if !role.can_change_role(&from, &member.role) {
+            return Err(group_repository::Error::Forbidden);
+        }
+        if from == member.role {
+            return Ok(false);
+        }
+
+        sqlx::query!(
+            r#"
+                UPDATE groups_members SET role = $3
+                WHERE group_id = $1 AND user_id = $2;
+            "#,
+            &Uuid::from(id),
+            &Uuid::from(member.user_id),
+            String::from(member.role)
+        ).execute(&mut *tx).await
+            .map_err(|_| group_repository::Error::DatabaseError)?;
+
+        tx.commit().await
+            .map_err(|_| group_repository::Error::DatabaseError)?;
+        Ok(true)
+    }
+
+    async fn set_roles(&self, conn: &Pool<Postgres>, id: Id, members: Vec<Member>) -> Result<(), group_repository::Error> {
+        let mut tx = conn.begin().await
+            .map_err(|_| group_repository::Error::DatabaseError)?;
+
+        for member in members {
+            let result = sqlx::query!(
+                r#"
+                    UPDATE groups_members SET role = $3
+                    WHERE group_id = $1 AND user_id = $2;
+                "#,
+                &Uuid::from(id),
+                &Uuid::from(member.user_id),
+                String::from(member.role)
+            ).execute(&mut *tx).await
+                .map_err(|_| group_repository::Error::DatabaseError)?;
+
+            if result.rows_affected() == 0 {
+                return Err(group_repository::Error::NotFound);
+            }
+        }
+
+        tx.commit().await
+            .map_err(|_| group_repository::Error::DatabaseError)
+    }
+
+    async fn delete(&self, conn: &Pool<Postgres>, id: Id) -> Result<(), group_repository::Error> {
+        let result = sqlx::query!(
+            r#"
+                DELETE FROM groups WHERE id = $1;
+            "#,
+            &Uuid::from(id)
+        ).execute(conn).await;
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(_) => Err(group_repository::Error::DatabaseError)
+        }
+    }
+}
+
+/// Group with its members, its row locked until the end of the transaction
+async fn find_locked(tx: &mut Transaction<'_, Postgres>, id: Id) -> Result<Group, group_repository::Error> {
+    let group = sqlx::query_as!(
+        GroupDB,
+        r#"
+            SELECT * FROM groups WHERE id = $1 FOR UPDATE;
+        "#,
+        &Uuid::from(id)
+    ).fetch_one(&mut **tx).await;
+    let group = match group {
+        Ok(group) => group,
+        Err(sqlx::Error::RowNotFound) => return Err(group_repository::Error::NotFound),
+        Err(_) => return Err(group_repository::Error::DatabaseError),
+    };
+    let members = find_members(&mut **tx, group.id).await
+        .map_err(|_| group_repository::Error::DatabaseError)?;
+    Ok(group.into_group(members))
+}
+
+async fn find_members(conn: impl PgExecutor<'_>, group_id: Uuid) -> Result<Vec<GroupMemberDB>, sqlx::Error> {
+    sqlx::query_as!(
+        GroupMemberDB,
+        r#"
+            SELECT user_id, role FROM groups_members WHERE group_id = $1 ORDER BY created_at;
+        "#,
+        group_id
+    ).fetch_all(conn).await
+}
+
+/// Memberships of users that do not exist violate the users foreign key
+fn to_repository_error(err: sqlx::Error) -> group_repository::Error {
+    match err.as_database_error().and_then(|err| err.code()) {
+        Some(code) if code == "23503" => group_repository::Error::NotFound,
+        _ => group_repository::Error::DatabaseError,
+    }
+}
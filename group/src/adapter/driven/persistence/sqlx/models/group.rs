@@ -0,0 +1,34 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::domain::group::{Group, Member};
+
+
+pub struct GroupDB {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+pub struct GroupMemberDB {
+    pub user_id: Uuid,
+    pub role: String,
+}
+
+impl GroupDB {
+    pub fn into_group(self, members: Vec<GroupMemberDB>) -> Group {
+        Group {
+            id: self.id.try_into().unwrap(),
+            name: self.name.try_into().unwrap(),
+            members: members.into_iter()
+                .map(|m| Member {
+                    user_id: m.user_id.try_into().unwrap(),
+                    role: m.role.try_into().unwrap(),
+                })
+                .collect(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
@@ -0,0 +1 @@
+pub mod group;
\ No newline at end of file
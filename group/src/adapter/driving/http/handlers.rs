@@ -0,0 +1,282 @@
+use axum::extract::{Path, State};
+use axum::Json;
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+
+use common::{
+    adapter::{response_schemas::JsonResponse, state::{AppState, DocumentClient}},
+    domain::types::id::Id,
+};
+use crate::{
+    application::{
+        port::driven::group_history::GroupHistoryTrait,
+        use_cases::{
+            add_member, change_role, create_group, get_group, get_groups, leave_group,
+            remove_member, rename_group, transfer_ownership,
+        },
+    },
+    domain::group::Group,
+};
+
+// Adapters
+use crate::adapter::driven::persistence::sqlx::group_repository::GroupRepository;
+
+use super::schemas::{IdJson, NewGroupJson, RoleJson, UpdateGroupJson};
+
+
+pub async fn handle_get_groups(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+) -> JsonResponse<Vec<Group>> {
+    match get_groups::execute(
+        &state.db_sql_pool,
+        &GroupRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+    )
+    .await
+    {
+        Ok(groups) => JsonResponse::new_ok(groups),
+        Err(err) => match err {
+            get_groups::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            get_groups::Error::DatabaseError => JsonResponse::new_int_ser_err(0, "".to_string()),
+        },
+    }
+}
+
+pub async fn handle_create_group(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Json(group_info): Json<NewGroupJson>,
+) -> JsonResponse<Group> {
+    match create_group::execute(
+        &state.db_sql_pool,
+        &GroupRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+        create_group::Payload {
+            name: group_info.name,
+            members: group_info.members,
+        },
+    )
+    .await
+    {
+        Ok(group) => JsonResponse::new_ok(group),
+        Err(err) => match err {
+            create_group::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            create_group::Error::NotFound => {
+                JsonResponse::new_not_found_err(0, "Member not found".to_string())
+            }
+            create_group::Error::DatabaseError => JsonResponse::new_int_ser_err(0, "".to_string()),
+        },
+    }
+}
+
+pub async fn handle_get_group(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<Id>,
+) -> JsonResponse<Group> {
+    match get_group::execute(
+        &state.db_sql_pool,
+        &GroupRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+        get_group::Payload { id },
+    )
+    .await
+    {
+        Ok(group) => JsonResponse::new_ok(group),
+        Err(err) => match err {
+            get_group::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            get_group::Error::NotFound => JsonResponse::new_not_found_err(0, "".to_string()),
+            get_group::Error::DatabaseError => JsonResponse::new_int_ser_err(0, "".to_string()),
+        },
+    }
+}
+
+pub async fn handle_rename_group(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<Id>,
+    Json(group_info): Json<UpdateGroupJson>,
+) -> JsonResponse<Group> {
+    match rename_group::execute(
+        &state.db_sql_pool,
+        &GroupRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+        rename_group::Payload { id, name: group_info.name },
+    )
+    .await
+    {
+        Ok(group) => JsonResponse::new_ok(group),
+        Err(err) => match err {
+            rename_group::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            rename_group::Error::NotFound => JsonResponse::new_not_found_err(0, "".to_string()),
+            rename_group::Error::Forbidden => JsonResponse::new_forbidden_err(0, "".to_string()),
+            rename_group::Error::DatabaseError => JsonResponse::new_int_ser_err(0, "".to_string()),
+        },
+    }
+}
+
+pub async fn handle_add_member(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<Id>,
+    Json(member): Json<IdJson>,
+) -> JsonResponse<Group> {
+    match add_member::execute(
+        &state.db_sql_pool,
+        &GroupRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+        add_member::Payload { id, user_id: member.id },
+    )
+    .await
+    {
+        Ok(group) => JsonResponse::new_ok(group),
+        Err(err) => match err {
+            add_member::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            add_member::Error::NotFound => JsonResponse::new_not_found_err(0, "".to_string()),
+            add_member::Error::Forbidden => JsonResponse::new_forbidden_err(0, "".to_string()),
+            add_member::Error::DatabaseError => JsonResponse::new_int_ser_err(0, "".to_string()),
+        },
+    }
+}
+
+/// `H` records the ownership moving to another member when the owner removes
+/// themselves
+pub async fn handle_remove_member<H: GroupHistoryTrait<DocumentClient> + Default>(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Path((id, user_id)): Path<(Id, Id)>,
+) -> JsonResponse<String> {
+    match remove_member::execute(
+        &state.db_sql_pool,
+        &GroupRepository(),
+        &state.db_document_client,
+        &H::default(),
+        &state.config.secret,
+        &token.token().to_string(),
+        remove_member::Payload { id, user_id },
+    )
+    .await
+    {
+        Ok(_) => JsonResponse::new_ok(String::from("OK")),
+        Err(err) => match err {
+            remove_member::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            remove_member::Error::NotFound => JsonResponse::new_not_found_err(0, "".to_string()),
+            remove_member::Error::Forbidden => JsonResponse::new_forbidden_err(0, "".to_string()),
+            remove_member::Error::DatabaseError => JsonResponse::new_int_ser_err(0, "".to_string()),
+        },
+    }
+}
+
+/// `H` records the ownership moving to another member when the owner leaves
+pub async fn handle_leave_group<H: GroupHistoryTrait<DocumentClient> + Default>(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<Id>,
+) -> JsonResponse<String> {
+    match leave_group::execute(
+        &state.db_sql_pool,
+        &GroupRepository(),
+        &state.db_document_client,
+        &H::default(),
+        &state.config.secret,
+        &token.token().to_string(),
+        leave_group::Payload { id },
+    )
+    .await
+    {
+        Ok(_) => JsonResponse::new_ok(String::from("OK")),
+        Err(err) => match err {
+            leave_group::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            leave_group::Error::NotFound => JsonResponse::new_not_found_err(0, "".to_string()),
+            leave_group::Error::DatabaseError => JsonResponse::new_int_ser_err(0, "".to_string()),
+        },
+    }
+}
+
+/// `H` records the role change in the group history
+pub async fn handle_change_role<H: GroupHistoryTrait<DocumentClient> + Default>(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Path((id, user_id)): Path<(Id, Id)>,
+    Json(role): Json<RoleJson>,
+) -> JsonResponse<Group> {
+    match change_role::execute(
+        &state.db_sql_pool,
+        &GroupRepository(),
+        &state.db_document_client,
+        &H::default(),
+        &state.config.secret,
+        &token.token().to_string(),
+        change_role::Payload { id, user_id, role: role.role },
+    )
+    .await
+    {
+        Ok(group) => JsonResponse::new_ok(group),
+        Err(err) => match err {
+            change_role::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            change_role::Error::NotFound => JsonResponse::new_not_found_err(0, "".to_string()),
+            change_role::Error::Forbidden => JsonResponse::new_forbidden_err(0, "".to_string()),
+            change_role::Error::DatabaseError => JsonResponse::new_int_ser_err(0, "".to_string()),
+        },
+    }
+}
+
+/// `H` records the role changes in the group history
+pub async fn handle_transfer_ownership<H: GroupHistoryTrait<DocumentClient> + Default>(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<Id>,
+    Json(member): Json<IdJson>,
+) -> JsonResponse<Group> {
+    match transfer_ownership::execute(
+        &state.db_sql_pool,
+        &GroupRepository(),
+        &state.db_document_client,
+        &H::default(),
+        &state.config.secret,
+        &token.token().to_string(),
+        transfer_ownership::Payload { id, user_id: member.id },
+    )
+    .await
+    {
+        Ok(group) => JsonResponse::new_ok(group),
+        Err(err) => match err {
+            transfer_ownership::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            transfer_ownership::Error::NotFound => {
+                JsonResponse::new_not_found_err(0, "".to_string())
+            }
+            transfer_ownership::Error::Forbidden => {
+                JsonResponse::new_forbidden_err(0, "".to_string())
+            }
+            transfer_ownership::Error::DatabaseError => {
+                JsonResponse::new_int_ser_err(0, "".to_string())
+            }
+        },
+    }
+}
@@ -0,0 +1,31 @@
+use common::domain::types::id::Id;
+use serde::{Deserialize, Serialize};
+use crate::domain::types::{group_name::GroupName, role::Role};
+
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewGroupJson {
+    pub name: GroupName,
+    #[serde(default)]
+    pub members: Vec<Id>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateGroupJson {
+    pub name: GroupName,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdJson {
+    pub id: Id,
+}
+
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleJson {
+    pub role: Role,
+}
@@ -0,0 +1,2 @@
+pub mod driven;
+pub mod driving;
\ No newline at end of file
@@ -0,0 +1,21 @@
+use common::domain::types::group::Group;
+use crate::domain::group::RoleChange;
+
+
+pub enum Error {
+    DatabaseError,
+}
+
+pub trait GroupHistoryTrait<T> {
+    /// Record a role change as a system message in the group history
+    /// 
+    /// # Parameters
+    /// - `conn` - The connection to the history storage
+    /// - `group` - The group, with its members after the change
+    /// - `change` - The role change
+    /// 
+    /// # Returns
+    /// - `Ok(())` - The change was recorded
+    /// - `Err(Error)` - An error occurred
+    fn add_role_change(&self, conn: &T, group: Group, change: RoleChange) -> impl std::future::Future<Output = Result<(), Error>> + Send;
+}
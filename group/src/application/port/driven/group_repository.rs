@@ -0,0 +1,136 @@
+use common::domain::types::id::Id;
+use crate::domain::{group::{Departure, Group, Member, NewGroup}, types::group_name::GroupName};
+
+
+pub enum Error {
+    NotFound,
+    /// The member acting on the group doesn't have the role to
+    Forbidden,
+    DatabaseError,
+}
+
+pub struct UpdateGroup {
+    pub id: Id,
+    pub name: Option<GroupName>,
+}
+
+pub trait GroupRepositoryTrait<T> {
+    /// Get a group by its id
+    /// 
+    /// # Parameters
+    /// - `conn` - The connection to the database
+    /// - `id` - The id of the group
+    /// 
+    /// # Returns
+    /// - `Ok(Group)` - The group with its members
+    /// - `Err(Error)` - An error occurred
+    fn find_by_id(&self, conn: &T, id: Id) -> impl std::future::Future<Output = Result<Group, Error>> + Send;
+
+    /// Get all groups a user is a member of
+    /// 
+    /// # Parameters
+    /// - `conn` - The connection to the database
+    /// - `user_id` - The id of the user
+    /// 
+    /// # Returns
+    /// - `Ok(Vec<Group>)` - The groups
+    /// - `Err(Error)` - An error occurred
+    fn find_by_member(&self, conn: &T, user_id: Id) -> impl std::future::Future<Output = Result<Vec<Group>, Error>> + Send;
+
+    /// Create a new group owned by `new_group.owner` along with its members
+    /// 
+    /// # Parameters
+    /// - `conn` - The connection to the database
+    /// - `new_group` - The new group
+    /// 
+    /// # Returns
+    /// - `Ok(Group)` - The created group
+    /// - `Err(Error::NotFound)` - A member does not exist
+    /// - `Err(Error)` - An error occurred
+    fn create(&self, conn: &T, new_group: NewGroup) -> impl std::future::Future<Output = Result<Group, Error>> + Send;
+
+    /// Update a group
+    /// 
+    /// # Parameters
+    /// - `conn` - The connection to the database
+    /// - `update_group` - The update group
+    /// 
+    /// # Returns
+    /// - `Ok(Group)` - The updated group
+    /// - `Err(Error)` - An error occurred
+    fn update(&self, conn: &T, update_group: UpdateGroup) -> impl std::future::Future<Output = Result<Group, Error>> + Send;
+
+    /// Add a member to a group, adding an existing member does nothing
+    /// 
+    /// # Parameters
+    /// - `conn` - The connection to the database
+    /// - `id` - The id of the group
+    /// - `user_id` - The id of the new member
+    /// 
+    /// # Returns
+    /// - `Ok(Group)` - The updated group
+    /// - `Err(Error::NotFound)` - The group or the user does not exist
+    /// - `Err(Error)` - An error occurred
+    fn add_member(&self, conn: &T, id: Id, user_id: Id) -> impl std::future::Future<Output = Result<Group, Error>> + Send;
+
+    /// Remove a member from a group, handing the ownership over to the next
+    /// owner when the owner leaves and deleting the group once nobody is left
+    /// in it. The group is locked meanwhile, concurrent changes to it wait
+    /// and then see its new members. Removing another member takes a role
+    /// above theirs, checked under the lock.
+    /// 
+    /// # Parameters
+    /// - `conn` - The connection to the database
+    /// - `id` - The id of the group
+    /// - `user_id` - The id of the member
+    /// - `removed_by` - The id of the member removing them, themselves when
+    ///   leaving
+    /// 
+    /// # Returns
+    /// - `Ok(Departure)` - The group left and its new owner, if any
+    /// - `Err(Error::NotFound)` - One of the users is not a member of the group
+    /// - `Err(Error::Forbidden)` - `removed_by` can't remove the member
+    /// - `Err(Error)` - An error occurred
+    fn remove_member(&self, conn: &T, id: Id, user_id: Id, removed_by: Id) -> impl std::future::Future<Output = Result<Departure, Error>> + Send;
+
+    /// Change the role of a member as `changed_by`, the group is locked
+    /// meanwhile and the change checked against the current roles
+    /// 
+    /// # Parameters
+    /// - `conn` - The connection to the database
+    /// - `id` - The id of the group
+    /// - `changed_by` - The id of the member changing the role
+    /// - `member` - The member with their new role
+    /// 
+    /// # Returns
+    /// - `Ok(bool)` - Whether the role changed, false when the member already had it
+    /// - `Err(Error::NotFound)` - One of the users is not a member of the group
+    /// - `Err(Error::Forbidden)` - `changed_by` can't give the member that role
+    /// - `Err(Error)` - An error occurred
+    fn change_role(&self, conn: &T, id: Id, changed_by: Id, member: Member) -> impl std::future::Future<Output = Result<bool, Error>> + Send;
+
+    /// Set the role of several members at once, either all of them change
+    /// or none does
+    /// 
+    /// # Parameters
+    /// - `conn` - The connection to the database
+    /// - `id` - The id of the group
+    /// - `members` - The members with their new role
+    /// 
+    /// # Returns
+    /// - `Ok(())` - The roles were changed
+    /// - `Err(Error::NotFound)` - A user is not a member of the group
+    /// - `Err(Error)` - An error occurred
+    fn set_roles(&self, conn: &T, id: Id, members: Vec<Member>) -> impl std::future::Future<Output = Result<(), Error>> + Send;
+
+    /// Delete a group and its memberships
+    /// 
+    /// # Parameters
+    /// - `conn` - The connection to the database
+    /// - `id` - The id of the group
+    ///
+    /// # Returns
+    /// - `Ok(())` - The group was deleted
+    /// - `Err(Error)` - An error occurred
+    fn delete(&self, conn: &T, id: Id) -> impl std::future::Future<Output = Result<(), Error>> + Send;
+}
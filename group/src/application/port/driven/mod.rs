@@ -0,0 +1,2 @@
+pub mod group_repository;
+pub mod group_history;
\ No newline at end of file
@@ -0,0 +1 @@
+pub mod driven;
\ No newline at end of file
@@ -0,0 +1,137 @@
+use auth::TokenData;
+use common::domain::types::id::Id;
+use crate::{
+    application::port::driven::{
+        group_history::GroupHistoryTrait,
+        group_repository::{GroupRepositoryTrait, Error as GroupRepositoryError},
+    },
+    domain::{group::{Group, Member, RoleChange}, types::role::Role},
+};
+
+
+pub enum Error {
+    NotFound,
+    DatabaseError,
+    Unauthorized,
+    Forbidden,
+}
+
+pub struct Payload {
+    pub id: Id,
+    pub user_id: Id,
+    pub role: Role,
+}
+
+pub async fn execute<T, U>(
+    conn: &T,
+    repo: &impl GroupRepositoryTrait<T>,
+    history_conn: &U,
+    history: &impl GroupHistoryTrait<U>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<Group, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+
+    // Checked against the roles of the locked group
+    let member = Member { user_id: payload.user_id, role: payload.role };
+    let changed = match repo.change_role(conn, payload.id, user_id, member).await {
+        Ok(changed) => changed,
+        Err(e) => return match e {
+            GroupRepositoryError::DatabaseError => Err(Error::DatabaseError),
+            GroupRepositoryError::NotFound => Err(Error::NotFound),
+            GroupRepositoryError::Forbidden => Err(Error::Forbidden),
+        },
+    };
+
+    let group = repo.find_by_id(conn, payload.id).await
+        .map_err(|_| Error::DatabaseError)?;
+    if changed {
+        // The role already changed, a line missing from the history doesn't
+        // undo it
+        let change = RoleChange { user_id: payload.user_id, role: payload.role, changed_by: Some(user_id) };
+        if history.add_role_change(history_conn, (&group).into(), change).await.is_err() {
+            eprintln!("Error recording a role change in group {}", payload.id);
+        }
+    }
+
+    Ok(group)
+}
+
+#[cfg(test)]
+mod test {
+    use common::{adapter::db::create_pool, domain::types::group::Group as GroupRecipient};
+    use sqlx::{Pool, Postgres};
+    use uuid::Uuid;
+
+    use crate::{
+        adapter::driven::persistence::sqlx::group_repository::GroupRepository,
+        application::{
+            port::driven::group_history,
+            use_cases::{create_group, remove_member},
+        },
+    };
+    use super::*;
+
+    // Base64, like the SECRET_KEY of the config
+    const SECRET: &[u8] = b"c2VjcmV0";
+
+    /// History storage that is down
+    struct History();
+
+    impl GroupHistoryTrait<()> for History {
+        async fn add_role_change(&self, _conn: &(), _group: GroupRecipient, _change: RoleChange) -> Result<(), group_history::Error> {
+            Err(group_history::Error::DatabaseError)
+        }
+    }
+
+    async fn new_user(pool: &Pool<Postgres>) -> (Id, String) {
+        let user_id: Uuid = sqlx::query_scalar("INSERT INTO auths (hashed_password) VALUES ('') RETURNING user_id")
+            .fetch_one(pool).await.unwrap();
+        (user_id.try_into().unwrap(), TokenData::new(&user_id).token(SECRET))
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Postgres at DATABASE_URL"]
+    async fn test_change_role() {
+        let pool = create_pool().await;
+        let repo = GroupRepository();
+        let (_, owner_token) = new_user(&pool).await;
+        let (admin, admin_token) = new_user(&pool).await;
+        let (member, _) = new_user(&pool).await;
+        let payload = create_group::Payload {
+            name: "Group".to_string().try_into().unwrap(),
+            members: vec![admin, member],
+        };
+        let group = create_group::execute(&pool, &repo, SECRET, &owner_token, payload).await
+            .unwrap_or_else(|_| panic!("Error creating the group"));
+        let change = |user_id, role| Payload { id: group.id, user_id, role };
+
+        // Changed even though the history couldn't record it
+        let changed = execute(&pool, &repo, &(), &History(), SECRET, &owner_token, change(admin, Role::Admin)).await
+            .unwrap_or_else(|_| panic!("Error changing the role"));
+        assert_eq!(changed.member(&admin).unwrap().role, Role::Admin);
+        assert!(matches!(
+            execute(&pool, &repo, &(), &History(), SECRET, &admin_token, change(admin, Role::Member)).await,
+            Err(Error::Forbidden),
+        ));
+
+        // Demoted, the former admin can no longer manage the members
+        assert!(execute(&pool, &repo, &(), &History(), SECRET, &owner_token, change(admin, Role::Member)).await.is_ok());
+        assert!(matches!(
+            execute(&pool, &repo, &(), &History(), SECRET, &admin_token, change(member, Role::Admin)).await,
+            Err(Error::Forbidden),
+        ));
+        let removed = remove_member::execute(
+            &pool, &repo, &(), &History(), SECRET, &admin_token,
+            remove_member::Payload { id: group.id, user_id: member },
+        ).await;
+        assert!(matches!(removed, Err(remove_member::Error::Forbidden)));
+        assert!(repo.find_by_id(&pool, group.id).await
+            .unwrap_or_else(|_| panic!("Error getting the group"))
+            .is_member(&member));
+    }
+}
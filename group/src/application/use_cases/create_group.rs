@@ -0,0 +1,47 @@
+use auth::TokenData;
+use common::domain::types::id::Id;
+use crate::{
+    application::port::driven::group_repository::{GroupRepositoryTrait, Error as GroupRepositoryError},
+    domain::{group::{Group, NewGroup}, types::group_name::GroupName},
+};
+
+
+pub enum Error {
+    NotFound,
+    DatabaseError,
+    Unauthorized,
+}
+
+pub struct Payload {
+    pub name: GroupName,
+    pub members: Vec<Id>,
+}
+
+pub async fn execute<T>(
+    conn: &T,
+    repo: &impl GroupRepositoryTrait<T>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<Group, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+
+    // The creator owns the group
+    let new_group = NewGroup {
+        name: payload.name,
+        owner: user_id,
+        members: payload.members.into_iter().filter(|id| *id != user_id).collect(),
+    };
+
+    match repo.create(conn, new_group).await {
+        Ok(group) => Ok(group),
+        Err(e) => match e {
+            GroupRepositoryError::DatabaseError => Err(Error::DatabaseError),
+            GroupRepositoryError::NotFound => Err(Error::NotFound),
+            GroupRepositoryError::Forbidden => Err(Error::DatabaseError),
+        },
+    }
+}
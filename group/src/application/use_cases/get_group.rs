@@ -0,0 +1,39 @@
+use auth::TokenData;
+use common::domain::types::id::Id;
+use crate::{
+    application::{port::driven::group_repository::GroupRepositoryTrait, use_cases::get_member_group},
+    domain::group::Group,
+};
+
+
+pub enum Error {
+    NotFound,
+    DatabaseError,
+    Unauthorized,
+}
+
+pub struct Payload {
+    pub id: Id,
+}
+
+pub async fn execute<T>(
+    conn: &T,
+    repo: &impl GroupRepositoryTrait<T>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<Group, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+
+    // Groups are only visible to their members
+    match get_member_group::execute(conn, repo, get_member_group::Payload { id: payload.id, user_id }).await {
+        Ok(group) => Ok(group),
+        Err(e) => match e {
+            get_member_group::Error::DatabaseError => Err(Error::DatabaseError),
+            get_member_group::Error::NotFound | get_member_group::Error::NotMember => Err(Error::NotFound),
+        },
+    }
+}
@@ -0,0 +1,26 @@
+use auth::TokenData;
+use common::domain::types::id::Id;
+use crate::{
+    application::port::driven::group_repository::GroupRepositoryTrait,
+    domain::group::Group,
+};
+
+
+pub enum Error {
+    DatabaseError,
+    Unauthorized,
+}
+
+pub async fn execute<T>(
+    conn: &T,
+    repo: &impl GroupRepositoryTrait<T>,
+    secret: &[u8],
+    token: &String,
+) -> Result<Vec<Group>, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+
+    repo.find_by_member(conn, user_id).await.map_err(|_| Error::DatabaseError)
+}
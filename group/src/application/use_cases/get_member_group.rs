@@ -0,0 +1,38 @@
+use common::domain::types::id::Id;
+use crate::{
+    application::port::driven::group_repository::{GroupRepositoryTrait, Error as GroupRepositoryError},
+    domain::group::Group,
+};
+
+
+pub enum Error {
+    NotFound,
+    NotMember,
+    DatabaseError,
+}
+
+pub struct Payload {
+    pub id: Id,
+    pub user_id: Id,
+}
+
+/// Get a group only if `user_id` is one of its members
+pub async fn execute<T>(
+    conn: &T,
+    repo: &impl GroupRepositoryTrait<T>,
+    payload: Payload,
+) -> Result<Group, Error> {
+    let group = match repo.find_by_id(conn, payload.id).await {
+        Ok(group) => group,
+        Err(e) => match e {
+            GroupRepositoryError::DatabaseError => return Err(Error::DatabaseError),
+            GroupRepositoryError::NotFound => return Err(Error::NotFound),
+            GroupRepositoryError::Forbidden => return Err(Error::DatabaseError),
+        },
+    };
+
+    if !group.is_member(&payload.user_id) {
+        return Err(Error::NotMember);
+    }
+    Ok(group)
+}
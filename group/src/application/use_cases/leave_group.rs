@@ -0,0 +1,137 @@
+use auth::TokenData;
+use common::domain::types::id::Id;
+use crate::{
+    application::port::driven::{
+        group_history::GroupHistoryTrait,
+        group_repository::{GroupRepositoryTrait, Error as GroupRepositoryError},
+    },
+    domain::{group::RoleChange, types::role::Role},
+};
+
+
+pub enum Error {
+    NotFound,
+    DatabaseError,
+    Unauthorized,
+}
+
+pub struct Payload {
+    pub id: Id,
+}
+
+pub async fn execute<T, U>(
+    conn: &T,
+    repo: &impl GroupRepositoryTrait<T>,
+    history_conn: &U,
+    history: &impl GroupHistoryTrait<U>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<(), Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+
+    match remove_and_clean_up(conn, repo, history_conn, history, payload.id, user_id, user_id).await {
+        Ok(_) => Ok(()),
+        Err(e) => match e {
+            GroupRepositoryError::DatabaseError => Err(Error::DatabaseError),
+            // Leaving is always allowed
+            GroupRepositoryError::NotFound | GroupRepositoryError::Forbidden => Err(Error::NotFound),
+        },
+    }
+}
+
+/// Remove a member as `removed_by`, handing the ownership over when the
+/// owner leaves, and delete the group once nobody is left in it
+pub(super) async fn remove_and_clean_up<T, U>(
+    conn: &T,
+    repo: &impl GroupRepositoryTrait<T>,
+    history_conn: &U,
+    history: &impl GroupHistoryTrait<U>,
+    id: Id,
+    user_id: Id,
+    removed_by: Id,
+) -> Result<(), GroupRepositoryError> {
+    let departure = repo.remove_member(conn, id, user_id, removed_by).await?;
+
+    if let (Some(group), Some(new_owner)) = (departure.group, departure.new_owner) {
+        // The member is already gone, a line missing from the history
+        // doesn't bring them back
+        let change = RoleChange { user_id: new_owner, role: Role::Owner, changed_by: None };
+        if history.add_role_change(history_conn, group.into(), change).await.is_err() {
+            eprintln!("Error recording a role change in group {}", id);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Mutex;
+
+    use common::{adapter::db::create_pool, domain::types::group::Group};
+    use sqlx::{Pool, Postgres};
+    use uuid::Uuid;
+
+    use crate::{
+        adapter::driven::persistence::sqlx::group_repository::GroupRepository,
+        application::{port::driven::group_history, use_cases::create_group},
+    };
+    use super::*;
+
+    // Base64, like the SECRET_KEY of the config
+    const SECRET: &[u8] = b"c2VjcmV0";
+
+    /// Owners recorded by the role changes
+    #[derive(Default)]
+    struct History(Mutex<Vec<Id>>);
+
+    impl GroupHistoryTrait<()> for History {
+        async fn add_role_change(&self, _conn: &(), _group: Group, change: RoleChange) -> Result<(), group_history::Error> {
+            self.0.lock().unwrap().push(change.user_id);
+            Ok(())
+        }
+    }
+
+    async fn new_user(pool: &Pool<Postgres>) -> (Id, String) {
+        let user_id: Uuid = sqlx::query_scalar("INSERT INTO auths (hashed_password) VALUES ('') RETURNING user_id")
+            .fetch_one(pool).await.unwrap();
+        (user_id.try_into().unwrap(), TokenData::new(&user_id).token(SECRET))
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Postgres at DATABASE_URL"]
+    async fn test_leave_group() {
+        let pool = create_pool().await;
+        let repo = GroupRepository();
+        let history = History::default();
+        let (_, owner_token) = new_user(&pool).await;
+        let (member, member_token) = new_user(&pool).await;
+        let (last, last_token) = new_user(&pool).await;
+        let payload = create_group::Payload {
+            name: "Group".to_string().try_into().unwrap(),
+            members: vec![member, last],
+        };
+        let group = create_group::execute(&pool, &repo, SECRET, &owner_token, payload).await
+            .unwrap_or_else(|_| panic!("Error creating the group"));
+
+        // The oldest member takes over from the owner
+        assert!(execute(&pool, &repo, &(), &history, SECRET, &owner_token, Payload { id: group.id }).await.is_ok());
+        assert_eq!(*history.0.lock().unwrap(), vec![member]);
+        let group = repo.find_by_id(&pool, group.id).await
+            .unwrap_or_else(|_| panic!("Error getting the group"));
+        assert_eq!(group.member(&member).unwrap().role, Role::Owner);
+
+        // Leaving together, one after the other, empties and deletes the group
+        let (left, last_left) = tokio::join!(
+            execute(&pool, &repo, &(), &history, SECRET, &member_token, Payload { id: group.id }),
+            execute(&pool, &repo, &(), &history, SECRET, &last_token, Payload { id: group.id }),
+        );
+        assert!(left.is_ok() && last_left.is_ok());
+        assert!(matches!(repo.find_by_id(&pool, group.id).await, Err(GroupRepositoryError::NotFound)));
+        let owners = history.0.lock().unwrap().clone();
+        assert!(owners == vec![member] || owners == vec![member, last]);
+    }
+}
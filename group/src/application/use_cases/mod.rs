@@ -0,0 +1,10 @@
+pub mod create_group;
+pub mod get_groups;
+pub mod get_group;
+pub mod rename_group;
+pub mod add_member;
+pub mod remove_member;
+pub mod leave_group;
+pub mod get_member_group;
+pub mod change_role;
+pub mod transfer_ownership;
\ No newline at end of file
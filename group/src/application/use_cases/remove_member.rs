@@ -0,0 +1,47 @@
+use auth::TokenData;
+use common::domain::types::id::Id;
+use crate::application::{
+    port::driven::{
+        group_history::GroupHistoryTrait,
+        group_repository::{GroupRepositoryTrait, Error as GroupRepositoryError},
+    },
+    use_cases::leave_group,
+};
+
+
+pub enum Error {
+    NotFound,
+    DatabaseError,
+    Unauthorized,
+    Forbidden,
+}
+
+pub struct Payload {
+    pub id: Id,
+    pub user_id: Id,
+}
+
+pub async fn execute<T, U>(
+    conn: &T,
+    repo: &impl GroupRepositoryTrait<T>,
+    history_conn: &U,
+    history: &impl GroupHistoryTrait<U>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<(), Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+
+    // Checked against the roles of the locked group
+    match leave_group::remove_and_clean_up(conn, repo, history_conn, history, payload.id, payload.user_id, user_id).await {
+        Ok(_) => Ok(()),
+        Err(e) => match e {
+            GroupRepositoryError::DatabaseError => Err(Error::DatabaseError),
+            GroupRepositoryError::NotFound => Err(Error::NotFound),
+            GroupRepositoryError::Forbidden => Err(Error::Forbidden),
+        },
+    }
+}
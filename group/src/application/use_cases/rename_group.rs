@@ -0,0 +1,55 @@
+use auth::TokenData;
+use common::domain::types::id::Id;
+use crate::{
+    application::{
+        port::driven::group_repository::{GroupRepositoryTrait, Error as GroupRepositoryError, UpdateGroup},
+        use_cases::get_member_group,
+    },
+    domain::{group::Group, types::group_name::GroupName},
+};
+
+
+pub enum Error {
+    NotFound,
+    DatabaseError,
+    Unauthorized,
+    Forbidden,
+}
+
+pub struct Payload {
+    pub id: Id,
+    pub name: GroupName,
+}
+
+pub async fn execute<T>(
+    conn: &T,
+    repo: &impl GroupRepositoryTrait<T>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<Group, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+
+    let group = match get_member_group::execute(conn, repo, get_member_group::Payload { id: payload.id, user_id }).await {
+        Ok(group) => group,
+        Err(e) => return match e {
+            get_member_group::Error::DatabaseError => Err(Error::DatabaseError),
+            get_member_group::Error::NotFound | get_member_group::Error::NotMember => Err(Error::NotFound),
+        },
+    };
+    if !group.member(&user_id).is_some_and(|member| member.role.is_admin()) {
+        return Err(Error::Forbidden);
+    }
+
+    match repo.update(conn, UpdateGroup { id: payload.id, name: Some(payload.name) }).await {
+        Ok(group) => Ok(group),
+        Err(e) => match e {
+            GroupRepositoryError::DatabaseError => Err(Error::DatabaseError),
+            GroupRepositoryError::NotFound => Err(Error::NotFound),
+            GroupRepositoryError::Forbidden => Err(Error::Forbidden),
+        },
+    }
+}
@@ -0,0 +1,86 @@
+use auth::TokenData;
+use common::domain::types::id::Id;
+use crate::{
+    application::{
+        port::driven::{
+            group_history::GroupHistoryTrait,
+            group_repository::{GroupRepositoryTrait, Error as GroupRepositoryError},
+        },
+        use_cases::get_member_group,
+    },
+    domain::{group::{Group, Member, RoleChange}, types::role::Role},
+};
+
+
+pub enum Error {
+    NotFound,
+    DatabaseError,
+    Unauthorized,
+    Forbidden,
+}
+
+pub struct Payload {
+    pub id: Id,
+    pub user_id: Id,
+}
+
+/// Make another member the owner, the current owner becomes an admin
+pub async fn execute<T, U>(
+    conn: &T,
+    repo: &impl GroupRepositoryTrait<T>,
+    history_conn: &U,
+    history: &impl GroupHistoryTrait<U>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<Group, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+
+    let group = match get_member_group::execute(conn, repo, get_member_group::Payload { id: payload.id, user_id }).await {
+        Ok(group) => group,
+        Err(e) => return match e {
+            get_member_group::Error::DatabaseError => Err(Error::DatabaseError),
+            get_member_group::Error::NotFound | get_member_group::Error::NotMember => Err(Error::NotFound),
+        },
+    };
+    if group.member(&user_id).map(|member| member.role) != Some(Role::Owner) {
+        return Err(Error::Forbidden);
+    }
+    if payload.user_id == user_id {
+        return Ok(group);
+    }
+    if !group.is_member(&payload.user_id) {
+        return Err(Error::NotFound);
+    }
+
+    let roles = vec![
+        Member { user_id: payload.user_id, role: Role::Owner },
+        Member { user_id, role: Role::Admin },
+    ];
+    if let Err(e) = repo.set_roles(conn, payload.id, roles).await {
+        return match e {
+            GroupRepositoryError::DatabaseError => Err(Error::DatabaseError),
+            GroupRepositoryError::NotFound => Err(Error::NotFound),
+            GroupRepositoryError::Forbidden => Err(Error::Forbidden),
+        };
+    }
+
+    let group = repo.find_by_id(conn, payload.id).await
+        .map_err(|_| Error::DatabaseError)?;
+    let changes = [
+        RoleChange { user_id: payload.user_id, role: Role::Owner, changed_by: Some(user_id) },
+        RoleChange { user_id, role: Role::Admin, changed_by: Some(user_id) },
+    ];
+    // The ownership already moved, lines missing from the history don't
+    // undo it
+    for change in changes {
+        if history.add_role_change(history_conn, (&group).into(), change).await.is_err() {
+            eprintln!("Error recording a role change in group {}", payload.id);
+        }
+    }
+
+    Ok(group)
+}
@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+
+use common::domain::types::{group::Group as GroupRecipient, id::Id};
+use super::types::{group_name::GroupName, role::Role};
+
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Member {
+    pub user_id: Id,
+    pub role: Role,
+}
+
+/// Members are ordered by the time they joined
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Group {
+    pub id: Id,
+    pub name: GroupName,
+    pub members: Vec<Member>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Group {
+    pub fn member(&self, user_id: &Id) -> Option<&Member> {
+        self.members.iter().find(|member| member.user_id == *user_id)
+    }
+
+    pub fn is_member(&self, user_id: &Id) -> bool {
+        self.member(user_id).is_some()
+    }
+
+    /// Member that takes over when the owner leaves: the oldest admin, or the
+    /// oldest member when there are no admins
+    pub fn next_owner(&self) -> Option<&Member> {
+        let candidates = || self.members.iter().filter(|member| member.role != Role::Owner);
+        candidates()
+            .find(|member| member.role == Role::Admin)
+            .or_else(|| candidates().next())
+    }
+}
+
+impl From<&Group> for GroupRecipient {
+    fn from(group: &Group) -> Self {
+        GroupRecipient {
+            id: group.id,
+            name: group.name.clone().into(),
+            members: group.members.iter().map(|member| member.user_id).collect(),
+        }
+    }
+}
+
+impl From<Group> for GroupRecipient {
+    fn from(group: Group) -> Self {
+        (&group).into()
+    }
+}
+
+pub struct NewGroup {
+    pub name: GroupName,
+    pub owner: Id,
+    pub members: Vec<Id>,
+}
+
+/// Outcome of a member leaving a group, `group` is `None` once the last
+/// member left and the group was deleted
+pub struct Departure {
+    pub group: Option<Group>,
+    pub new_owner: Option<Id>,
+}
+
+/// Change of the role of a member, recorded in the group history.
+/// `changed_by` is `None` when the change follows the owner leaving.
+pub struct RoleChange {
+    pub user_id: Id,
+    pub role: Role,
+    pub changed_by: Option<Id>,
+}
+
+#[cfg(test)]
+mod tests_group {
+    use uuid::Uuid;
+    use super::*;
+
+    fn member(role: Role) -> Member {
+        Member { user_id: Uuid::new_v4().try_into().unwrap(), role }
+    }
+
+    fn group(members: Vec<Member>) -> Group {
+        Group {
+            id: Uuid::new_v4().try_into().unwrap(),
+            name: "Group".to_string().try_into().unwrap(),
+            members,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_next_owner() {
+        let owner = member(Role::Owner);
+        let first = member(Role::Member);
+        let admin = member(Role::Admin);
+        let g = group(vec![owner.clone(), first.clone(), admin.clone()]);
+        assert_eq!(g.next_owner().unwrap().user_id, admin.user_id);
+
+        let g = group(vec![owner.clone(), first.clone()]);
+        assert_eq!(g.next_owner().unwrap().user_id, first.user_id);
+
+        let g = group(vec![owner]);
+        assert!(g.next_owner().is_none());
+    }
+}
@@ -0,0 +1,68 @@
+use std::fmt::Display;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug)]
+pub enum Error {
+    TooShort,
+    TooLong,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::TooShort => write!(f, "Group name is too short"),
+            Error::TooLong => write!(f, "Group name is too long"),
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+pub struct GroupName(String);
+
+impl TryFrom<String> for GroupName {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let value = value.trim().to_string();
+        if value.is_empty() {
+            return Err(Error::TooShort);
+        }
+        if value.chars().count() > 64 {
+            return Err(Error::TooLong);
+        }
+        Ok(GroupName(value))
+    }
+}
+
+impl From<GroupName> for String {
+    fn from(group_name: GroupName) -> Self {
+        group_name.0
+    }
+}
+
+impl<'de> Deserialize<'de> for GroupName {
+    fn deserialize<D>(deserializer: D) -> Result<GroupName, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        GroupName::try_from(s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests_group_name {
+    use super::*;
+
+    #[test]
+    fn test_group_name() {
+        let group_name = GroupName::try_from("Family".to_string());
+        assert!(group_name.is_ok());
+        let group_name = GroupName::try_from("  Family  ".to_string());
+        assert_eq!(String::from(group_name.unwrap()), "Family");
+        let group_name = GroupName::try_from("   ".to_string());
+        assert!(group_name.is_err());
+        let group_name = GroupName::try_from("a".repeat(65));
+        assert!(group_name.is_err());
+    }
+}
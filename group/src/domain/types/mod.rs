@@ -0,0 +1,2 @@
+pub mod group_name;
+pub mod role;
\ No newline at end of file
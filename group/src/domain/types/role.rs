@@ -0,0 +1,87 @@
+use std::fmt::Display;
+use serde::{Deserialize, Serialize};
+
+
+#[derive(Debug)]
+pub struct Error;
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Invalid role")
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Role {
+    Member,
+    Admin,
+    Owner,
+}
+
+impl Role {
+    /// Admins and the owner manage the group: name and members
+    pub fn is_admin(&self) -> bool {
+        *self >= Role::Admin
+    }
+
+    /// Members can only be removed by someone above them
+    pub fn can_remove(&self, member: &Role) -> bool {
+        self.is_admin() && self > member
+    }
+
+    /// Admins promote members, only the owner demotes admins. Ownership is
+    /// only given away by transferring it.
+    pub fn can_change_role(&self, from: &Role, to: &Role) -> bool {
+        if !self.is_admin() || *from == Role::Owner || *to == Role::Owner {
+            return false;
+        }
+        *self == Role::Owner || *from == Role::Member
+    }
+}
+
+impl TryFrom<String> for Role {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "OWNER" => Ok(Role::Owner),
+            "ADMIN" => Ok(Role::Admin),
+            "MEMBER" => Ok(Role::Member),
+            _ => Err(Error),
+        }
+    }
+}
+
+impl From<Role> for String {
+    fn from(role: Role) -> Self {
+        match role {
+            Role::Owner => "OWNER",
+            Role::Admin => "ADMIN",
+            Role::Member => "MEMBER",
+        }.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests_role {
+    use super::*;
+
+    #[test]
+    fn test_role() {
+        assert!(Role::try_from("ADMIN".to_string()).is_ok());
+        assert!(Role::try_from("admin".to_string()).is_err());
+
+        assert!(!Role::Member.is_admin());
+        assert!(Role::Admin.can_remove(&Role::Member));
+        assert!(!Role::Admin.can_remove(&Role::Admin));
+        assert!(Role::Owner.can_remove(&Role::Admin));
+        assert!(!Role::Owner.can_remove(&Role::Owner));
+
+        assert!(Role::Admin.can_change_role(&Role::Member, &Role::Admin));
+        assert!(!Role::Admin.can_change_role(&Role::Admin, &Role::Member));
+        assert!(Role::Owner.can_change_role(&Role::Admin, &Role::Member));
+        assert!(!Role::Owner.can_change_role(&Role::Member, &Role::Owner));
+        assert!(!Role::Member.can_change_role(&Role::Member, &Role::Admin));
+    }
+}
@@ -0,0 +1,15 @@
+mod domain;
+mod application;
+mod adapter;
+
+// Adapter layer
+pub use adapter::driving::http::{handlers, schemas};
+pub use adapter::driven::persistence::sqlx::group_repository::GroupRepository;
+// Application layer
+pub use application::port::driven::{
+    group_history::{self, GroupHistoryTrait},
+    group_repository::GroupRepositoryTrait,
+};
+pub use application::use_cases::{get_groups, get_member_group};
+// Domain layer
+pub use domain::{group::RoleChange, types::role::Role};
@@ -0,0 +1,294 @@
+use sqlx::{Postgres, Pool, Transaction};
+use uuid::Uuid;
+
+use common::domain::types::id::Id;
+use key_repository::KeyRepositoryTrait;
+use crate::application::port::driven::key_repository;
+use crate::domain::device_keys::{
+    DeviceKeys, NewDeviceKeys, OneTimePrekey, PrekeyBundle, MAX_ONE_TIME_PREKEYS,
+    ONE_TIME_PREKEY_CLAIM_WINDOW,
+};
+use super::models::device_keys::{DeviceKeysDB, PrekeyBundleDB};
+
+
+pub struct KeyRepository();
+
+impl KeyRepositoryTrait<Pool<Postgres>> for KeyRepository {
+    async fn find_by_user(&self, conn: &Pool<Postgres>, user_id: Id) -> Result<Vec<DeviceKeys>, key_repository::Error> {
+        let keys = sqlx::query_as!(
+            DeviceKeysDB,
+            r#"
+                SELECT d.device_id, d.identity_key, d.signed_prekey_id, d.signed_prekey,
+                    d.signed_prekey_signature, d.created_at, d.updated_at,
+                    (
+                        SELECT COUNT(*) FROM one_time_prekeys AS o
+                        WHERE o.user_id = d.user_id AND o.device_id = d.device_id
+                    ) AS one_time_prekeys
+                FROM devices_keys AS d
+                WHERE d.user_id = $1
+                ORDER BY d.created_at;
+            "#,
+            &Uuid::from(user_id)
+        ).fetch_all(conn).await;
+
+        match keys {
+            Ok(keys) => Ok(keys.into_iter().map(DeviceKeys::from).collect()),
+            Err(_) => Err(key_repository::Error::DatabaseError),
+        }
+    }
+
+    async fn upsert(&self, conn: &Pool<Postgres>, new_keys: NewDeviceKeys) -> Result<DeviceKeys, key_repository::Error> {
+        let mut tx = conn.begin().await
+            .map_err(|_| key_repository::Error::DatabaseError)?;
+
+        let user_id = Uuid::from(new_keys.user_id);
+        let device_id = Uuid::from(new_keys.device_id);
+        let identity_key: Vec<u8> = new_keys.identity_key.into();
+
+        // Prekeys of a previous identity can't be used with the new one
+        sqlx::query!(
+            r#"
+                DELETE FROM one_time_prekeys AS o
+                USING devices_keys AS d
+                WHERE o.user_id = d.user_id AND o.device_id = d.device_id
+                    AND d.user_id = $1 AND d.device_id = $2 AND d.identity_key <> $3;
+            "#,
+            &user_id,
+            &device_id,
+            &identity_key
+        ).execute(&mut *tx).await
+            .map_err(|_| key_repository::Error::DatabaseError)?;
+
+        sqlx::query!(
+            r#"
+                INSERT INTO devices_keys (user_id, device_id, identity_key, signed_prekey_id, signed_prekey, signed_prekey_signature)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (user_id, device_id) DO UPDATE
+                SET identity_key = EXCLUDED.identity_key,
+                    signed_prekey_id = EXCLUDED.signed_prekey_id,
+                    signed_prekey = EXCLUDED.signed_prekey,
+                    signed_prekey_signature = EXCLUDED.signed_prekey_signature,
+                    updated_at = NOW();
+            "#,
+            &user_id,
+            &device_id,
+            &identity_key,
+            i64::from(new_keys.signed_prekey.key_id),
+            Vec::<u8>::from(new_keys.signed_prekey.public_key),
+            Vec::<u8>::from(new_keys.signed_prekey.signature)
+        ).execute(&mut *tx).await
+            .map_err(to_repository_error)?;
+
+        insert_one_time_prekeys(&mut tx, user_id, device_id, new_keys.one_time_prekeys).await?;
+
+        tx.commit().await
+            .map_err(|_| key_repository::Error::DatabaseError)?;
+
+        find_device(conn, user_id, device_id).await
+    }
+
+    async fn add_one_time_prekeys(
+        &self,
+        conn: &Pool<Postgres>,
+        user_id: Id,
+        device_id: Id,
+        prekeys: Vec<OneTimePrekey>,
+    ) -> Result<DeviceKeys, key_repository::Error> {
+        let mut tx = conn.begin().await
+            .map_err(|_| key_repository::Error::DatabaseError)?;
+        insert_one_time_prekeys(&mut tx, user_id.into(), device_id.into(), prekeys).await?;
+        tx.commit().await
+            .map_err(|_| key_repository::Error::DatabaseError)?;
+
+        find_device(conn, user_id.into(), device_id.into()).await
+    }
+
+    async fn delete(&self, conn: &Pool<Postgres>, user_id: Id, device_id: Id) -> Result<(), key_repository::Error> {
+        let result = sqlx::query!(
+            r#"
+                DELETE FROM devices_keys WHERE user_id = $1 AND device_id = $2;
+            "#,
+            &Uuid::from(user_id),
+            &Uuid::from(device_id)
+        ).execute(conn).await;
+
+        match result {
+            Ok(result) if result.rows_affected() > 0 => Ok(()),
+            Ok(_) => Err(key_repository::Error::NotFound),
+            Err(_) => Err(key_repository::Error::DatabaseError),
+        }
+    }
+
+    async fn take_bundles(
+        &self,
+        conn: &Pool<Postgres>,
+        requester_id: Id,
+        requester_device_id: Id,
+        user_id: Id,
+    ) -> Result<Vec<PrekeyBundle>, key_repository::Error> {
+        let mut tx = conn.begin().await
+            .map_err(|_| key_repository::Error::DatabaseError)?;
+        let requester_id = Uuid::from(requester_id);
+        let requester_device_id = Uuid::from(requester_device_id);
+
+        // Concurrent requests of a device would both find no claim, made up
+        // devices would get around the claims
+        let requester_device = sqlx::query!(
+            r#"
+                SELECT 1 AS locked FROM devices_keys
+                WHERE user_id = $1 AND device_id = $2
+                FOR UPDATE;
+            "#,
+            &requester_id,
+            &requester_device_id
+        ).fetch_optional(&mut *tx).await
+            .map_err(|_| key_repository::Error::DatabaseError)?;
+        if requester_device.is_none() {
+            return Err(key_repository::Error::NotFound);
+        }
+
+        // The oldest one-time prekey of every device the requesting device
+        // has no recent claim on, the ones being taken by a concurrent
+        // request are skipped
+        let bundles = sqlx::query_as!(
+            PrekeyBundleDB,
+            r#"
+                WITH claimable AS (
+                    SELECT d.user_id, d.device_id FROM devices_keys AS d
+                    WHERE d.user_id = $2 AND NOT EXISTS (
+                        SELECT 1 FROM one_time_prekey_claims AS c
+                        WHERE c.requester_id = $1 AND c.requester_device_id = $4
+                            AND c.user_id = d.user_id AND c.device_id = d.device_id
+                            AND c.claimed_at > NOW() - make_interval(secs => $3)
+                    )
+                ),
+                taken AS (
+                    DELETE FROM one_time_prekeys
+                    WHERE (user_id, device_id, key_id) IN (
+                        SELECT o.user_id, o.device_id, o.key_id FROM claimable AS d
+                        CROSS JOIN LATERAL (
+                            SELECT * FROM one_time_prekeys AS o
+                            WHERE o.user_id = d.user_id AND o.device_id = d.device_id
+                            ORDER BY o.key_id
+                            LIMIT 1
+                            FOR UPDATE SKIP LOCKED
+                        ) AS o
+                    )
+                    RETURNING user_id, device_id, key_id, public_key
+                ),
+                claimed AS (
+                    INSERT INTO one_time_prekey_claims (requester_id, requester_device_id, user_id, device_id)
+                    SELECT $1, $4, user_id, device_id FROM taken
+                    ON CONFLICT (requester_id, requester_device_id, user_id, device_id)
+                    DO UPDATE SET claimed_at = NOW()
+                )
+                SELECT d.user_id, d.device_id, d.identity_key, d.signed_prekey_id, d.signed_prekey,
+                    d.signed_prekey_signature,
+                    t.key_id AS "one_time_prekey_id?", t.public_key AS "one_time_prekey?"
+                FROM devices_keys AS d
+                LEFT JOIN taken AS t ON t.device_id = d.device_id
+                WHERE d.user_id = $2
+                ORDER BY d.created_at;
+            "#,
+            &requester_id,
+            &Uuid::from(user_id),
+            ONE_TIME_PREKEY_CLAIM_WINDOW.as_secs_f64(),
+            &requester_device_id
+        ).fetch_all(&mut *tx).await
+            .map_err(|_| key_repository::Error::DatabaseError)?;
+
+        tx.commit().await
+            .map_err(|_| key_repository::Error::DatabaseError)?;
+        Ok(bundles.into_iter().map(PrekeyBundle::from).collect())
+    }
+}
+
+async fn find_device(conn: &Pool<Postgres>, user_id: Uuid, device_id: Uuid) -> Result<DeviceKeys, key_repository::Error> {
+    let keys = sqlx::query_as!(
+        DeviceKeysDB,
+        r#"
+            SELECT d.device_id, d.identity_key, d.signed_prekey_id, d.signed_prekey,
+                d.signed_prekey_signature, d.created_at, d.updated_at,
+                (
+                    SELECT COUNT(*) FROM one_time_prekeys AS o
+                    WHERE o.user_id = d.user_id AND o.device_id = d.device_id
+                ) AS one_time_prekeys
+            FROM devices_keys AS d
+            WHERE d.user_id = $1 AND d.device_id = $2;
+        "#,
+        &user_id,
+        &device_id
+    ).fetch_one(conn).await;
+
+    match keys {
+        Ok(keys) => Ok(keys.into()),
+        Err(sqlx::Error::RowNotFound) => Err(key_repository::Error::NotFound),
+        Err(_) => Err(key_repository::Error::DatabaseError),
+    }
+}
+
+/// Insert the one-time prekeys of a device as long as it holds at most
+/// `MAX_ONE_TIME_PREKEYS` afterwards, none are inserted otherwise
+async fn insert_one_time_prekeys(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    device_id: Uuid,
+    prekeys: Vec<OneTimePrekey>,
+) -> Result<(), key_repository::Error> {
+    // Concurrent uploads to the device would both stay under the limit
+    sqlx::query!(
+        r#"
+            SELECT 1 AS locked FROM devices_keys
+            WHERE user_id = $1 AND device_id = $2
+            FOR UPDATE;
+        "#,
+        &user_id,
+        &device_id
+    ).fetch_optional(&mut **tx).await
+        .map_err(|_| key_repository::Error::DatabaseError)?;
+
+    let (key_ids, public_keys): (Vec<i64>, Vec<Vec<u8>>) = prekeys.into_iter()
+        .map(|prekey| (i64::from(prekey.key_id), Vec::from(prekey.public_key)))
+        .unzip();
+    // Prekeys with the id of a stored one are ignored and don't count
+    let counts = sqlx::query!(
+        r#"
+            WITH new AS (
+                SELECT DISTINCT ON (n.key_id) n.key_id, n.public_key
+                FROM UNNEST($3::BIGINT[], $4::BYTEA[]) AS n(key_id, public_key)
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM one_time_prekeys AS o
+                    WHERE o.user_id = $1 AND o.device_id = $2 AND o.key_id = n.key_id
+                )
+            ),
+            inserted AS (
+                INSERT INTO one_time_prekeys (user_id, device_id, key_id, public_key)
+                SELECT $1, $2, key_id, public_key FROM new
+                WHERE (SELECT COUNT(*) FROM one_time_prekeys WHERE user_id = $1 AND device_id = $2)
+                    + (SELECT COUNT(*) FROM new) <= $5
+                ON CONFLICT DO NOTHING
+                RETURNING key_id
+            )
+            SELECT (SELECT COUNT(*) FROM new) AS "new!", (SELECT COUNT(*) FROM inserted) AS "inserted!";
+        "#,
+        &user_id,
+        &device_id,
+        &key_ids,
+        &public_keys,
+        MAX_ONE_TIME_PREKEYS as i64
+    ).fetch_one(&mut **tx).await
+        .map_err(to_repository_error)?;
+
+    if counts.new > 0 && counts.inserted == 0 {
+        return Err(key_repository::Error::TooManyPrekeys);
+    }
+    Ok(())
+}
+
+/// Keys of users or devices that do not exist violate a foreign key
+fn to_repository_error(err: sqlx::Error) -> key_repository::Error {
+    match err.as_database_error().and_then(|err| err.code()) {
+        Some(code) if code == "23503" => key_repository::Error::NotFound,
+        _ => key_repository::Error::DatabaseError,
+    }
+}
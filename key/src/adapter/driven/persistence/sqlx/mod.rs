@@ -0,0 +1,2 @@
+pub mod key_repository;
+pub mod models;
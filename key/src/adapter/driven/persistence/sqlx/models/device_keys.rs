@@ -0,0 +1,64 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::domain::device_keys::{DeviceKeys, OneTimePrekey, PrekeyBundle, SignedPrekey};
+
+
+pub struct DeviceKeysDB {
+    pub device_id: Uuid,
+    pub identity_key: Vec<u8>,
+    pub signed_prekey_id: i64,
+    pub signed_prekey: Vec<u8>,
+    pub signed_prekey_signature: Vec<u8>,
+    pub one_time_prekeys: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<DeviceKeysDB> for DeviceKeys {
+    fn from(keys: DeviceKeysDB) -> Self {
+        DeviceKeys {
+            device_id: keys.device_id.try_into().unwrap(),
+            identity_key: keys.identity_key.try_into().unwrap(),
+            signed_prekey: SignedPrekey {
+                key_id: keys.signed_prekey_id as u32,
+                public_key: keys.signed_prekey.try_into().unwrap(),
+                signature: keys.signed_prekey_signature.try_into().unwrap(),
+            },
+            one_time_prekeys: keys.one_time_prekeys.unwrap_or(0),
+            created_at: keys.created_at,
+            updated_at: keys.updated_at,
+        }
+    }
+}
+
+pub struct PrekeyBundleDB {
+    pub user_id: Uuid,
+    pub device_id: Uuid,
+    pub identity_key: Vec<u8>,
+    pub signed_prekey_id: i64,
+    pub signed_prekey: Vec<u8>,
+    pub signed_prekey_signature: Vec<u8>,
+    pub one_time_prekey_id: Option<i64>,
+    pub one_time_prekey: Option<Vec<u8>>,
+}
+
+impl From<PrekeyBundleDB> for PrekeyBundle {
+    fn from(bundle: PrekeyBundleDB) -> Self {
+        PrekeyBundle {
+            user_id: bundle.user_id.try_into().unwrap(),
+            device_id: bundle.device_id.try_into().unwrap(),
+            identity_key: bundle.identity_key.try_into().unwrap(),
+            signed_prekey: SignedPrekey {
+                key_id: bundle.signed_prekey_id as u32,
+                public_key: bundle.signed_prekey.try_into().unwrap(),
+                signature: bundle.signed_prekey_signature.try_into().unwrap(),
+            },
+            one_time_prekey: bundle.one_time_prekey_id.zip(bundle.one_time_prekey)
+                .map(|(key_id, public_key)| OneTimePrekey {
+                    key_id: key_id as u32,
+                    public_key: public_key.try_into().unwrap(),
+                }),
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+
+use common::{
+    adapter::{response_schemas::JsonResponse, state::AppState},
+    domain::types::id::Id,
+};
+use crate::{
+    application::use_cases::{
+        add_one_time_prekeys, delete_device, get_devices, get_prekey_bundles, upload_keys,
+    },
+    domain::device_keys::{DeviceKeys, PrekeyBundle, MAX_ONE_TIME_PREKEYS},
+};
+
+// Adapters
+use crate::adapter::driven::persistence::sqlx::key_repository::KeyRepository;
+
+use super::schemas::{BundlesParamsJson, DeviceKeysJson, OneTimePrekeysJson};
+
+
+pub async fn handle_get_devices(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+) -> JsonResponse<Vec<DeviceKeys>> {
+    match get_devices::execute(
+        &state.db_sql_pool,
+        &KeyRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+    )
+    .await
+    {
+        Ok(devices) => JsonResponse::new_ok(devices),
+        Err(err) => match err {
+            get_devices::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            get_devices::Error::DatabaseError => JsonResponse::new_int_ser_err(0, "".to_string()),
+        },
+    }
+}
+
+pub async fn handle_upload_keys(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Path(device_id): Path<Id>,
+    Json(keys): Json<DeviceKeysJson>,
+) -> JsonResponse<DeviceKeys> {
+    match upload_keys::execute(
+        &state.db_sql_pool,
+        &KeyRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+        upload_keys::Payload {
+            device_id,
+            identity_key: keys.identity_key,
+            signed_prekey: keys.signed_prekey,
+            one_time_prekeys: keys.one_time_prekeys,
+        },
+    )
+    .await
+    {
+        Ok(keys) => JsonResponse::new_ok(keys),
+        Err(err) => match err {
+            upload_keys::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            upload_keys::Error::NotFound => JsonResponse::new_not_found_err(0, "".to_string()),
+            upload_keys::Error::TooManyPrekeys => JsonResponse::new_bad_req_err(
+                0,
+                format!("A device holds at most {} one-time prekeys", MAX_ONE_TIME_PREKEYS),
+            ),
+            upload_keys::Error::DatabaseError => JsonResponse::new_int_ser_err(0, "".to_string()),
+        },
+    }
+}
+
+pub async fn handle_add_one_time_prekeys(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Path(device_id): Path<Id>,
+    Json(prekeys): Json<OneTimePrekeysJson>,
+) -> JsonResponse<DeviceKeys> {
+    match add_one_time_prekeys::execute(
+        &state.db_sql_pool,
+        &KeyRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+        add_one_time_prekeys::Payload { device_id, one_time_prekeys: prekeys.one_time_prekeys },
+    )
+    .await
+    {
+        Ok(keys) => JsonResponse::new_ok(keys),
+        Err(err) => match err {
+            add_one_time_prekeys::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            add_one_time_prekeys::Error::NotFound => {
+                JsonResponse::new_not_found_err(0, "Device not found".to_string())
+            }
+            add_one_time_prekeys::Error::TooManyPrekeys => JsonResponse::new_bad_req_err(
+                0,
+                format!("A device holds at most {} one-time prekeys", MAX_ONE_TIME_PREKEYS),
+            ),
+            add_one_time_prekeys::Error::DatabaseError => {
+                JsonResponse::new_int_ser_err(0, "".to_string())
+            }
+        },
+    }
+}
+
+pub async fn handle_delete_device(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Path(device_id): Path<Id>,
+) -> JsonResponse<String> {
+    match delete_device::execute(
+        &state.db_sql_pool,
+        &KeyRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+        delete_device::Payload { device_id },
+    )
+    .await
+    {
+        Ok(_) => JsonResponse::new_ok(String::from("OK")),
+        Err(err) => match err {
+            delete_device::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            delete_device::Error::NotFound => JsonResponse::new_not_found_err(0, "".to_string()),
+            delete_device::Error::DatabaseError => JsonResponse::new_int_ser_err(0, "".to_string()),
+        },
+    }
+}
+
+pub async fn handle_get_prekey_bundles(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Path(user_id): Path<Id>,
+    Query(params): Query<BundlesParamsJson>,
+) -> JsonResponse<Vec<PrekeyBundle>> {
+    match get_prekey_bundles::execute(
+        &state.db_sql_pool,
+        &KeyRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+        get_prekey_bundles::Payload { user_id, device_id: params.device_id },
+    )
+    .await
+    {
+        Ok(bundles) => JsonResponse::new_ok(bundles),
+        Err(err) => match err {
+            get_prekey_bundles::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            get_prekey_bundles::Error::NotFound => {
+                JsonResponse::new_not_found_err(0, "Device not found".to_string())
+            }
+            get_prekey_bundles::Error::DatabaseError => {
+                JsonResponse::new_int_ser_err(0, "".to_string())
+            }
+        },
+    }
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use common::domain::types::id::Id;
+use crate::domain::{
+    device_keys::{OneTimePrekey, SignedPrekey},
+    types::public_key::PublicKey,
+};
+
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceKeysJson {
+    pub identity_key: PublicKey,
+    pub signed_prekey: SignedPrekey,
+    #[serde(default)]
+    pub one_time_prekeys: Vec<OneTimePrekey>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OneTimePrekeysJson {
+    pub one_time_prekeys: Vec<OneTimePrekey>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundlesParamsJson {
+    /// Device of the requester starting the sessions
+    pub device_id: Id,
+}
@@ -0,0 +1,2 @@
+pub mod port;
+pub mod use_cases;
@@ -0,0 +1,96 @@
+use common::domain::types::id::Id;
+use crate::domain::device_keys::{DeviceKeys, NewDeviceKeys, OneTimePrekey, PrekeyBundle};
+
+
+pub enum Error {
+    NotFound,
+    DatabaseError,
+    /// The device would hold more than `MAX_ONE_TIME_PREKEYS`
+    TooManyPrekeys,
+}
+
+pub trait KeyRepositoryTrait<T> {
+    /// Get the keys of every device of a user
+    /// 
+    /// # Parameters
+    /// - `conn` - The connection to the database
+    /// - `user_id` - The id of the user
+    /// 
+    /// # Returns
+    /// - `Ok(Vec<DeviceKeys>)` - The keys of the devices, oldest device first
+    /// - `Err(Error)` - An error occurred
+    fn find_by_user(&self, conn: &T, user_id: Id) -> impl std::future::Future<Output = Result<Vec<DeviceKeys>, Error>> + Send;
+
+    /// Register the keys of a device or replace them. The one-time prekeys are
+    /// added to the ones left, unless the identity key changed: those belong
+    /// to the previous identity and are dropped.
+    /// 
+    /// # Parameters
+    /// - `conn` - The connection to the database
+    /// - `new_keys` - The keys of the device
+    /// 
+    /// # Returns
+    /// - `Ok(DeviceKeys)` - The keys of the device
+    /// - `Err(Error::NotFound)` - The user does not exist
+    /// - `Err(Error::TooManyPrekeys)` - The device would hold too many one-time prekeys
+    /// - `Err(Error)` - An error occurred
+    fn upsert(&self, conn: &T, new_keys: NewDeviceKeys) -> impl std::future::Future<Output = Result<DeviceKeys, Error>> + Send;
+
+    /// Add one-time prekeys to a device, prekeys with the id of an existing
+    /// one are ignored
+    /// 
+    /// # Parameters
+    /// - `conn` - The connection to the database
+    /// - `user_id` - The id of the owner of the device
+    /// - `device_id` - The id of the device
+    /// - `prekeys` - The new one-time prekeys
+    /// 
+    /// # Returns
+    /// - `Ok(DeviceKeys)` - The keys of the device
+    /// - `Err(Error::NotFound)` - The device has no keys
+    /// - `Err(Error::TooManyPrekeys)` - The device would hold too many one-time prekeys
+    /// - `Err(Error)` - An error occurred
+    fn add_one_time_prekeys(
+        &self,
+        conn: &T,
+        user_id: Id,
+        device_id: Id,
+        prekeys: Vec<OneTimePrekey>,
+    ) -> impl std::future::Future<Output = Result<DeviceKeys, Error>> + Send;
+
+    /// Delete the keys of a device
+    /// 
+    /// # Parameters
+    /// - `conn` - The connection to the database
+    /// - `user_id` - The id of the owner of the device
+    /// - `device_id` - The id of the device
+    /// 
+    /// # Returns
+    /// - `Ok(())` - The keys were deleted
+    /// - `Err(Error::NotFound)` - The device has no keys
+    /// - `Err(Error)` - An error occurred
+    fn delete(&self, conn: &T, user_id: Id, device_id: Id) -> impl std::future::Future<Output = Result<(), Error>> + Send;
+
+    /// Get a prekey bundle for every device of a user. The one-time prekey of
+    /// each bundle is removed, it is never handed out twice. A device of the
+    /// requester gets a one-time prekey of a device once per
+    /// `ONE_TIME_PREKEY_CLAIM_WINDOW`, the bundles have none in between.
+    /// 
+    /// # Parameters
+    /// - `conn` - The connection to the database
+    /// - `requester_id` - The id of the user starting the sessions
+    /// - `requester_device_id` - The id of the device starting the sessions
+    /// - `user_id` - The id of the user
+    /// 
+    /// # Returns
+    /// - `Ok(Vec<PrekeyBundle>)` - The bundles, oldest device first
+    /// - `Err(Error::NotFound)` - The device of the requester has no keys
+    /// - `Err(Error)` - An error occurred
+    fn take_bundles(
+        &self,
+        conn: &T,
+        requester_id: Id,
+        requester_device_id: Id,
+        user_id: Id,
+    ) -> impl std::future::Future<Output = Result<Vec<PrekeyBundle>, Error>> + Send;
+}
@@ -0,0 +1,46 @@
+use auth::TokenData;
+use common::domain::types::id::Id;
+use crate::{
+    application::port::driven::key_repository::{KeyRepositoryTrait, Error as KeyRepositoryError},
+    domain::device_keys::{DeviceKeys, OneTimePrekey, MAX_ONE_TIME_PREKEYS},
+};
+
+
+pub enum Error {
+    NotFound,
+    DatabaseError,
+    Unauthorized,
+    TooManyPrekeys,
+}
+
+pub struct Payload {
+    pub device_id: Id,
+    pub one_time_prekeys: Vec<OneTimePrekey>,
+}
+
+/// Replenish the one-time prekeys of a device of the user, up to
+/// `MAX_ONE_TIME_PREKEYS` held by the device
+pub async fn execute<T>(
+    conn: &T,
+    repo: &impl KeyRepositoryTrait<T>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<DeviceKeys, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+    if payload.one_time_prekeys.len() > MAX_ONE_TIME_PREKEYS {
+        return Err(Error::TooManyPrekeys);
+    }
+
+    match repo.add_one_time_prekeys(conn, user_id, payload.device_id, payload.one_time_prekeys).await {
+        Ok(keys) => Ok(keys),
+        Err(e) => match e {
+            KeyRepositoryError::DatabaseError => Err(Error::DatabaseError),
+            KeyRepositoryError::NotFound => Err(Error::NotFound),
+            KeyRepositoryError::TooManyPrekeys => Err(Error::TooManyPrekeys),
+        },
+    }
+}
@@ -0,0 +1,37 @@
+use auth::TokenData;
+use common::domain::types::id::Id;
+use crate::application::port::driven::key_repository::{KeyRepositoryTrait, Error as KeyRepositoryError};
+
+
+pub enum Error {
+    NotFound,
+    DatabaseError,
+    Unauthorized,
+}
+
+pub struct Payload {
+    pub device_id: Id,
+}
+
+/// Remove the keys of a device of the user, no new session can be started
+/// with it
+pub async fn execute<T>(
+    conn: &T,
+    repo: &impl KeyRepositoryTrait<T>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<(), Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+
+    match repo.delete(conn, user_id, payload.device_id).await {
+        Ok(_) => Ok(()),
+        Err(e) => match e {
+            KeyRepositoryError::DatabaseError => Err(Error::DatabaseError),
+            KeyRepositoryError::NotFound | KeyRepositoryError::TooManyPrekeys => Err(Error::NotFound),
+        },
+    }
+}
@@ -0,0 +1,27 @@
+use auth::TokenData;
+use common::domain::types::id::Id;
+use crate::{
+    application::port::driven::key_repository::KeyRepositoryTrait,
+    domain::device_keys::DeviceKeys,
+};
+
+
+pub enum Error {
+    DatabaseError,
+    Unauthorized,
+}
+
+/// Keys of the devices of the user, with the one-time prekeys they have left
+pub async fn execute<T>(
+    conn: &T,
+    repo: &impl KeyRepositoryTrait<T>,
+    secret: &[u8],
+    token: &String,
+) -> Result<Vec<DeviceKeys>, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+
+    repo.find_by_user(conn, user_id).await.map_err(|_| Error::DatabaseError)
+}
@@ -0,0 +1,207 @@
+use auth::TokenData;
+use common::domain::types::id::Id;
+use crate::{
+    application::port::driven::key_repository::{KeyRepositoryTrait, Error as KeyRepositoryError},
+    domain::device_keys::PrekeyBundle,
+};
+
+
+pub enum Error {
+    /// The requesting device has no keys
+    NotFound,
+    DatabaseError,
+    Unauthorized,
+}
+
+pub struct Payload {
+    pub user_id: Id,
+    /// Device of the requester starting the sessions
+    pub device_id: Id,
+}
+
+/// Prekey bundles for a device of the requester to start a session with every
+/// device of a user, each one uses up a one-time prekey of its device unless
+/// the requesting device took one of that device recently. Users without keys
+/// have no bundles, messages to them can't be encrypted.
+pub async fn execute<T>(
+    conn: &T,
+    repo: &impl KeyRepositoryTrait<T>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<Vec<PrekeyBundle>, Error> {
+    let requester_id = match TokenData::from_token(token, secret) {
+        Ok(auth) => Id::try_from(auth.id).map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+
+    match repo.take_bundles(conn, requester_id, payload.device_id, payload.user_id).await {
+        Ok(bundles) => Ok(bundles),
+        Err(KeyRepositoryError::NotFound) => Err(Error::NotFound),
+        Err(_) => Err(Error::DatabaseError),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::adapter::db::create_pool;
+    use sqlx::{Pool, Postgres};
+    use uuid::Uuid;
+
+    use crate::{
+        adapter::driven::persistence::sqlx::key_repository::KeyRepository,
+        application::use_cases::{add_one_time_prekeys, delete_device, get_devices, upload_keys},
+        domain::{
+            device_keys::{OneTimePrekey, SignedPrekey, MAX_ONE_TIME_PREKEYS},
+            types::public_key::PublicKey,
+        },
+    };
+    use super::*;
+
+    // Base64, like the SECRET_KEY of the config
+    const SECRET: &[u8] = b"c2VjcmV0";
+
+    async fn new_user(pool: &Pool<Postgres>) -> (Id, String) {
+        let user_id: Uuid = sqlx::query_scalar("INSERT INTO auths (hashed_password) VALUES ('') RETURNING user_id")
+            .fetch_one(pool).await.unwrap();
+        (user_id.try_into().unwrap(), TokenData::new(&user_id).token(SECRET))
+    }
+
+    fn keys(device_id: Id, identity: u8, one_time_prekeys: &[u32]) -> upload_keys::Payload {
+        upload_keys::Payload {
+            device_id,
+            identity_key: vec![identity; 32].try_into().unwrap(),
+            signed_prekey: SignedPrekey {
+                key_id: 1,
+                public_key: vec![identity + 1; 32].try_into().unwrap(),
+                signature: vec![identity + 2; 64].try_into().unwrap(),
+            },
+            one_time_prekeys: one_time_prekeys.iter()
+                .map(|key_id| OneTimePrekey {
+                    key_id: *key_id,
+                    public_key: vec![*key_id as u8; 32].try_into().unwrap(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Register a device without one-time prekeys
+    async fn new_device(pool: &Pool<Postgres>, token: &String) -> Id {
+        let device_id: Id = Uuid::new_v4().try_into().unwrap();
+        upload_keys::execute(pool, &KeyRepository(), SECRET, token, keys(device_id, 40, &[])).await
+            .unwrap_or_else(|_| panic!("Error uploading the keys"));
+        device_id
+    }
+
+    async fn bundles(pool: &Pool<Postgres>, token: &String, device_id: Id, user_id: Id) -> Vec<PrekeyBundle> {
+        execute(pool, &KeyRepository(), SECRET, token, Payload { user_id, device_id }).await
+            .unwrap_or_else(|_| panic!("Error getting the bundles"))
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Postgres at DATABASE_URL"]
+    async fn test_two_device_exchange() {
+        let pool = create_pool().await;
+        let repo = KeyRepository();
+        let (alice, alice_token) = new_user(&pool).await;
+        let (bob, bob_token) = new_user(&pool).await;
+        let alice_device: Id = Uuid::new_v4().try_into().unwrap();
+        let bob_device: Id = Uuid::new_v4().try_into().unwrap();
+
+        // Both devices publish their keys
+        let uploaded = upload_keys::execute(&pool, &repo, SECRET, &alice_token, keys(alice_device, 10, &[1, 2, 3])).await
+            .unwrap_or_else(|_| panic!("Error uploading the keys"));
+        assert_eq!(uploaded.one_time_prekeys, 3);
+        upload_keys::execute(&pool, &repo, SECRET, &bob_token, keys(bob_device, 20, &[1])).await
+            .unwrap_or_else(|_| panic!("Error uploading the keys"));
+
+        // Each one starts a session with the other, using up a one-time prekey
+        let bundle = bundles(&pool, &bob_token, bob_device, alice).await.pop().unwrap();
+        assert_eq!(bundle.device_id, alice_device);
+        assert_eq!(bundle.identity_key, PublicKey::try_from(vec![10; 32]).unwrap());
+        assert_eq!(bundle.signed_prekey, keys(alice_device, 10, &[]).signed_prekey);
+        assert_eq!(bundle.one_time_prekey.unwrap().key_id, 1);
+        let bundle = bundles(&pool, &alice_token, alice_device, bob).await.pop().unwrap();
+        assert_eq!(bundle.device_id, bob_device);
+        assert_eq!(bundle.one_time_prekey.unwrap().key_id, 1);
+
+        // A device gets a single one-time prekey of a device while its session
+        // is expected to be live, the other devices of its owner start their
+        // own sessions
+        let bundle = bundles(&pool, &bob_token, bob_device, alice).await.pop().unwrap();
+        assert!(bundle.one_time_prekey.is_none());
+        let bob_laptop = new_device(&pool, &bob_token).await;
+        let bundle = bundles(&pool, &bob_token, bob_laptop, alice).await.pop().unwrap();
+        assert_eq!(bundle.one_time_prekey.unwrap().key_id, 2);
+
+        // Only devices with keys start sessions
+        let made_up: Id = Uuid::new_v4().try_into().unwrap();
+        assert!(matches!(
+            execute(&pool, &repo, SECRET, &bob_token, Payload { user_id: alice, device_id: made_up }).await,
+            Err(Error::NotFound)
+        ));
+        assert!(matches!(
+            execute(&pool, &repo, SECRET, &bob_token, Payload { user_id: alice, device_id: alice_device }).await,
+            Err(Error::NotFound)
+        ));
+
+        // One-time prekeys are never handed out twice, the signed prekey is
+        // left once they run out
+        let (_, carol_token) = new_user(&pool).await;
+        let carol_device = new_device(&pool, &carol_token).await;
+        let bundle = bundles(&pool, &carol_token, carol_device, alice).await.pop().unwrap();
+        assert_eq!(bundle.one_time_prekey.unwrap().key_id, 3);
+        let (_, dave_token) = new_user(&pool).await;
+        let dave_device = new_device(&pool, &dave_token).await;
+        let bundle = bundles(&pool, &dave_token, dave_device, alice).await.pop().unwrap();
+        assert!(bundle.one_time_prekey.is_none());
+        let devices = get_devices::execute(&pool, &repo, SECRET, &alice_token).await
+            .unwrap_or_else(|_| panic!("Error getting the devices"));
+        assert_eq!(devices[0].one_time_prekeys, 0);
+
+        // Replenished by the device
+        let payload = add_one_time_prekeys::Payload {
+            device_id: alice_device,
+            one_time_prekeys: keys(alice_device, 10, &[3, 4]).one_time_prekeys,
+        };
+        let replenished = add_one_time_prekeys::execute(&pool, &repo, SECRET, &alice_token, payload).await
+            .unwrap_or_else(|_| panic!("Error adding the prekeys"));
+        assert_eq!(replenished.one_time_prekeys, 2);
+
+        // Up to the most a device holds, stored ones included
+        let key_ids: Vec<u32> = (5..MAX_ONE_TIME_PREKEYS as u32 + 4).collect();
+        let payload = add_one_time_prekeys::Payload {
+            device_id: alice_device,
+            one_time_prekeys: keys(alice_device, 10, &key_ids).one_time_prekeys,
+        };
+        assert!(matches!(
+            add_one_time_prekeys::execute(&pool, &repo, SECRET, &alice_token, payload).await,
+            Err(add_one_time_prekeys::Error::TooManyPrekeys)
+        ));
+        let payload = add_one_time_prekeys::Payload {
+            device_id: alice_device,
+            one_time_prekeys: keys(alice_device, 10, &key_ids[1..]).one_time_prekeys,
+        };
+        let replenished = add_one_time_prekeys::execute(&pool, &repo, SECRET, &alice_token, payload).await
+            .unwrap_or_else(|_| panic!("Error adding the prekeys"));
+        assert_eq!(replenished.one_time_prekeys, MAX_ONE_TIME_PREKEYS as i64);
+
+        // A new identity drops the prekeys of the previous one
+        let uploaded = upload_keys::execute(&pool, &repo, SECRET, &alice_token, keys(alice_device, 30, &[200])).await
+            .unwrap_or_else(|_| panic!("Error uploading the keys"));
+        assert_eq!(uploaded.one_time_prekeys, 1);
+        let bundle = bundles(&pool, &dave_token, dave_device, alice).await.pop().unwrap();
+        assert_eq!(bundle.identity_key, PublicKey::try_from(vec![30; 32]).unwrap());
+        assert_eq!(bundle.one_time_prekey.unwrap().key_id, 200);
+
+        // Nothing to fetch once the device is gone
+        let payload = delete_device::Payload { device_id: alice_device };
+        assert!(delete_device::execute(&pool, &repo, SECRET, &alice_token, payload).await.is_ok());
+        assert!(bundles(&pool, &bob_token, bob_device, alice).await.is_empty());
+        let payload = delete_device::Payload { device_id: alice_device };
+        assert!(matches!(
+            delete_device::execute(&pool, &repo, SECRET, &alice_token, payload).await,
+            Err(delete_device::Error::NotFound)
+        ));
+    }
+}
@@ -0,0 +1,5 @@
+pub mod upload_keys;
+pub mod add_one_time_prekeys;
+pub mod get_devices;
+pub mod delete_device;
+pub mod get_prekey_bundles;
@@ -0,0 +1,58 @@
+use auth::TokenData;
+use common::domain::types::id::Id;
+use crate::{
+    application::port::driven::key_repository::{KeyRepositoryTrait, Error as KeyRepositoryError},
+    domain::{
+        device_keys::{DeviceKeys, NewDeviceKeys, OneTimePrekey, SignedPrekey, MAX_ONE_TIME_PREKEYS},
+        types::public_key::PublicKey,
+    },
+};
+
+
+pub enum Error {
+    NotFound,
+    DatabaseError,
+    Unauthorized,
+    TooManyPrekeys,
+}
+
+pub struct Payload {
+    pub device_id: Id,
+    pub identity_key: PublicKey,
+    pub signed_prekey: SignedPrekey,
+    pub one_time_prekeys: Vec<OneTimePrekey>,
+}
+
+/// Register the keys of a device of the user or rotate them
+pub async fn execute<T>(
+    conn: &T,
+    repo: &impl KeyRepositoryTrait<T>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<DeviceKeys, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+    if payload.one_time_prekeys.len() > MAX_ONE_TIME_PREKEYS {
+        return Err(Error::TooManyPrekeys);
+    }
+
+    let new_keys = NewDeviceKeys {
+        user_id,
+        device_id: payload.device_id,
+        identity_key: payload.identity_key,
+        signed_prekey: payload.signed_prekey,
+        one_time_prekeys: payload.one_time_prekeys,
+    };
+
+    match repo.upsert(conn, new_keys).await {
+        Ok(keys) => Ok(keys),
+        Err(e) => match e {
+            KeyRepositoryError::DatabaseError => Err(Error::DatabaseError),
+            KeyRepositoryError::NotFound => Err(Error::NotFound),
+            KeyRepositoryError::TooManyPrekeys => Err(Error::TooManyPrekeys),
+        },
+    }
+}
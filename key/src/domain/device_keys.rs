@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+
+use common::domain::types::id::Id;
+use super::types::{public_key::PublicKey, signature::Signature};
+
+
+/// One-time prekeys a device can hold, and so upload at once
+pub const MAX_ONE_TIME_PREKEYS: usize = 100;
+
+/// Time a device of a requester gets no other one-time prekey of a device
+/// after taking one, the session started with it is expected to be used
+/// meanwhile
+pub const ONE_TIME_PREKEY_CLAIM_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Medium-term prekey of a device, replaced by the device from time to time
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedPrekey {
+    pub key_id: u32,
+    pub public_key: PublicKey,
+    pub signature: Signature,
+}
+
+/// Prekey handed out once, to the first device starting a session with its
+/// owner
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OneTimePrekey {
+    pub key_id: u32,
+    pub public_key: PublicKey,
+}
+
+/// Public keys of a device as seen by its owner, `one_time_prekeys` is the
+/// number of one-time prekeys left
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceKeys {
+    pub device_id: Id,
+    pub identity_key: PublicKey,
+    pub signed_prekey: SignedPrekey,
+    pub one_time_prekeys: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+pub struct NewDeviceKeys {
+    pub user_id: Id,
+    pub device_id: Id,
+    pub identity_key: PublicKey,
+    pub signed_prekey: SignedPrekey,
+    pub one_time_prekeys: Vec<OneTimePrekey>,
+}
+
+/// Keys another device needs to start a session with a device, the one-time
+/// prekey is missing once the device ran out of them
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrekeyBundle {
+    pub user_id: Id,
+    pub device_id: Id,
+    pub identity_key: PublicKey,
+    pub signed_prekey: SignedPrekey,
+    pub one_time_prekey: Option<OneTimePrekey>,
+}
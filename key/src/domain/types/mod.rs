@@ -0,0 +1,2 @@
+pub mod public_key;
+pub mod signature;
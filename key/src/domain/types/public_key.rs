@@ -0,0 +1,82 @@
+use std::fmt::Display;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug)]
+pub enum Error {
+    InvalidEncoding,
+    InvalidLength,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::InvalidEncoding => write!(f, "Public key is not valid base64"),
+            Error::InvalidLength => write!(f, "Public key must be 32 or 33 bytes long"),
+        }
+    }
+}
+
+/// Curve25519 public key, optionally prefixed by the byte telling its type.
+/// Only distributed, the server never uses it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicKey(Vec<u8>);
+
+impl TryFrom<Vec<u8>> for PublicKey {
+    type Error = Error;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        if value.len() != 32 && value.len() != 33 {
+            return Err(Error::InvalidLength);
+        }
+        Ok(PublicKey(value))
+    }
+}
+
+impl TryFrom<String> for PublicKey {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let value = STANDARD.decode(value).map_err(|_| Error::InvalidEncoding)?;
+        PublicKey::try_from(value)
+    }
+}
+
+impl From<PublicKey> for Vec<u8> {
+    fn from(public_key: PublicKey) -> Self {
+        public_key.0
+    }
+}
+
+impl Serialize for PublicKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
+        STANDARD.encode(&self.0).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PublicKey {
+    fn deserialize<D>(deserializer: D) -> Result<PublicKey, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        PublicKey::try_from(s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests_public_key {
+    use super::*;
+
+    #[test]
+    fn test_public_key() {
+        assert!(PublicKey::try_from(vec![1; 32]).is_ok());
+        assert!(PublicKey::try_from(vec![5; 33]).is_ok());
+        assert!(PublicKey::try_from(vec![1; 31]).is_err());
+        assert!(PublicKey::try_from(Vec::new()).is_err());
+
+        let public_key = PublicKey::try_from(STANDARD.encode([7; 32])).unwrap();
+        assert_eq!(Vec::from(public_key), vec![7; 32]);
+        assert!(PublicKey::try_from("not base64!".to_string()).is_err());
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt::Display;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug)]
+pub enum Error {
+    InvalidEncoding,
+    InvalidLength,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::InvalidEncoding => write!(f, "Signature is not valid base64"),
+            Error::InvalidLength => write!(f, "Signature must be 64 bytes long"),
+        }
+    }
+}
+
+/// Signature of a signed prekey by the identity key of its device, checked
+/// by the devices fetching it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Signature(Vec<u8>);
+
+impl TryFrom<Vec<u8>> for Signature {
+    type Error = Error;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        if value.len() != 64 {
+            return Err(Error::InvalidLength);
+        }
+        Ok(Signature(value))
+    }
+}
+
+impl TryFrom<String> for Signature {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let value = STANDARD.decode(value).map_err(|_| Error::InvalidEncoding)?;
+        Signature::try_from(value)
+    }
+}
+
+impl From<Signature> for Vec<u8> {
+    fn from(signature: Signature) -> Self {
+        signature.0
+    }
+}
+
+impl Serialize for Signature {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
+        STANDARD.encode(&self.0).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Signature {
+    fn deserialize<D>(deserializer: D) -> Result<Signature, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Signature::try_from(s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests_signature {
+    use super::*;
+
+    #[test]
+    fn test_signature() {
+        assert!(Signature::try_from(vec![1; 64]).is_ok());
+        assert!(Signature::try_from(vec![1; 63]).is_err());
+        assert!(Signature::try_from(STANDARD.encode([1; 65])).is_err());
+        assert!(Signature::try_from("not base64!".to_string()).is_err());
+    }
+}
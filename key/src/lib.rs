@@ -0,0 +1,7 @@
+mod domain;
+mod application;
+mod adapter;
+
+// Adapter layer
+pub use adapter::driving::http::{handlers, schemas};
+pub use adapter::driven::persistence::sqlx::key_repository::KeyRepository;
@@ -0,0 +1,29 @@
+use async_trait::async_trait;
+use contact::{blocked_by, is_blocked, ContactRepository};
+use sqlx::{Pool, Postgres};
+
+use common::domain::types::id::Id;
+use crate::application::port::driven::block_list::{BlockListTrait, Error};
+
+
+/// Blocks are the contacts flagged as blocked
+pub struct BlockList();
+
+#[async_trait]
+impl BlockListTrait<Pool<Postgres>> for BlockList {
+    async fn is_blocked(&self, conn: &Pool<Postgres>, user_id: Id, blocked_id: Id) -> Result<bool, Error> {
+        is_blocked::execute(
+            conn,
+            &ContactRepository(),
+            is_blocked::Payload { user_id, contact_id: blocked_id },
+        ).await.map_err(|_| Error::Unknown("Error checking the contacts".to_string()))
+    }
+
+    async fn blocked_by(&self, conn: &Pool<Postgres>, user_ids: &[Id], blocked_id: Id) -> Result<Vec<Id>, Error> {
+        blocked_by::execute(
+            conn,
+            &ContactRepository(),
+            blocked_by::Payload { user_ids: user_ids.to_vec(), contact_id: blocked_id },
+        ).await.map_err(|_| Error::Unknown("Error checking the contacts".to_string()))
+    }
+}
@@ -0,0 +1,21 @@
+use async_trait::async_trait;
+use contact::{is_contact, ContactRepository};
+use sqlx::{Pool, Postgres};
+
+use common::domain::types::id::Id;
+use crate::application::port::driven::contact_list::{ContactListTrait, Error};
+
+
+/// Contacts stored by the contact module
+pub struct ContactList();
+
+#[async_trait]
+impl ContactListTrait<Pool<Postgres>> for ContactList {
+    async fn is_contact(&self, conn: &Pool<Postgres>, user_id: Id, contact_id: Id) -> Result<bool, Error> {
+        is_contact::execute(
+            conn,
+            &ContactRepository(),
+            is_contact::Payload { user_id, contact_id },
+        ).await.map_err(|_| Error::Unknown("Error checking the contacts".to_string()))
+    }
+}
@@ -0,0 +1,50 @@
+use mongodb::Client;
+use serde::Serialize;
+
+use common::domain::types::{group::Group, id::Id, recipient::Recipient, sender_type::Sender};
+use group::{group_history::Error, GroupHistoryTrait, Role, RoleChange};
+use crate::{
+    application::port::driven::message_repository::MessageRepositoryTrait,
+    domain::message::{MessageType, NewMessage},
+};
+use super::message_repository::MessageRepository;
+
+
+/// Content of the system message recorded for a role change
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RoleChangeContent {
+    event: &'static str,
+    user_id: Id,
+    role: Role,
+    changed_by: Option<Id>,
+}
+
+/// Group history kept as system messages of the group conversation
+#[derive(Default)]
+pub struct GroupHistory();
+
+impl GroupHistoryTrait<Client> for GroupHistory {
+    async fn add_role_change(&self, conn: &Client, group: Group, change: RoleChange) -> Result<(), Error> {
+        let content = RoleChangeContent {
+            event: "ROLE_CHANGED",
+            user_id: change.user_id,
+            role: change.role,
+            changed_by: change.changed_by,
+        };
+        let new_message = NewMessage {
+            // Automatic changes are attributed to the promoted member
+            sender: Sender::User(change.changed_by.unwrap_or(change.user_id)),
+            recipient: Recipient::Group(group),
+            message_type: MessageType::System,
+            content: serde_json::to_vec(&content).map_err(|_| Error::DatabaseError)?,
+            envelopes: Vec::new(),
+            preview: None,
+        };
+
+        match MessageRepository().create(conn, new_message).await {
+            Ok(_) => Ok(()),
+            Err(_) => Err(Error::DatabaseError),
+        }
+    }
+}
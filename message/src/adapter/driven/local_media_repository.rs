@@ -0,0 +1,140 @@
+use std::{io::ErrorKind, path::{Path, PathBuf}};
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use tokio::{fs, io::AsyncReadExt};
+use uuid::Uuid;
+
+use common::domain::types::media_path::MediaPath;
+use crate::application::port::driven::{
+    errors::MediaError,
+    media_repository::{Media, MediaRepository, PresignedUrl},
+};
+
+
+/// Media stored on disk under the root directory, named by the SHA-256 of
+/// their content so identical uploads are kept once. Blobs are spread in
+/// sub directories named by the first two characters of the hash.
+pub struct LocalMediaRepository();
+
+fn blob_path(root: &Path, hash: &str) -> PathBuf {
+    root.join(&hash[..2]).join(hash)
+}
+
+/// Temporary file next to where the blob goes, the blob is written there
+/// first and renamed so it is never seen half written
+async fn tmp_path(root: &Path, hash: &str) -> Result<PathBuf, MediaError> {
+    let dir = root.join(&hash[..2]);
+    fs::create_dir_all(&dir).await
+        .map_err(|err| MediaError::Unknown(err.to_string()))?;
+    Ok(dir.join(format!(".{}.tmp", Uuid::new_v4())))
+}
+
+async fn store_blob(root: &Path, hash: &str, tmp: &Path, written: std::io::Result<()>) -> Result<(), MediaError> {
+    if let Err(err) = written.and(fs::rename(tmp, blob_path(root, hash)).await) {
+        let _ = fs::remove_file(tmp).await;
+        return Err(MediaError::Unknown(err.to_string()));
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl MediaRepository<PathBuf> for LocalMediaRepository {
+    async fn add(&self, conn: &PathBuf, media: &Media) -> Result<MediaPath, MediaError> {
+        let hash = hex::encode(Sha256::digest(media.data()));
+        let path = MediaPath::new(media.kind(), &hash)
+            .map_err(|err| MediaError::Unknown(err.to_string()))?;
+        if self.exists(conn, &path).await? {
+            return Ok(path);
+        }
+        let tmp = tmp_path(conn, &hash).await?;
+        let written = fs::write(&tmp, media.data()).await;
+        store_blob(conn, &hash, &tmp, written).await?;
+        Ok(path)
+    }
+
+    async fn add_file(&self, conn: &PathBuf, path: &MediaPath, file: &Path) -> Result<(), MediaError> {
+        if self.exists(conn, path).await? {
+            return Ok(());
+        }
+        let tmp = tmp_path(conn, path.hash()).await?;
+        let written = fs::copy(file, &tmp).await.map(|_| ());
+        store_blob(conn, path.hash(), &tmp, written).await
+    }
+
+    async fn get(&self, conn: &PathBuf, path: &MediaPath) -> Result<Vec<u8>, MediaError> {
+        match fs::read(blob_path(conn, path.hash())).await {
+            Ok(data) => Ok(data),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                Err(MediaError::NotFound(path.to_string()))
+            },
+            Err(err) => Err(MediaError::Unknown(err.to_string())),
+        }
+    }
+
+    async fn exists(&self, conn: &PathBuf, path: &MediaPath) -> Result<bool, MediaError> {
+        fs::try_exists(blob_path(conn, path.hash())).await
+            .map_err(|err| MediaError::Unknown(err.to_string()))
+    }
+
+    async fn head(&self, conn: &PathBuf, path: &MediaPath, len: usize) -> Result<(Vec<u8>, u64), MediaError> {
+        let to_error = |err: std::io::Error| match err.kind() {
+            ErrorKind::NotFound => MediaError::NotFound(path.to_string()),
+            _ => MediaError::Unknown(err.to_string()),
+        };
+        let file = fs::File::open(blob_path(conn, path.hash())).await.map_err(to_error)?;
+        let size = file.metadata().await.map_err(to_error)?.len();
+        let mut head = Vec::with_capacity(len);
+        file.take(len as u64).read_to_end(&mut head).await.map_err(to_error)?;
+        Ok((head, size))
+    }
+
+    async fn upload_url(&self, _conn: &PathBuf, _path: &MediaPath) -> Result<Option<PresignedUrl>, MediaError> {
+        Ok(None)
+    }
+
+    async fn download_url(&self, _conn: &PathBuf, _path: &MediaPath) -> Result<Option<PresignedUrl>, MediaError> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_add_and_get() {
+        let root = std::env::temp_dir().join(format!("media-{}", Uuid::new_v4()));
+        let repo = LocalMediaRepository();
+
+        let path = repo.add(&root, &Media::Image(b"hello".to_vec())).await.unwrap();
+        assert_eq!(path.kind(), "image");
+        assert_eq!(path.hash(), hex::encode(Sha256::digest(b"hello")));
+        assert_eq!(repo.get(&root, &path).await.unwrap(), b"hello".to_vec());
+        assert!(repo.exists(&root, &path).await.unwrap());
+        assert_eq!(repo.head(&root, &path, 2).await.unwrap(), (b"he".to_vec(), 5));
+
+        // The same content is stored once
+        let again = repo.add(&root, &Media::File(b"hello".to_vec())).await.unwrap();
+        assert_eq!(again.hash(), path.hash());
+        let mut entries = fs::read_dir(root.join(&path.hash()[..2])).await.unwrap();
+        let mut count = 0;
+        while entries.next_entry().await.unwrap().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 1);
+
+        let missing = MediaPath::new("image", &"0".repeat(64)).unwrap();
+        assert!(matches!(repo.get(&root, &missing).await, Err(MediaError::NotFound(_))));
+        assert!(!repo.exists(&root, &missing).await.unwrap());
+        assert!(matches!(repo.head(&root, &missing, 2).await, Err(MediaError::NotFound(_))));
+
+        let file = root.join("upload");
+        fs::write(&file, b"world").await.unwrap();
+        let uploaded = MediaPath::new("video", &hex::encode(Sha256::digest(b"world"))).unwrap();
+        repo.add_file(&root, &uploaded, &file).await.unwrap();
+        assert_eq!(repo.get(&root, &uploaded).await.unwrap(), b"world".to_vec());
+
+        fs::remove_dir_all(&root).await.unwrap();
+    }
+}
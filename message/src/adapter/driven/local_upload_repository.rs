@@ -0,0 +1,141 @@
+use std::{io::ErrorKind, path::{Path, PathBuf}};
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use tokio::{fs, io::AsyncWriteExt};
+use uuid::Uuid;
+
+use crate::{
+    application::port::driven::upload_repository::{Error, UploadRepositoryTrait},
+    domain::upload::Upload,
+};
+
+
+/// Uploads kept on disk under the root directory, one directory per upload
+/// holding its description and a file per received chunk. Every server has
+/// to share the directory for uploads to resume on any of them.
+pub struct LocalUploadRepository();
+
+const UPLOAD_FILE: &str = "upload.json";
+const ASSEMBLED_FILE: &str = "assembled";
+const CHUNK_EXTENSION: &str = "part";
+
+fn upload_dir(root: &Path, id: Uuid) -> PathBuf {
+    root.join(id.to_string())
+}
+
+fn to_error(err: std::io::Error) -> Error {
+    match err.kind() {
+        ErrorKind::NotFound => Error::NotFound(err.to_string()),
+        _ => Error::Unknown(err.to_string()),
+    }
+}
+
+/// Indexes of the chunks stored in the upload directory, in order
+async fn received_chunks(dir: &Path) -> Result<Vec<u64>, Error> {
+    let mut entries = fs::read_dir(dir).await.map_err(to_error)?;
+    let mut chunks = Vec::new();
+    while let Some(entry) = entries.next_entry().await.map_err(to_error)? {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(CHUNK_EXTENSION) {
+            continue;
+        }
+        if let Some(index) = path.file_stem().and_then(|stem| stem.to_str()?.parse().ok()) {
+            chunks.push(index);
+        }
+    }
+    chunks.sort_unstable();
+    Ok(chunks)
+}
+
+#[async_trait]
+impl UploadRepositoryTrait<PathBuf> for LocalUploadRepository {
+    async fn create(&self, conn: &PathBuf, upload: &Upload) -> Result<(), Error> {
+        let dir = upload_dir(conn, upload.id);
+        fs::create_dir_all(&dir).await.map_err(to_error)?;
+        let description = serde_json::to_vec(upload)
+            .map_err(|err| Error::Unknown(err.to_string()))?;
+        fs::write(dir.join(UPLOAD_FILE), description).await.map_err(to_error)
+    }
+
+    async fn find_by_id(&self, conn: &PathBuf, id: Uuid) -> Result<Upload, Error> {
+        let dir = upload_dir(conn, id);
+        let description = fs::read(dir.join(UPLOAD_FILE)).await.map_err(to_error)?;
+        let mut upload: Upload = serde_json::from_slice(&description)
+            .map_err(|err| Error::Unknown(err.to_string()))?;
+        upload.received_chunks = received_chunks(&dir).await?;
+        Ok(upload)
+    }
+
+    async fn add_chunk(&self, conn: &PathBuf, id: Uuid, index: u64, data: &[u8]) -> Result<(), Error> {
+        let dir = upload_dir(conn, id);
+        if !fs::try_exists(dir.join(UPLOAD_FILE)).await.map_err(to_error)? {
+            return Err(Error::NotFound(id.to_string()));
+        }
+        // Written aside and renamed so a chunk is never seen half written
+        let tmp = dir.join(format!(".{}.tmp", Uuid::new_v4()));
+        let chunk = dir.join(format!("{}.{}", index, CHUNK_EXTENSION));
+        if let Err(err) = fs::write(&tmp, data).await.and(fs::rename(&tmp, &chunk).await) {
+            let _ = fs::remove_file(&tmp).await;
+            return Err(to_error(err));
+        }
+        Ok(())
+    }
+
+    async fn assemble(&self, conn: &PathBuf, id: Uuid) -> Result<(PathBuf, String), Error> {
+        let dir = upload_dir(conn, id);
+        let path = dir.join(ASSEMBLED_FILE);
+        let mut file = fs::File::create(&path).await.map_err(to_error)?;
+        let mut hasher = Sha256::new();
+        // One chunk in memory at a time
+        for index in received_chunks(&dir).await? {
+            let chunk = fs::read(dir.join(format!("{}.{}", index, CHUNK_EXTENSION))).await
+                .map_err(to_error)?;
+            hasher.update(&chunk);
+            file.write_all(&chunk).await.map_err(to_error)?;
+        }
+        file.flush().await.map_err(to_error)?;
+        Ok((path, hex::encode(hasher.finalize())))
+    }
+
+    async fn delete(&self, conn: &PathBuf, id: Uuid) -> Result<(), Error> {
+        match fs::remove_dir_all(upload_dir(conn, id)).await {
+            Ok(_) => Ok(()),
+            Err(err) => Err(to_error(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::domain::types::{id::Id, media_path::MediaPath};
+    use super::*;
+
+    #[tokio::test]
+    async fn test_upload_chunks() {
+        let root = std::env::temp_dir().join(format!("uploads-{}", Uuid::new_v4()));
+        let repo = LocalUploadRepository();
+        let user_id: Id = Uuid::new_v4().try_into().unwrap();
+        let hash = hex::encode(Sha256::digest(b"hello world"));
+        let upload = Upload::new(user_id, MediaPath::new("file", &hash).unwrap(), 11).unwrap();
+        repo.create(&root, &upload).await.unwrap();
+
+        // Chunks can arrive in any order and be sent again
+        repo.add_chunk(&root, upload.id, 1, b" world").await.unwrap();
+        repo.add_chunk(&root, upload.id, 0, b"hello").await.unwrap();
+        repo.add_chunk(&root, upload.id, 0, b"hello").await.unwrap();
+        let found = repo.find_by_id(&root, upload.id).await.unwrap();
+        assert_eq!(found.received_chunks, vec![0, 1]);
+        assert_eq!(found.media_path, upload.media_path);
+
+        let (file, assembled_hash) = repo.assemble(&root, upload.id).await.unwrap();
+        assert_eq!(assembled_hash, hash);
+        assert_eq!(fs::read(&file).await.unwrap(), b"hello world".to_vec());
+
+        repo.delete(&root, upload.id).await.unwrap();
+        assert!(matches!(repo.find_by_id(&root, upload.id).await, Err(Error::NotFound(_))));
+        assert!(matches!(repo.add_chunk(&root, upload.id, 0, b"hello").await, Err(Error::NotFound(_))));
+
+        fs::remove_dir_all(&root).await.unwrap();
+    }
+}
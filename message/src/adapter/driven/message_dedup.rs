@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use deadpool_redis::{redis::{cmd, RedisError}, Pool};
+use uuid::Uuid;
+
+use common::domain::types::id::Id;
+use crate::application::port::driven::message_dedup::{Claim, Error, MessageDedupTrait};
+
+
+/// Client ids are kept as keys expiring after the window, holding the id and
+/// creation time of the stored message or an empty value while it is being
+/// stored.
+pub struct MessageDedup();
+
+fn key(sender: Id, client_id: &str) -> String {
+    format!("client_messages:{}:{}", sender, client_id)
+}
+
+fn parse_sent(value: &str) -> Option<Claim> {
+    let (message_id, created_at) = value.split_once(' ')?;
+    let message_id = Uuid::parse_str(message_id).ok()?;
+    let created_at = Utc.timestamp_millis_opt(created_at.parse().ok()?).single()?;
+    Some(Claim::Sent(message_id, created_at))
+}
+
+#[async_trait]
+impl MessageDedupTrait<Pool> for MessageDedup {
+    async fn claim(
+        &self,
+        conn: &Pool,
+        sender: Id,
+        client_id: &str,
+        window: Duration,
+    ) -> Result<Claim, Error> {
+        let mut conn = conn.get().await.map_err(|e| {
+            Error::Unknown(format!("Failed to get connection from pool: {}", e))
+        })?;
+        let claimed: Result<Option<String>, RedisError> = cmd("SET")
+            .arg(key(sender, client_id))
+            .arg("")
+            .arg("NX")
+            .arg("EX")
+            .arg(window.as_secs().max(1))
+            .query_async(&mut conn)
+            .await;
+        match claimed {
+            Ok(Some(_)) => return Ok(Claim::New),
+            Ok(None) => (),
+            Err(err) => return Err(Error::Unknown(format!("Failed to set value {}", err))),
+        }
+        let res: Result<Option<String>, RedisError> = cmd("GET")
+            .arg(key(sender, client_id))
+            .query_async(&mut conn)
+            .await;
+        match res {
+            Ok(Some(value)) => Ok(parse_sent(&value).unwrap_or(Claim::Pending)),
+            // Expired in between, the previous send is long gone
+            Ok(None) => Ok(Claim::Pending),
+            Err(err) => Err(Error::Unknown(format!("Failed to get value {}", err))),
+        }
+    }
+
+    async fn confirm(
+        &self,
+        conn: &Pool,
+        sender: Id,
+        client_id: &str,
+        message_id: Uuid,
+        created_at: DateTime<Utc>,
+        window: Duration,
+    ) -> Result<(), Error> {
+        let mut conn = conn.get().await.map_err(|e| {
+            Error::Unknown(format!("Failed to get connection from pool: {}", e))
+        })?;
+        let res: Result<(), RedisError> = cmd("SET")
+            .arg(key(sender, client_id))
+            .arg(format!("{} {}", message_id, created_at.timestamp_millis()))
+            .arg("EX")
+            .arg(window.as_secs().max(1))
+            .query_async(&mut conn)
+            .await;
+        match res {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::Unknown(format!("Failed to set value {}", err))),
+        }
+    }
+
+    async fn release(&self, conn: &Pool, sender: Id, client_id: &str) -> Result<(), Error> {
+        let mut conn = conn.get().await.map_err(|e| {
+            Error::Unknown(format!("Failed to get connection from pool: {}", e))
+        })?;
+        let res: Result<(), RedisError> = cmd("DEL")
+            .arg(key(sender, client_id))
+            .query_async(&mut conn)
+            .await;
+        match res {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::Unknown(format!("Failed to remove value {}", err))),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use deadpool_redis::{redis::{cmd, pipe, RedisError}, Pool};
+use uuid::Uuid;
+
+use common::domain::types::id::Id;
+use crate::{
+    application::port::driven::message_queue::{Error, MessageQueueTrait},
+    domain::message::Message,
+};
+
+
+/// Pending messages are kept in a sorted set per recipient, scored by the
+/// creation time of the message. The set is trimmed to the newest messages
+/// and expires unless a message is added.
+pub struct MessageQueue();
+
+fn key(recipient: Id) -> String {
+    format!("pending_messages:{}", recipient)
+}
+
+#[async_trait]
+impl MessageQueueTrait<Pool> for MessageQueue {
+    async fn add(
+        &self,
+        conn: &Pool,
+        recipient: Id,
+        message: &Message,
+        max: u64,
+        ttl: Duration,
+    ) -> Result<(), Error> {
+        let mut conn = conn.get().await.map_err(|e| {
+            Error::Unknown(format!("Failed to get connection from pool: {}", e))
+        })?;
+        let key = key(recipient);
+        // Ranks count from the oldest, the last `max` ones are kept
+        let last_dropped = -(max.min(i64::MAX as u64) as i64) - 1;
+        let res: Result<(), RedisError> = pipe()
+            .atomic()
+            .cmd("ZADD").arg(&key)
+            .arg(message.created_at.timestamp_millis())
+            .arg(message.id.to_string()).ignore()
+            .cmd("ZREMRANGEBYRANK").arg(&key).arg(0).arg(last_dropped).ignore()
+            .cmd("EXPIRE").arg(&key).arg(ttl.as_secs().max(1)).ignore()
+            .query_async(&mut conn)
+            .await;
+        match res {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::Unknown(format!("Failed to add value {}", err))),
+        }
+    }
+
+    async fn find_list(&self, conn: &Pool, recipient: Id) -> Result<Vec<Uuid>, Error> {
+        let mut conn = conn.get().await.map_err(|e| {
+            Error::Unknown(format!("Failed to get connection from pool: {}", e))
+        })?;
+        let res: Result<Vec<String>, RedisError> = cmd("ZRANGE")
+            .arg(key(recipient))
+            .arg(0)
+            .arg(-1)
+            .query_async(&mut conn)
+            .await;
+        match res {
+            Ok(ids) => Ok(ids.iter().filter_map(|id| Uuid::parse_str(id).ok()).collect()),
+            Err(err) => Err(Error::Unknown(format!("Failed to get values {}", err))),
+        }
+    }
+
+    async fn remove(&self, conn: &Pool, recipient: Id, message_id: Uuid) -> Result<(), Error> {
+        let mut conn = conn.get().await.map_err(|e| {
+            Error::Unknown(format!("Failed to get connection from pool: {}", e))
+        })?;
+        let res: Result<(), RedisError> = cmd("ZREM")
+            .arg(key(recipient))
+            .arg(message_id.to_string())
+            .query_async(&mut conn)
+            .await;
+        match res {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::Unknown(format!("Failed to remove value {}", err))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration as ChronoDuration, Utc};
+    use common::adapter::cache;
+    use common::domain::types::{recipient::Recipient, sender_type::Sender};
+    use crate::domain::message::MessageType;
+    use super::*;
+
+    fn new_message(recipient: Id, age: i64) -> Message {
+        let created_at = Utc::now() - ChronoDuration::seconds(age);
+        Message {
+            id: Uuid::new_v4(),
+            sender: Sender::User(Uuid::new_v4().try_into().unwrap()),
+            recipient: Recipient::User(recipient),
+            message_type: MessageType::Text,
+            content: b"hello".to_vec(),
+            envelopes: Vec::new(),
+            deleted: false,
+            edit_history: Vec::new(),
+            preview: None,
+            reactions: Vec::new(),
+            received_by: Vec::new(),
+            read_by: Vec::new(),
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at CACHE_URL"]
+    async fn test_trim_and_expire() {
+        let pool = cache::create_pool().await;
+        let queue = MessageQueue();
+        let recipient: Id = Uuid::new_v4().try_into().unwrap();
+        let ttl = Duration::from_secs(60);
+
+        let messages: Vec<Message> = (0..5).rev().map(|age| new_message(recipient, age)).collect();
+        for message in &messages {
+            queue.add(&pool, recipient, message, 3, ttl).await.unwrap();
+        }
+        // The oldest are dropped
+        let ids: Vec<Uuid> = messages[2..].iter().map(|message| message.id).collect();
+        assert_eq!(queue.find_list(&pool, recipient).await.unwrap(), ids);
+
+        let mut conn = pool.get().await.unwrap();
+        let remaining: i64 = cmd("TTL").arg(key(recipient)).query_async(&mut conn).await.unwrap();
+        assert!(remaining > 0 && remaining <= 60);
+        let _: () = cmd("DEL").arg(key(recipient)).query_async(&mut conn).await.unwrap();
+    }
+}
@@ -1,60 +1,102 @@
+use std::collections::HashMap;
+
 use axum::async_trait;
 use uuid::Uuid;
-use mongodb::{bson::{Document, doc}, options::FindOptions, Client, Collection};
+use mongodb::{bson::{self, Document, doc, from_document, to_bson}, options::{FindOneAndUpdateOptions, FindOneOptions, FindOptions, ReturnDocument, UpdateOptions}, Client, Collection};
 use futures::TryStreamExt;
+use serde::Deserialize;
 
-use common::domain::types::{recipient::Recipient, sender_type::Sender};
+use common::domain::types::{id::Id, media_path::MediaPath, recipient::Recipient};
 use crate::{
     application::port::driven::message_repository::{Error, MessageRepositoryTrait, UpdateMessage}, 
-    domain::message::{Message, NewMessage}
+    domain::{
+        conversation::Conversation,
+        message::{Message, NewMessage, Receipt},
+        reaction::{Reaction, MAX_REACTIONS_PER_USER},
+        types::{cursor::Cursor, emoji::Emoji},
+    },
 };
 
 
+/// Messages are kept in `messages`. The inbox reads `conversations`, a
+/// summary per user and peer with the latest message and the unread count,
+/// updated as messages are created and read. `media_owners` holds the
+/// uploaders of each media.
 pub struct MessageRepository();
 
+#[derive(Deserialize)]
+struct LastMessage {
+    id: bson::Uuid,
+}
+
+#[derive(Deserialize)]
+struct ConversationRow {
+    last: LastMessage,
+    unread: i64,
+}
+
 #[async_trait]
 impl MessageRepositoryTrait<Client> for MessageRepository {
     async fn create(&self, conn: &Client, new_message: NewMessage) -> Result<Message, Error> {
+        // Truncated to the precision stored by the database so cursors match
+        let now = bson::DateTime::now().to_chrono();
         let message = Message {
             id: Uuid::new_v4(),
             sender: new_message.sender,
             recipient: new_message.recipient,
             message_type: new_message.message_type,
             content: new_message.content,
+            envelopes: new_message.envelopes,
             deleted: false,
-            received_at: None,
-            read_at: None,
-            created_at: chrono::Utc::now(),
-            updated_at: chrono::Utc::now(),
+            edit_history: Vec::new(),
+            preview: new_message.preview,
+            reactions: Vec::new(),
+            received_by: Vec::new(),
+            read_by: Vec::new(),
+            created_at: now,
+            updated_at: now,
         };
         let collection: Collection<Message> = conn.database("chat_app").collection("messages");
-        let res = collection.insert_one(&message, None).await;
-        match res {
-            Ok(_) => Ok(message),
-            Err(err) => Err(Error::DatabaseError(err.to_string())),
-        }
+        collection.insert_one(&message, None).await
+            .map_err(|err| Error::DatabaseError(err.to_string()))?;
+        update_conversations(conn, &message).await?;
+        Ok(message)
     }
     
-    async fn find_list(
-        &self, 
-        conn: &Client, 
-        sender: Sender,
-        recipient: Recipient,
-        limit: i64, 
-        offset: Option<u64>, 
-        ascending: bool,
+    async fn find_conversation(
+        &self,
+        conn: &Client,
+        user_id: Id,
+        peer_id: Id,
+        limit: i64,
+        before: Option<Cursor>,
     ) -> Result<Vec<Message>, Error> {
         let collection: Collection<Message> = conn.database("chat_app").collection("messages");
-        let filter = doc! {
-            "sender": Into::<String>::into(sender),
-            "recipient": Into::<String>::into(recipient),
+        let user_id = Into::<String>::into(user_id);
+        let peer_id = Into::<String>::into(peer_id);
+        let mut filter = doc! {
+            "$or": [
+                { "sender.User": &user_id, "recipient.User": &peer_id },
+                { "sender.User": &peer_id, "recipient.User": &user_id },
+                { "recipient.Group.id": &peer_id, "recipient.Group.members": &user_id },
+            ],
         };
+        if let Some(before) = before {
+            let created_at = bson::DateTime::from_chrono(before.created_at);
+            let id = bson::Uuid::from(before.id);
+            filter = doc! {
+                "$and": [
+                    filter,
+                    { "$or": [
+                        { "created_at": { "$lt": created_at } },
+                        { "created_at": created_at, "id": { "$lt": id } },
+                    ] },
+                ],
+            };
+        }
         let options = FindOptions::builder()
             .limit(limit)
-            .skip(offset)
-            .sort(doc! {
-                "created_at": if ascending { 1 } else { -1 },
-            })
+            .sort(doc! { "created_at": -1, "id": -1 })
             .build();
         let mut cursor = collection.find(filter, options).await
             .map_err(|err| Error::DatabaseError(err.to_string()))?;
@@ -67,9 +109,74 @@ impl MessageRepositoryTrait<Client> for MessageRepository {
         Ok(messages)
     }
     
+    async fn find_conversations(
+        &self,
+        conn: &Client,
+        user_id: Id,
+        groups: &[Id],
+        limit: i64,
+        before: Option<Cursor>,
+    ) -> Result<Vec<Conversation>, Error> {
+        let conversations: Collection<Document> = conn.database("chat_app").collection("conversations");
+        let groups: Vec<String> = groups.iter().map(|&group| group.into()).collect();
+        let mut filter = doc! {
+            "user_id": Into::<String>::into(user_id),
+            "$or": [{ "group": false }, { "peer": { "$in": groups } }],
+        };
+        if let Some(before) = before {
+            let created_at = bson::DateTime::from_chrono(before.created_at);
+            let id = bson::Uuid::from(before.id);
+            filter = doc! {
+                "$and": [
+                    filter,
+                    { "$or": [
+                        { "last.created_at": { "$lt": created_at } },
+                        { "last.created_at": created_at, "last.id": { "$lt": id } },
+                    ] },
+                ],
+            };
+        }
+        let options = FindOptions::builder()
+            .limit(limit)
+            .sort(doc! { "last.created_at": -1, "last.id": -1 })
+            .build();
+        let mut cursor = conversations.find(filter, options).await
+            .map_err(|err| Error::DatabaseError(err.to_string()))?;
+        let mut rows = Vec::new();
+        while let Some(row) = cursor.try_next().await
+            .map_err(|err| Error::DatabaseError(err.to_string()))?
+        {
+            let row: ConversationRow = from_document(row)
+                .map_err(|err| Error::DatabaseError(err.to_string()))?;
+            rows.push(row);
+        }
+
+        let collection: Collection<Message> = conn.database("chat_app").collection("messages");
+        let ids: Vec<bson::Uuid> = rows.iter().map(|row| row.last.id).collect();
+        let mut cursor = collection.find(doc! { "id": { "$in": ids } }, None).await
+            .map_err(|err| Error::DatabaseError(err.to_string()))?;
+        let mut messages = HashMap::new();
+        while let Some(message) = cursor.try_next().await
+            .map_err(|err| Error::DatabaseError(err.to_string()))?
+        {
+            messages.insert(message.id, message);
+        }
+
+        Ok(rows.into_iter()
+            .filter_map(|row| {
+                let last_message = messages.remove(&row.last.id.into())?;
+                Some(Conversation {
+                    peer: Conversation::peer_of(&last_message, user_id),
+                    last_message,
+                    unread_count: row.unread.max(0) as u64,
+                })
+            })
+            .collect())
+    }
+    
     async fn find_by_id(&self, conn: &Client, id: Uuid) -> Result<Message, Error> {
         let collection: Collection<Message> = conn.database("chat_app").collection("messages");
-        let result = collection.find_one(doc! { "id": Into::<String>::into(id) }, None).await
+        let result = collection.find_one(doc! { "id": bson::Uuid::from(id) }, None).await
             .map_err(|err| Error::DatabaseError(err.to_string()))?;
         match result {
             Some(message) => Ok(message),
@@ -79,16 +186,33 @@ impl MessageRepositoryTrait<Client> for MessageRepository {
     
     async fn update(&self, conn: &Client, message: &UpdateMessage) -> Result<Message, Error> {
         let collection: Collection<Message> = conn.database("chat_app").collection("messages");
-        let filter = doc! { "id": Into::<String>::into(message.id) };
+        let filter = doc! { "id": bson::Uuid::from(message.id) };
 
         let mut doc = Document::new();
 
-        if let Some(received_at) = message.received_at {
-            doc.insert("received_at", received_at.map(|x| x.to_string()));
+        // Serialized the same way as the stored `Message` so it can be read back
+        if let Some(content) = &message.content {
+            doc.insert("content", to_bson(content).map_err(|err| Error::DatabaseError(err.to_string()))?);
+        }
+
+        if let Some(envelopes) = &message.envelopes {
+            doc.insert("envelopes", to_bson(envelopes).map_err(|err| Error::DatabaseError(err.to_string()))?);
         }
 
-        if let Some(read_at) = message.read_at {
-            doc.insert("read_at", read_at.map(|x| x.to_string()));
+        if let Some(edit_history) = &message.edit_history {
+            doc.insert("edit_history", to_bson(edit_history).map_err(|err| Error::DatabaseError(err.to_string()))?);
+        }
+
+        if let Some(preview) = &message.preview {
+            doc.insert("preview", to_bson(preview).map_err(|err| Error::DatabaseError(err.to_string()))?);
+        }
+
+        if let Some(deleted) = message.deleted {
+            doc.insert("deleted", deleted);
+        }
+
+        if let Some(updated_at) = message.updated_at {
+            doc.insert("updated_at", bson::DateTime::from_chrono(updated_at));
         }
         
         let update = doc! { "$set": doc };
@@ -103,11 +227,197 @@ impl MessageRepositoryTrait<Client> for MessageRepository {
     
     async fn delete(&self, conn: &Client, id: Uuid) -> Result<(), Error> {
         let collection: Collection<Message> = conn.database("chat_app").collection("messages");
-        let result = collection.delete_one(doc! { "id": Into::<String>::into(id) }, None).await
+        let result = collection.delete_one(doc! { "id": bson::Uuid::from(id) }, None).await
             .map_err(|err| Error::DatabaseError(err.to_string()))?;
         match result.deleted_count {
             1 => Ok(()),
             _ => Err(Error::NotFound("".to_string())),
         }
     }
+    
+    async fn add_received(&self, conn: &Client, id: Uuid, receipt: &Receipt) -> Result<Message, Error> {
+        let collection: Collection<Message> = conn.database("chat_app").collection("messages");
+        push_receipt(&collection, id, "received_by", receipt).await?;
+        self.find_by_id(conn, id).await
+    }
+    
+    async fn add_read(&self, conn: &Client, id: Uuid, receipt: &Receipt) -> Result<Message, Error> {
+        let collection: Collection<Message> = conn.database("chat_app").collection("messages");
+        push_receipt(&collection, id, "received_by", receipt).await?;
+        let first_read = push_receipt(&collection, id, "read_by", receipt).await?;
+        let message = self.find_by_id(conn, id).await?;
+        if first_read {
+            mark_read(conn, &message, receipt.user_id).await?;
+        }
+        Ok(message)
+    }
+    
+    async fn add_reaction(&self, conn: &Client, id: Uuid, reaction: &Reaction) -> Result<Option<Message>, Error> {
+        let collection: Collection<Message> = conn.database("chat_app").collection("messages");
+        let user_id = Into::<String>::into(reaction.user_id);
+        let emoji = Into::<String>::into(reaction.emoji.clone());
+        // Matching and pushing in one update keeps concurrent duplicates and
+        // reactions over the limit out
+        let filter = doc! {
+            "id": bson::Uuid::from(id),
+            "reactions": { "$not": { "$elemMatch": { "user_id": &user_id, "emoji": &emoji } } },
+            "$expr": { "$lt": [
+                { "$size": { "$filter": {
+                    "input": { "$ifNull": ["$reactions", []] },
+                    "cond": { "$eq": ["$$this.user_id", &user_id] },
+                } } },
+                MAX_REACTIONS_PER_USER as i64,
+            ] },
+        };
+        let reaction = to_bson(reaction).map_err(|err| Error::DatabaseError(err.to_string()))?;
+        let update = doc! { "$push": { "reactions": reaction } };
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        collection.find_one_and_update(filter, update, options).await
+            .map_err(|err| Error::DatabaseError(err.to_string()))
+    }
+    
+    async fn remove_reaction(
+        &self,
+        conn: &Client,
+        id: Uuid,
+        user_id: Id,
+        emoji: &Emoji,
+    ) -> Result<Option<Message>, Error> {
+        let collection: Collection<Message> = conn.database("chat_app").collection("messages");
+        let user_id = Into::<String>::into(user_id);
+        let emoji = Into::<String>::into(emoji.clone());
+        let filter = doc! {
+            "id": bson::Uuid::from(id),
+            "reactions": { "$elemMatch": { "user_id": &user_id, "emoji": &emoji } },
+        };
+        let update = doc! { "$pull": { "reactions": { "user_id": &user_id, "emoji": &emoji } } };
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        collection.find_one_and_update(filter, update, options).await
+            .map_err(|err| Error::DatabaseError(err.to_string()))
+    }
+
+    async fn references_media(&self, conn: &Client, user_id: Id, path: &MediaPath) -> Result<bool, Error> {
+        let collection: Collection<Document> = conn.database("chat_app").collection("messages");
+        let user_id = Into::<String>::into(user_id);
+        let path = Into::<String>::into(path.clone());
+        // The content is stored like the one of `Message`
+        let content = to_bson(&path.clone().into_bytes())
+            .map_err(|err| Error::DatabaseError(err.to_string()))?;
+        // Read through the indexes of the messages of the user
+        let filter = doc! {
+            "$and": [
+                { "$or": [
+                    { "sender.User": &user_id },
+                    { "recipient.User": &user_id },
+                    { "recipient.Group.members": &user_id },
+                ] },
+                { "$or": [
+                    { "content": content },
+                    { "preview.thumbnails.path": &path },
+                ] },
+            ],
+        };
+        let options = FindOneOptions::builder().projection(doc! { "_id": 1 }).build();
+        let found = collection.find_one(filter, options).await
+            .map_err(|err| Error::DatabaseError(err.to_string()))?;
+        Ok(found.is_some())
+    }
+
+    async fn add_media_owner(&self, conn: &Client, user_id: Id, path: &MediaPath) -> Result<(), Error> {
+        let collection: Collection<Document> = conn.database("chat_app").collection("media_owners");
+        let owner = doc! {
+            "user_id": Into::<String>::into(user_id),
+            "path": Into::<String>::into(path.clone()),
+        };
+        let options = UpdateOptions::builder().upsert(true).build();
+        collection.update_one(owner.clone(), doc! { "$setOnInsert": owner }, options).await
+            .map_err(|err| Error::DatabaseError(err.to_string()))?;
+        Ok(())
+    }
+
+    async fn owns_media(&self, conn: &Client, user_id: Id, path: &MediaPath) -> Result<bool, Error> {
+        let collection: Collection<Document> = conn.database("chat_app").collection("media_owners");
+        let filter = doc! {
+            "user_id": Into::<String>::into(user_id),
+            "path": Into::<String>::into(path.clone()),
+        };
+        let options = FindOneOptions::builder().projection(doc! { "_id": 1 }).build();
+        let found = collection.find_one(filter, options).await
+            .map_err(|err| Error::DatabaseError(err.to_string()))?;
+        Ok(found.is_some())
+    }
+}
+
+/// Push `receipt` to the receipts in `field` unless its user already has
+/// one, true when pushed
+async fn push_receipt(
+    collection: &Collection<Message>,
+    id: Uuid,
+    field: &str,
+    receipt: &Receipt,
+) -> Result<bool, Error> {
+    let user_id = Into::<String>::into(receipt.user_id);
+    let filter = doc! {
+        "id": bson::Uuid::from(id),
+        field: { "$not": { "$elemMatch": { "user_id": &user_id } } },
+    };
+    let receipt = to_bson(receipt).map_err(|err| Error::DatabaseError(err.to_string()))?;
+    let result = collection.update_one(filter, doc! { "$push": { field: receipt } }, None).await
+        .map_err(|err| Error::DatabaseError(err.to_string()))?;
+    Ok(result.modified_count == 1)
+}
+
+/// Make `message` the last one of the conversations of its sender and
+/// recipients when it is newer, counting it as unread for the recipients
+async fn update_conversations(conn: &Client, message: &Message) -> Result<(), Error> {
+    let conversations: Collection<Document> = conn.database("chat_app").collection("conversations");
+    let sender: Id = message.sender.clone().into();
+    let mut users = vec![sender];
+    match &message.recipient {
+        Recipient::User(recipient) if *recipient != sender => users.push(*recipient),
+        Recipient::User(_) => (),
+        Recipient::Group(group) => {
+            users.extend(group.members.iter().filter(|&&member| member != sender));
+        },
+    }
+    let group = matches!(message.recipient, Recipient::Group(_));
+    // Documents compare field by field, the latest message wins
+    let last = doc! {
+        "created_at": bson::DateTime::from_chrono(message.created_at),
+        "id": bson::Uuid::from(message.id),
+    };
+    for user_id in users {
+        let peer: String = Conversation::peer_of(message, user_id).into();
+        let filter = doc! { "user_id": Into::<String>::into(user_id), "peer": peer };
+        let update = doc! {
+            "$set": { "group": group },
+            "$max": { "last": last.clone() },
+            "$inc": { "unread": if user_id == sender { 0 } else { 1 } },
+        };
+        let options = UpdateOptions::builder().upsert(true).build();
+        conversations.update_one(filter, update, options).await
+            .map_err(|err| Error::DatabaseError(err.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Count `message` as read by `user_id` in its conversation
+async fn mark_read(conn: &Client, message: &Message, user_id: Id) -> Result<(), Error> {
+    if Id::from(message.sender.clone()) == user_id {
+        return Ok(());
+    }
+    let conversations: Collection<Document> = conn.database("chat_app").collection("conversations");
+    let peer: String = Conversation::peer_of(message, user_id).into();
+    let filter = doc! {
+        "user_id": Into::<String>::into(user_id),
+        "peer": peer,
+        "unread": { "$gt": 0 },
+    };
+    conversations.update_one(filter, doc! { "$inc": { "unread": -1 } }, None).await
+        .map_err(|err| Error::DatabaseError(err.to_string()))?;
+    Ok(())
 }
@@ -1 +1,13 @@
-pub mod message_repository;
\ No newline at end of file
+pub mod message_repository;
+pub mod message_queue;
+pub mod receipt_queue;
+pub mod local_media_repository;
+pub mod group_history;
+pub mod block_list;
+pub mod s3_media_repository;
+pub mod store_media_repository;
+pub mod local_upload_repository;
+pub mod presence_repository;
+pub mod contact_list;
+pub mod message_dedup;
+pub mod presence_registry;
\ No newline at end of file
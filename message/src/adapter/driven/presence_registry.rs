@@ -0,0 +1,264 @@
+use std::{fmt::Display, time::Duration};
+
+use async_trait::async_trait;
+use deadpool_redis::{redis::{cmd, pipe, RedisError}, Pool};
+use uuid::Uuid;
+
+use common::domain::types::id::Id;
+use crate::application::port::driven::presence_registry::{Error, PresenceRegistryTrait};
+
+
+/// Connections are counted in a hash per user with a field per node. Every
+/// node keeps a key expiring unless refreshed, the counts of the nodes whose
+/// key expired are dropped the next time the hash is read. Subscriptions are
+/// kept in a set of subscribers per user and a set of followed users per
+/// subscriber.
+pub struct PresenceRegistry();
+
+const NODE_PREFIX: &str = "presence:node:";
+
+fn connections_key(user_id: Id) -> String {
+    format!("presence:connections:{}", user_id)
+}
+
+fn subscribers_key(user_id: impl Display) -> String {
+    format!("presence:subscribers:{}", user_id)
+}
+
+fn following_key(subscriber: Id) -> String {
+    format!("presence:following:{}", subscriber)
+}
+
+/// KEYS[1] connections, ARGV node id, node prefix and node ttl. Returns 1
+/// when no live node had a connection.
+const CONNECT: &str = r"
+redis.call('SET', ARGV[2] .. ARGV[1], 1, 'EX', ARGV[3])
+local online = 0
+for _, node in ipairs(redis.call('HKEYS', KEYS[1])) do
+    if redis.call('EXISTS', ARGV[2] .. node) == 1 then
+        online = 1
+    else
+        redis.call('HDEL', KEYS[1], node)
+    end
+end
+redis.call('HINCRBY', KEYS[1], ARGV[1], 1)
+return 1 - online
+";
+
+/// KEYS[1] connections, ARGV node id, node prefix and node ttl. Returns 1
+/// when no live node has a connection left.
+const DISCONNECT: &str = r"
+redis.call('SET', ARGV[2] .. ARGV[1], 1, 'EX', ARGV[3])
+if redis.call('HINCRBY', KEYS[1], ARGV[1], -1) <= 0 then
+    redis.call('HDEL', KEYS[1], ARGV[1])
+end
+for _, node in ipairs(redis.call('HKEYS', KEYS[1])) do
+    if redis.call('EXISTS', ARGV[2] .. node) == 1 then
+        return 0
+    end
+    redis.call('HDEL', KEYS[1], node)
+end
+return 1
+";
+
+/// KEYS[1] connections, ARGV node prefix. Returns 1 when a live node has a
+/// connection.
+const IS_ONLINE: &str = r"
+for _, node in ipairs(redis.call('HKEYS', KEYS[1])) do
+    if redis.call('EXISTS', ARGV[1] .. node) == 1 then
+        return 1
+    end
+end
+return 0
+";
+
+/// Time a node stays alive without refreshing itself, also renewed by every
+/// connection opening or closing on it
+const NODE_TTL: Duration = Duration::from_secs(30);
+
+#[async_trait]
+impl PresenceRegistryTrait<Pool> for PresenceRegistry {
+    async fn connect(&self, conn: &Pool, node_id: Uuid, user_id: Id) -> Result<bool, Error> {
+        let mut conn = conn.get().await.map_err(|e| {
+            Error::Unknown(format!("Failed to get connection from pool: {}", e))
+        })?;
+        let res: Result<i64, RedisError> = cmd("EVAL")
+            .arg(CONNECT)
+            .arg(1)
+            .arg(connections_key(user_id))
+            .arg(node_id.to_string())
+            .arg(NODE_PREFIX)
+            .arg(NODE_TTL.as_secs())
+            .query_async(&mut conn)
+            .await;
+        match res {
+            Ok(came_online) => Ok(came_online == 1),
+            Err(err) => Err(Error::Unknown(format!("Failed to count connection {}", err))),
+        }
+    }
+
+    async fn disconnect(&self, conn: &Pool, node_id: Uuid, user_id: Id) -> Result<bool, Error> {
+        let mut conn = conn.get().await.map_err(|e| {
+            Error::Unknown(format!("Failed to get connection from pool: {}", e))
+        })?;
+        let res: Result<i64, RedisError> = cmd("EVAL")
+            .arg(DISCONNECT)
+            .arg(1)
+            .arg(connections_key(user_id))
+            .arg(node_id.to_string())
+            .arg(NODE_PREFIX)
+            .arg(NODE_TTL.as_secs())
+            .query_async(&mut conn)
+            .await;
+        match res {
+            Ok(went_offline) => Ok(went_offline == 1),
+            Err(err) => Err(Error::Unknown(format!("Failed to count connection {}", err))),
+        }
+    }
+
+    async fn is_online(&self, conn: &Pool, user_id: Id) -> Result<bool, Error> {
+        let mut conn = conn.get().await.map_err(|e| {
+            Error::Unknown(format!("Failed to get connection from pool: {}", e))
+        })?;
+        let res: Result<i64, RedisError> = cmd("EVAL")
+            .arg(IS_ONLINE)
+            .arg(1)
+            .arg(connections_key(user_id))
+            .arg(NODE_PREFIX)
+            .query_async(&mut conn)
+            .await;
+        match res {
+            Ok(online) => Ok(online == 1),
+            Err(err) => Err(Error::Unknown(format!("Failed to get value {}", err))),
+        }
+    }
+
+    async fn refresh_node(&self, conn: &Pool, node_id: Uuid) -> Result<(), Error> {
+        let mut conn = conn.get().await.map_err(|e| {
+            Error::Unknown(format!("Failed to get connection from pool: {}", e))
+        })?;
+        let res: Result<(), RedisError> = cmd("SET")
+            .arg(format!("{}{}", NODE_PREFIX, node_id))
+            .arg(1)
+            .arg("EX")
+            .arg(NODE_TTL.as_secs())
+            .query_async(&mut conn)
+            .await;
+        match res {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::Unknown(format!("Failed to set value {}", err))),
+        }
+    }
+
+    async fn subscribe(&self, conn: &Pool, subscriber: Id, user_id: Id) -> Result<(), Error> {
+        let mut conn = conn.get().await.map_err(|e| {
+            Error::Unknown(format!("Failed to get connection from pool: {}", e))
+        })?;
+        let res: Result<(), RedisError> = pipe()
+            .atomic()
+            .cmd("SADD").arg(subscribers_key(user_id)).arg(subscriber.to_string()).ignore()
+            .cmd("SADD").arg(following_key(subscriber)).arg(user_id.to_string()).ignore()
+            .query_async(&mut conn)
+            .await;
+        match res {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::Unknown(format!("Failed to add value {}", err))),
+        }
+    }
+
+    async fn unsubscribe(&self, conn: &Pool, subscriber: Id, user_id: Id) -> Result<(), Error> {
+        let mut conn = conn.get().await.map_err(|e| {
+            Error::Unknown(format!("Failed to get connection from pool: {}", e))
+        })?;
+        let res: Result<(), RedisError> = pipe()
+            .atomic()
+            .cmd("SREM").arg(subscribers_key(user_id)).arg(subscriber.to_string()).ignore()
+            .cmd("SREM").arg(following_key(subscriber)).arg(user_id.to_string()).ignore()
+            .query_async(&mut conn)
+            .await;
+        match res {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::Unknown(format!("Failed to remove value {}", err))),
+        }
+    }
+
+    async fn unsubscribe_all(&self, conn: &Pool, subscriber: Id) -> Result<(), Error> {
+        let mut conn = conn.get().await.map_err(|e| {
+            Error::Unknown(format!("Failed to get connection from pool: {}", e))
+        })?;
+        let followed: Vec<String> = cmd("SMEMBERS")
+            .arg(following_key(subscriber))
+            .query_async(&mut conn)
+            .await
+            .map_err(|err| Error::Unknown(format!("Failed to get values {}", err)))?;
+        let mut pipe = pipe();
+        pipe.atomic();
+        for user_id in followed {
+            pipe.cmd("SREM")
+                .arg(subscribers_key(user_id))
+                .arg(subscriber.to_string())
+                .ignore();
+        }
+        pipe.cmd("DEL").arg(following_key(subscriber)).ignore();
+        let res: Result<(), RedisError> = pipe.query_async(&mut conn).await;
+        match res {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::Unknown(format!("Failed to remove values {}", err))),
+        }
+    }
+
+    async fn subscribers(&self, conn: &Pool, user_id: Id) -> Result<Vec<Id>, Error> {
+        let mut conn = conn.get().await.map_err(|e| {
+            Error::Unknown(format!("Failed to get connection from pool: {}", e))
+        })?;
+        let res: Result<Vec<String>, RedisError> = cmd("SMEMBERS")
+            .arg(subscribers_key(user_id))
+            .query_async(&mut conn)
+            .await;
+        match res {
+            Ok(ids) => Ok(ids.into_iter().filter_map(|id| Id::try_from(id).ok()).collect()),
+            Err(err) => Err(Error::Unknown(format!("Failed to get values {}", err))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::adapter::cache;
+    use super::*;
+
+    #[tokio::test]
+    #[ignore = "needs Redis at CACHE_URL"]
+    async fn test_two_nodes() {
+        let pool = cache::create_pool().await;
+        let registry = PresenceRegistry();
+        let (node_a, node_b) = (Uuid::new_v4(), Uuid::new_v4());
+        let user_id: Id = Uuid::new_v4().try_into().unwrap();
+
+        assert!(!registry.is_online(&pool, user_id).await.unwrap());
+        assert!(registry.connect(&pool, node_a, user_id).await.unwrap());
+        // Already online through node A
+        assert!(!registry.connect(&pool, node_b, user_id).await.unwrap());
+        assert!(!registry.connect(&pool, node_b, user_id).await.unwrap());
+        assert!(!registry.disconnect(&pool, node_a, user_id).await.unwrap());
+        assert!(registry.is_online(&pool, user_id).await.unwrap());
+        assert!(!registry.disconnect(&pool, node_b, user_id).await.unwrap());
+        assert!(registry.disconnect(&pool, node_b, user_id).await.unwrap());
+        assert!(!registry.is_online(&pool, user_id).await.unwrap());
+
+        // The connections of a node gone silent no longer count
+        registry.connect(&pool, node_a, user_id).await.unwrap();
+        let mut conn = pool.get().await.unwrap();
+        let _: () = cmd("DEL").arg(format!("{}{}", NODE_PREFIX, node_a))
+            .query_async(&mut conn).await.unwrap();
+        assert!(!registry.is_online(&pool, user_id).await.unwrap());
+        assert!(registry.connect(&pool, node_b, user_id).await.unwrap());
+        assert!(registry.disconnect(&pool, node_b, user_id).await.unwrap());
+
+        let subscriber: Id = Uuid::new_v4().try_into().unwrap();
+        registry.subscribe(&pool, subscriber, user_id).await.unwrap();
+        assert_eq!(registry.subscribers(&pool, user_id).await.unwrap(), vec![subscriber]);
+        registry.unsubscribe_all(&pool, subscriber).await.unwrap();
+        assert!(registry.subscribers(&pool, user_id).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,96 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use common::domain::types::id::Id;
+use crate::{
+    application::port::driven::presence_repository::{Error, PresenceRepositoryTrait},
+    domain::presence::PresenceRecord,
+};
+
+
+pub struct PresenceRepository();
+
+struct PresenceDB {
+    user_id: Uuid,
+    last_seen: Option<DateTime<Utc>>,
+    hide_last_seen: bool,
+}
+
+impl From<PresenceDB> for PresenceRecord {
+    fn from(value: PresenceDB) -> Self {
+        PresenceRecord {
+            user_id: value.user_id.try_into().unwrap(),
+            last_seen: value.last_seen,
+            hide_last_seen: value.hide_last_seen,
+        }
+    }
+}
+
+#[async_trait]
+impl PresenceRepositoryTrait<Pool<Postgres>> for PresenceRepository {
+    async fn find(&self, conn: &Pool<Postgres>, user_id: Id) -> Result<PresenceRecord, Error> {
+        let presence = sqlx::query_as!(
+            PresenceDB,
+            r#"
+                SELECT user_id, last_seen, hide_last_seen FROM presences WHERE user_id = $1;
+            "#,
+            &Uuid::from(user_id)
+        ).fetch_one(conn).await;
+
+        match presence {
+            Ok(presence) => Ok(presence.into()),
+            Err(sqlx::Error::RowNotFound) => Err(Error::NotFound("Presence not found".to_string())),
+            Err(err) => Err(Error::DatabaseError(err.to_string())),
+        }
+    }
+
+    async fn set_last_seen(
+        &self,
+        conn: &Pool<Postgres>,
+        user_id: Id,
+        last_seen: DateTime<Utc>,
+    ) -> Result<PresenceRecord, Error> {
+        let presence = sqlx::query_as!(
+            PresenceDB,
+            r#"
+                INSERT INTO presences (user_id, last_seen)
+                VALUES ($1, $2)
+                ON CONFLICT (user_id)
+                DO UPDATE SET last_seen = EXCLUDED.last_seen, updated_at = NOW()
+                RETURNING user_id, last_seen, hide_last_seen;
+            "#,
+            &Uuid::from(user_id),
+            last_seen
+        ).fetch_one(conn).await;
+
+        presence
+            .map(PresenceRecord::from)
+            .map_err(|err| Error::DatabaseError(err.to_string()))
+    }
+
+    async fn set_hide_last_seen(
+        &self,
+        conn: &Pool<Postgres>,
+        user_id: Id,
+        hide_last_seen: bool,
+    ) -> Result<PresenceRecord, Error> {
+        let presence = sqlx::query_as!(
+            PresenceDB,
+            r#"
+                INSERT INTO presences (user_id, hide_last_seen)
+                VALUES ($1, $2)
+                ON CONFLICT (user_id)
+                DO UPDATE SET hide_last_seen = EXCLUDED.hide_last_seen, updated_at = NOW()
+                RETURNING user_id, last_seen, hide_last_seen;
+            "#,
+            &Uuid::from(user_id),
+            hide_last_seen
+        ).fetch_one(conn).await;
+
+        presence
+            .map(PresenceRecord::from)
+            .map_err(|err| Error::DatabaseError(err.to_string()))
+    }
+}
@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use deadpool_redis::{redis::{pipe, RedisError}, Pool};
+use uuid::Uuid;
+
+use common::domain::types::id::Id;
+use crate::application::port::driven::receipt_queue::{Error, ReceiptQueueTrait};
+
+
+/// Messages with missed receipts are kept in a sorted set per sender, scored
+/// by the time the last receipt was missed. The set is trimmed and expires
+/// like the pending messages.
+pub struct ReceiptQueue();
+
+fn key(sender: Id) -> String {
+    format!("pending_receipts:{}", sender)
+}
+
+#[async_trait]
+impl ReceiptQueueTrait<Pool> for ReceiptQueue {
+    async fn add(
+        &self,
+        conn: &Pool,
+        sender: Id,
+        message_id: Uuid,
+        max: u64,
+        ttl: Duration,
+    ) -> Result<(), Error> {
+        let mut conn = conn.get().await.map_err(|e| {
+            Error::Unknown(format!("Failed to get connection from pool: {}", e))
+        })?;
+        let key = key(sender);
+        // Ranks count from the oldest, the last `max` ones are kept
+        let last_dropped = -(max.min(i64::MAX as u64) as i64) - 1;
+        let res: Result<(), RedisError> = pipe()
+            .atomic()
+            .cmd("ZADD").arg(&key)
+            .arg(Utc::now().timestamp_millis())
+            .arg(message_id.to_string()).ignore()
+            .cmd("ZREMRANGEBYRANK").arg(&key).arg(0).arg(last_dropped).ignore()
+            .cmd("EXPIRE").arg(&key).arg(ttl.as_secs().max(1)).ignore()
+            .query_async(&mut conn)
+            .await;
+        match res {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::Unknown(format!("Failed to add value {}", err))),
+        }
+    }
+
+    async fn take(&self, conn: &Pool, sender: Id) -> Result<Vec<Uuid>, Error> {
+        let mut conn = conn.get().await.map_err(|e| {
+            Error::Unknown(format!("Failed to get connection from pool: {}", e))
+        })?;
+        let key = key(sender);
+        let res: Result<(Vec<String>,), RedisError> = pipe()
+            .atomic()
+            .cmd("ZRANGE").arg(&key).arg(0).arg(-1)
+            .cmd("DEL").arg(&key).ignore()
+            .query_async(&mut conn)
+            .await;
+        match res {
+            Ok((ids,)) => Ok(ids.iter().filter_map(|id| Uuid::parse_str(id).ok()).collect()),
+            Err(err) => Err(Error::Unknown(format!("Failed to take values {}", err))),
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use std::{path::Path, time::Duration};
+
+use async_trait::async_trait;
+use aws_sdk_s3::{presigning::{PresignedRequest, PresigningConfig}, primitives::ByteStream};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+
+use common::{adapter::media_store::S3Bucket, domain::types::media_path::MediaPath};
+use crate::application::port::driven::{
+    errors::MediaError,
+    media_repository::{Media, MediaRepository, PresignedUrl},
+};
+
+
+/// Seconds a pre-signed url stays valid
+const PRESIGNED_URL_TTL_SECS: u64 = 15 * 60;
+
+/// Media stored in an S3 compatible bucket, keyed by the SHA-256 of their
+/// content so identical uploads are kept once
+pub struct S3MediaRepository();
+
+fn presigned_url(request: PresignedRequest) -> PresignedUrl {
+    PresignedUrl {
+        url: request.uri().to_string(),
+        method: request.method().to_string(),
+        headers: request.headers()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        expires_at: Utc::now() + chrono::Duration::seconds(PRESIGNED_URL_TTL_SECS as i64),
+    }
+}
+
+fn presigning_config() -> Result<PresigningConfig, MediaError> {
+    PresigningConfig::expires_in(Duration::from_secs(PRESIGNED_URL_TTL_SECS))
+        .map_err(|err| MediaError::Unknown(err.to_string()))
+}
+
+/// Checksum S3 checks the uploaded content against, base64 of the raw hash
+fn checksum(path: &MediaPath) -> Result<String, MediaError> {
+    hex::decode(path.hash())
+        .map(|hash| STANDARD.encode(hash))
+        .map_err(|err| MediaError::Unknown(err.to_string()))
+}
+
+#[async_trait]
+impl MediaRepository<S3Bucket> for S3MediaRepository {
+    async fn add(&self, conn: &S3Bucket, media: &Media) -> Result<MediaPath, MediaError> {
+        let hash = hex::encode(Sha256::digest(media.data()));
+        let path = MediaPath::new(media.kind(), &hash)
+            .map_err(|err| MediaError::Unknown(err.to_string()))?;
+        if self.exists(conn, &path).await? {
+            return Ok(path);
+        }
+
+        conn.client.put_object()
+            .bucket(&conn.bucket)
+            .key(path.hash())
+            .checksum_sha256(checksum(&path)?)
+            .body(ByteStream::from(media.data().to_vec()))
+            .send().await
+            .map_err(|err| MediaError::Unknown(err.to_string()))?;
+        Ok(path)
+    }
+
+    async fn add_file(&self, conn: &S3Bucket, path: &MediaPath, file: &Path) -> Result<(), MediaError> {
+        if self.exists(conn, path).await? {
+            return Ok(());
+        }
+        // Streamed from the disk rather than loaded in memory
+        let body = ByteStream::from_path(file).await
+            .map_err(|err| MediaError::Unknown(err.to_string()))?;
+        conn.client.put_object()
+            .bucket(&conn.bucket)
+            .key(path.hash())
+            .checksum_sha256(checksum(path)?)
+            .body(body)
+            .send().await
+            .map_err(|err| MediaError::Unknown(err.to_string()))?;
+        Ok(())
+    }
+
+    async fn get(&self, conn: &S3Bucket, path: &MediaPath) -> Result<Vec<u8>, MediaError> {
+        let object = match conn.client.get_object()
+            .bucket(&conn.bucket)
+            .key(path.hash())
+            .send().await
+        {
+            Ok(object) => object,
+            Err(err) => {
+                let err = err.into_service_error();
+                if err.is_no_such_key() {
+                    return Err(MediaError::NotFound(path.to_string()));
+                }
+                return Err(MediaError::Unknown(err.to_string()));
+            },
+        };
+        let data = object.body.collect().await
+            .map_err(|err| MediaError::Unknown(err.to_string()))?;
+        Ok(data.into_bytes().to_vec())
+    }
+
+    async fn exists(&self, conn: &S3Bucket, path: &MediaPath) -> Result<bool, MediaError> {
+        match conn.client.head_object()
+            .bucket(&conn.bucket)
+            .key(path.hash())
+            .send().await
+        {
+            Ok(_) => Ok(true),
+            Err(err) => {
+                let err = err.into_service_error();
+                if err.is_not_found() {
+                    Ok(false)
+                } else {
+                    Err(MediaError::Unknown(err.to_string()))
+                }
+            },
+        }
+    }
+
+    async fn head(&self, conn: &S3Bucket, path: &MediaPath, len: usize) -> Result<(Vec<u8>, u64), MediaError> {
+        let object = match conn.client.get_object()
+            .bucket(&conn.bucket)
+            .key(path.hash())
+            .range(format!("bytes=0-{}", len.max(1) - 1))
+            .send().await
+        {
+            Ok(object) => object,
+            Err(err) => {
+                let err = err.into_service_error();
+                if err.is_no_such_key() {
+                    return Err(MediaError::NotFound(path.to_string()));
+                }
+                return Err(MediaError::Unknown(err.to_string()));
+            },
+        };
+        // `bytes 0-<end>/<size>`, missing when the range covers the object
+        let size = object.content_range()
+            .and_then(|range| range.rsplit_once('/')?.1.parse().ok())
+            .or(object.content_length().map(|size| size as u64))
+            .unwrap_or_default();
+        let data = object.body.collect().await
+            .map_err(|err| MediaError::Unknown(err.to_string()))?;
+        Ok((data.into_bytes().to_vec(), size))
+    }
+
+    async fn upload_url(&self, conn: &S3Bucket, path: &MediaPath) -> Result<Option<PresignedUrl>, MediaError> {
+        // Signed with the checksum so only the content matching the path is accepted
+        let request = conn.client.put_object()
+            .bucket(&conn.bucket)
+            .key(path.hash())
+            .checksum_sha256(checksum(path)?)
+            .presigned(presigning_config()?).await
+            .map_err(|err| MediaError::Unknown(err.to_string()))?;
+        Ok(Some(presigned_url(request)))
+    }
+
+    async fn download_url(&self, conn: &S3Bucket, path: &MediaPath) -> Result<Option<PresignedUrl>, MediaError> {
+        let request = conn.client.get_object()
+            .bucket(&conn.bucket)
+            .key(path.hash())
+            .presigned(presigning_config()?).await
+            .map_err(|err| MediaError::Unknown(err.to_string()))?;
+        Ok(Some(presigned_url(request)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use aws_sdk_s3::{config::{Builder, Credentials, Region}, Client};
+    use super::*;
+
+    /// Bucket on the server at `S3_ENDPOINT`, e.g. the MinIO of the compose
+    /// files, reached with the `AWS_*` credentials
+    async fn test_bucket() -> S3Bucket {
+        let endpoint = env::var("S3_ENDPOINT").expect("S3_ENDPOINT must be set");
+        let credentials = Credentials::new(
+            env::var("AWS_ACCESS_KEY_ID").expect("AWS_ACCESS_KEY_ID must be set"),
+            env::var("AWS_SECRET_ACCESS_KEY").expect("AWS_SECRET_ACCESS_KEY must be set"),
+            None,
+            None,
+            "test",
+        );
+        let config = Builder::new()
+            .behavior_version_latest()
+            .region(Region::new("us-east-1"))
+            .credentials_provider(credentials)
+            .endpoint_url(endpoint)
+            .force_path_style(true)
+            .build();
+        let client = Client::from_conf(config);
+        let bucket = format!("test-{}", uuid::Uuid::new_v4());
+        client.create_bucket().bucket(&bucket).send().await.unwrap();
+        S3Bucket { client, bucket }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs an S3 compatible server"]
+    async fn test_add_and_get() {
+        let bucket = test_bucket().await;
+        let repo = S3MediaRepository();
+
+        let path = repo.add(&bucket, &Media::Image(b"hello".to_vec())).await.unwrap();
+        assert_eq!(path.hash(), hex::encode(Sha256::digest(b"hello")));
+        assert_eq!(repo.get(&bucket, &path).await.unwrap(), b"hello".to_vec());
+        assert!(repo.exists(&bucket, &path).await.unwrap());
+        assert_eq!(repo.head(&bucket, &path, 2).await.unwrap(), (b"he".to_vec(), 5));
+
+        let missing = MediaPath::new("image", &"0".repeat(64)).unwrap();
+        assert!(!repo.exists(&bucket, &missing).await.unwrap());
+        assert!(matches!(repo.get(&bucket, &missing).await, Err(MediaError::NotFound(_))));
+
+        let upload = repo.upload_url(&bucket, &missing).await.unwrap().unwrap();
+        assert_eq!(upload.method, "PUT");
+        assert!(upload.url.contains(missing.hash()));
+        let download = repo.download_url(&bucket, &path).await.unwrap().unwrap();
+        assert_eq!(download.method, "GET");
+    }
+}
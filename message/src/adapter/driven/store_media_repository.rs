@@ -0,0 +1,66 @@
+use std::path::Path;
+
+use async_trait::async_trait;
+
+use common::{adapter::media_store::MediaStore, domain::types::media_path::MediaPath};
+use super::{local_media_repository::LocalMediaRepository, s3_media_repository::S3MediaRepository};
+use crate::application::port::driven::{
+    errors::MediaError,
+    media_repository::{Media, MediaRepository, PresignedUrl},
+};
+
+
+/// Media repository of the storage selected in the configuration
+pub struct StoreMediaRepository();
+
+#[async_trait]
+impl MediaRepository<MediaStore> for StoreMediaRepository {
+    async fn add(&self, conn: &MediaStore, media: &Media) -> Result<MediaPath, MediaError> {
+        match conn {
+            MediaStore::Local(root) => LocalMediaRepository().add(root, media).await,
+            MediaStore::S3(bucket) => S3MediaRepository().add(bucket, media).await,
+        }
+    }
+
+    async fn add_file(&self, conn: &MediaStore, path: &MediaPath, file: &Path) -> Result<(), MediaError> {
+        match conn {
+            MediaStore::Local(root) => LocalMediaRepository().add_file(root, path, file).await,
+            MediaStore::S3(bucket) => S3MediaRepository().add_file(bucket, path, file).await,
+        }
+    }
+
+    async fn get(&self, conn: &MediaStore, path: &MediaPath) -> Result<Vec<u8>, MediaError> {
+        match conn {
+            MediaStore::Local(root) => LocalMediaRepository().get(root, path).await,
+            MediaStore::S3(bucket) => S3MediaRepository().get(bucket, path).await,
+        }
+    }
+
+    async fn exists(&self, conn: &MediaStore, path: &MediaPath) -> Result<bool, MediaError> {
+        match conn {
+            MediaStore::Local(root) => LocalMediaRepository().exists(root, path).await,
+            MediaStore::S3(bucket) => S3MediaRepository().exists(bucket, path).await,
+        }
+    }
+
+    async fn head(&self, conn: &MediaStore, path: &MediaPath, len: usize) -> Result<(Vec<u8>, u64), MediaError> {
+        match conn {
+            MediaStore::Local(root) => LocalMediaRepository().head(root, path, len).await,
+            MediaStore::S3(bucket) => S3MediaRepository().head(bucket, path, len).await,
+        }
+    }
+
+    async fn upload_url(&self, conn: &MediaStore, path: &MediaPath) -> Result<Option<PresignedUrl>, MediaError> {
+        match conn {
+            MediaStore::Local(root) => LocalMediaRepository().upload_url(root, path).await,
+            MediaStore::S3(bucket) => S3MediaRepository().upload_url(bucket, path).await,
+        }
+    }
+
+    async fn download_url(&self, conn: &MediaStore, path: &MediaPath) -> Result<Option<PresignedUrl>, MediaError> {
+        match conn {
+            MediaStore::Local(root) => LocalMediaRepository().download_url(root, path).await,
+            MediaStore::S3(bucket) => S3MediaRepository().download_url(bucket, path).await,
+        }
+    }
+}
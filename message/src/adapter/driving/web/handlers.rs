@@ -1,16 +1,450 @@
-use axum::extract::ws::{WebSocket, Message};
-use common::domain::{models::{client::Clients}, types::id::Id};
-use futures_util::stream::SplitSink;
-
-use crate::application::use_cases;
-
-
-// pub async fn send_message(
-//     event: Event<MessageDomain>, 
-//     clients: Clients<SplitSink<WebSocket, Message>>
-// ) -> Result<Vec<Id>, ()> {
-//     match use_cases::consume_event::execute(event, clients).await {
-//         Ok(ids) => Ok(ids),
-//         Err(_) => Err(()),
-//     }
-// }
\ No newline at end of file
+use axum::{
+    body::Bytes,
+    extract::{Json, Path, Query, State},
+    http::{header, HeaderMap},
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use common::{
+    adapter::{response_schemas::JsonResponse, state::AppState},
+    domain::types::id::Id,
+};
+use group::{get_groups, GroupRepository};
+use uuid::Uuid;
+
+use crate::application::use_cases::{
+    complete_upload, create_upload, get_conversation, get_inbox, get_media, get_message,
+    get_presence_settings, get_upload, get_upload_url, update_presence_settings, upload_chunk,
+};
+
+// Adapters
+use crate::adapter::driven::{
+    local_upload_repository::LocalUploadRepository,
+    message_repository::MessageRepository,
+    presence_repository::PresenceRepository,
+    store_media_repository::StoreMediaRepository,
+};
+
+use super::schemas::{
+    ConversationJson, ConversationsPageJson, MessageJson, MessagesPageJson, PageParamsJson,
+    MediaPathJson, NewUploadJson, PresenceSettingsJson, PresignedUrlJson, UploadJson,
+};
+
+/// Header carrying the SHA-256 of an uploaded chunk in hex
+const CHUNK_SHA256_HEADER: &str = "x-chunk-sha256";
+
+
+pub async fn handle_get_conversations(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Query(params): Query<PageParamsJson>,
+) -> JsonResponse<ConversationsPageJson> {
+    // Groups left by the user are no longer part of the inbox
+    let groups = match get_groups::execute(
+        &state.db_sql_pool,
+        &GroupRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+    )
+    .await
+    {
+        Ok(groups) => groups.into_iter().map(|group| group.id).collect(),
+        Err(get_groups::Error::Unauthorized) => {
+            return JsonResponse::new_unauthorized_err(0, "".to_string())
+        }
+        Err(get_groups::Error::DatabaseError) => {
+            return JsonResponse::new_int_ser_err(0, "".to_string())
+        }
+    };
+    match get_inbox::execute(
+        &state.db_document_client,
+        &MessageRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+        get_inbox::Payload {
+            groups,
+            cursor: params.cursor,
+            limit: params.limit,
+        },
+    )
+    .await
+    {
+        Ok(page) => JsonResponse::new_ok(ConversationsPageJson {
+            conversations: page.conversations.into_iter().map(ConversationJson::from).collect(),
+            next_cursor: page.next_cursor.map(Into::into),
+        }),
+        Err(err) => match err {
+            get_inbox::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            get_inbox::Error::InvalidCursor => {
+                JsonResponse::new_bad_req_err(0, "Invalid cursor".to_string())
+            }
+            get_inbox::Error::DatabaseError => {
+                JsonResponse::new_int_ser_err(0, "".to_string())
+            }
+        },
+    }
+}
+
+pub async fn handle_get_conversation_messages(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Path(peer_id): Path<Id>,
+    Query(params): Query<PageParamsJson>,
+) -> JsonResponse<MessagesPageJson> {
+    match get_conversation::execute(
+        &state.db_document_client,
+        &MessageRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+        get_conversation::Payload {
+            peer_id,
+            cursor: params.cursor,
+            limit: params.limit,
+        },
+    )
+    .await
+    {
+        Ok(page) => JsonResponse::new_ok(MessagesPageJson {
+            messages: page.messages.into_iter().map(MessageJson::from).collect(),
+            next_cursor: page.next_cursor.map(Into::into),
+        }),
+        Err(err) => match err {
+            get_conversation::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            get_conversation::Error::InvalidCursor => {
+                JsonResponse::new_bad_req_err(0, "Invalid cursor".to_string())
+            }
+            get_conversation::Error::DatabaseError => {
+                JsonResponse::new_int_ser_err(0, "".to_string())
+            }
+        },
+    }
+}
+
+pub async fn handle_get_conversation_message(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Path((peer_id, message_id)): Path<(Id, Uuid)>,
+) -> JsonResponse<MessageJson> {
+    match get_message::execute(
+        &state.db_document_client,
+        &MessageRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+        get_message::Payload { peer_id, message_id },
+    )
+    .await
+    {
+        Ok(message) => JsonResponse::new_ok(message.into()),
+        Err(err) => match err {
+            get_message::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            get_message::Error::NotFound => JsonResponse::new_not_found_err(0, "".to_string()),
+            get_message::Error::DatabaseError => {
+                JsonResponse::new_int_ser_err(0, "".to_string())
+            }
+        },
+    }
+}
+
+pub async fn handle_get_media(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Path((kind, hash)): Path<(String, String)>,
+) -> Response {
+    match get_media::execute(
+        &state.media_store,
+        &StoreMediaRepository(),
+        &state.db_document_client,
+        &MessageRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+        get_media::Payload { path: format!("{}/{}", kind, hash) },
+    )
+    .await
+    {
+        Ok(get_media::Download::Url(url)) => Redirect::temporary(&url.url).into_response(),
+        // Content addressed, what a path points to never changes
+        Ok(get_media::Download::Data(data)) => (
+            [
+                (header::CONTENT_TYPE, "application/octet-stream"),
+                (header::CACHE_CONTROL, "private, max-age=31536000, immutable"),
+            ],
+            data,
+        ).into_response(),
+        Err(err) => match err {
+            get_media::Error::Unauthorized => {
+                JsonResponse::<()>::new_unauthorized_err(0, "".to_string())
+            }
+            get_media::Error::InvalidPath => {
+                JsonResponse::new_bad_req_err(0, "Invalid media path".to_string())
+            }
+            get_media::Error::NotFound => JsonResponse::new_not_found_err(0, "".to_string()),
+            get_media::Error::ConnectionError => {
+                JsonResponse::new_int_ser_err(0, "".to_string())
+            }
+        }.into_response(),
+    }
+}
+
+pub async fn handle_get_upload_url(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Path((kind, hash)): Path<(String, String)>,
+) -> JsonResponse<PresignedUrlJson> {
+    match get_upload_url::execute(
+        &state.media_store,
+        &StoreMediaRepository(),
+        &state.db_document_client,
+        &MessageRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+        get_upload_url::Payload { path: format!("{}/{}", kind, hash) },
+    )
+    .await
+    {
+        Ok(url) => JsonResponse::new_ok(url.into()),
+        Err(err) => match err {
+            get_upload_url::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            get_upload_url::Error::InvalidPath => {
+                JsonResponse::new_bad_req_err(0, "Invalid media path".to_string())
+            }
+            get_upload_url::Error::AlreadyStored => {
+                JsonResponse::new_conflict_err(0, "Media already stored".to_string())
+            }
+            get_upload_url::Error::Unsupported => {
+                JsonResponse::new_bad_req_err(0, "Direct uploads are not supported".to_string())
+            }
+            get_upload_url::Error::ConnectionError => {
+                JsonResponse::new_int_ser_err(0, "".to_string())
+            }
+        },
+    }
+}
+
+pub async fn handle_create_upload(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Json(payload): Json<NewUploadJson>,
+) -> JsonResponse<UploadJson> {
+    match create_upload::execute(
+        &state.config.upload_root,
+        &LocalUploadRepository(),
+        &state.media_store,
+        &StoreMediaRepository(),
+        &state.db_document_client,
+        &MessageRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+        create_upload::Payload {
+            kind: payload.kind,
+            sha256: payload.sha256,
+            size: payload.size,
+            media_limits: state.config.media_limits.clone(),
+        },
+    )
+    .await
+    {
+        Ok(upload) => JsonResponse::new_ok(upload.into()),
+        Err(err) => match err {
+            create_upload::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            create_upload::Error::InvalidPath => {
+                JsonResponse::new_bad_req_err(0, "Invalid media path".to_string())
+            }
+            create_upload::Error::InvalidMedia(err) => {
+                JsonResponse::new_bad_req_err(err.code(), err.to_string())
+            }
+            create_upload::Error::AlreadyStored => {
+                JsonResponse::new_conflict_err(0, "Media already stored".to_string())
+            }
+            create_upload::Error::ConnectionError => {
+                JsonResponse::new_int_ser_err(0, "".to_string())
+            }
+        },
+    }
+}
+
+pub async fn handle_get_upload(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<Uuid>,
+) -> JsonResponse<UploadJson> {
+    match get_upload::execute(
+        &state.config.upload_root,
+        &LocalUploadRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+        get_upload::Payload { id },
+    )
+    .await
+    {
+        Ok(upload) => JsonResponse::new_ok(upload.into()),
+        Err(err) => match err {
+            get_upload::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            get_upload::Error::NotFound => JsonResponse::new_not_found_err(0, "".to_string()),
+            get_upload::Error::ConnectionError => {
+                JsonResponse::new_int_ser_err(0, "".to_string())
+            }
+        },
+    }
+}
+
+pub async fn handle_upload_chunk(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Path((id, index)): Path<(Uuid, u64)>,
+    headers: HeaderMap,
+    data: Bytes,
+) -> JsonResponse<UploadJson> {
+    let sha256 = match headers.get(CHUNK_SHA256_HEADER).and_then(|value| value.to_str().ok()) {
+        Some(sha256) => sha256.to_string(),
+        None => return JsonResponse::new_bad_req_err(
+            0,
+            format!("Missing {} header", CHUNK_SHA256_HEADER),
+        ),
+    };
+    match upload_chunk::execute(
+        &state.config.upload_root,
+        &LocalUploadRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+        upload_chunk::Payload {
+            id,
+            index,
+            data: data.to_vec(),
+            sha256,
+            media_limits: state.config.media_limits.clone(),
+        },
+    )
+    .await
+    {
+        Ok(upload) => JsonResponse::new_ok(upload.into()),
+        Err(err) => match err {
+            upload_chunk::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            upload_chunk::Error::NotFound => JsonResponse::new_not_found_err(0, "".to_string()),
+            upload_chunk::Error::InvalidChunk => {
+                JsonResponse::new_bad_req_err(0, "Invalid chunk".to_string())
+            }
+            upload_chunk::Error::ChecksumMismatch => {
+                JsonResponse::new_bad_req_err(0, "Checksum mismatch".to_string())
+            }
+            upload_chunk::Error::InvalidMedia(err) => {
+                JsonResponse::new_bad_req_err(err.code(), err.to_string())
+            }
+            upload_chunk::Error::ConnectionError => {
+                JsonResponse::new_int_ser_err(0, "".to_string())
+            }
+        },
+    }
+}
+
+pub async fn handle_complete_upload(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<Uuid>,
+) -> JsonResponse<MediaPathJson> {
+    match complete_upload::execute(
+        &state.config.upload_root,
+        &LocalUploadRepository(),
+        &state.media_store,
+        &StoreMediaRepository(),
+        &state.db_document_client,
+        &MessageRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+        complete_upload::Payload { id },
+    )
+    .await
+    {
+        Ok(path) => JsonResponse::new_ok(path.into()),
+        Err(err) => match err {
+            complete_upload::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            complete_upload::Error::NotFound => JsonResponse::new_not_found_err(0, "".to_string()),
+            complete_upload::Error::Incomplete(missing_chunks) => {
+                let missing_chunks: Vec<String> = missing_chunks.iter()
+                    .map(|index| index.to_string())
+                    .collect();
+                JsonResponse::new_conflict_err(
+                    0,
+                    format!("Missing chunks: {}", missing_chunks.join(",")),
+                )
+            }
+            complete_upload::Error::ChecksumMismatch => {
+                JsonResponse::new_bad_req_err(0, "Checksum mismatch".to_string())
+            }
+            complete_upload::Error::InvalidMedia(err) => {
+                JsonResponse::new_bad_req_err(err.code(), err.to_string())
+            }
+            complete_upload::Error::ConnectionError => {
+                JsonResponse::new_int_ser_err(0, "".to_string())
+            }
+        },
+    }
+}
+
+pub async fn handle_get_presence_settings(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+) -> JsonResponse<PresenceSettingsJson> {
+    match get_presence_settings::execute(
+        &state.db_sql_pool,
+        &PresenceRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+    )
+    .await
+    {
+        Ok(record) => JsonResponse::new_ok(record.into()),
+        Err(err) => match err {
+            get_presence_settings::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            get_presence_settings::Error::DatabaseError => {
+                JsonResponse::new_int_ser_err(0, "".to_string())
+            }
+        },
+    }
+}
+
+pub async fn handle_update_presence_settings(
+    State(state): State<AppState>,
+    TypedHeader(token): TypedHeader<Authorization<Bearer>>,
+    Json(payload): Json<PresenceSettingsJson>,
+) -> JsonResponse<PresenceSettingsJson> {
+    match update_presence_settings::execute(
+        &state.db_sql_pool,
+        &PresenceRepository(),
+        &state.config.secret,
+        &token.token().to_string(),
+        update_presence_settings::Payload { hide_last_seen: payload.hide_last_seen },
+    )
+    .await
+    {
+        Ok(record) => JsonResponse::new_ok(record.into()),
+        Err(err) => match err {
+            update_presence_settings::Error::Unauthorized => {
+                JsonResponse::new_unauthorized_err(0, "".to_string())
+            }
+            update_presence_settings::Error::DatabaseError => {
+                JsonResponse::new_int_ser_err(0, "".to_string())
+            }
+        },
+    }
+}
@@ -0,0 +1,319 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use common::domain::types::{media_path::MediaPath, recipient::Recipient};
+
+use crate::{
+    application::port::driven::media_repository::PresignedUrl,
+    domain::{
+        conversation::Conversation,
+        message::{Envelope, Message, MessageVersion, Receipt},
+        presence::PresenceRecord,
+        preview::{Preview, Thumbnail},
+        reaction::{self, ReactionCount},
+        upload::Upload,
+    },
+};
+
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageJson {
+    pub id: Uuid,
+    pub sender: String,
+    pub recipient: String,
+    pub message_type: String,
+    /// Base64 encoded, media and encrypted payloads are not valid UTF-8
+    pub content: String,
+    /// Content of encrypted messages, one per device
+    pub envelopes: Vec<EnvelopeJson>,
+    pub deleted: bool,
+    pub edit_history: Vec<MessageVersionJson>,
+    pub preview: Option<PreviewJson>,
+    /// Reactions grouped by emoji, in the order each emoji was first used
+    pub reactions: Vec<ReactionCountJson>,
+    /// First reception and read, the recipient's for a message to a user
+    pub received_at: Option<DateTime<Utc>>,
+    pub read_at: Option<DateTime<Utc>>,
+    /// One per member for a message to a group
+    pub received_by: Vec<ReceiptJson>,
+    pub read_by: Vec<ReceiptJson>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Message> for MessageJson {
+    fn from(value: Message) -> Self {
+        Self {
+            id: value.id,
+            sender: value.sender.into(),
+            recipient: value.recipient.into(),
+            message_type: value.message_type.into(),
+            content: STANDARD.encode(value.content),
+            envelopes: value.envelopes.into_iter().map(Into::into).collect(),
+            deleted: value.deleted,
+            edit_history: value.edit_history.into_iter().map(Into::into).collect(),
+            preview: value.preview.map(Into::into),
+            reactions: reaction::counts(&value.reactions).into_iter().map(Into::into).collect(),
+            received_at: value.received_by.first().map(|receipt| receipt.at),
+            read_at: value.read_by.first().map(|receipt| receipt.at),
+            received_by: value.received_by.into_iter().map(Into::into).collect(),
+            read_by: value.read_by.into_iter().map(Into::into).collect(),
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageVersionJson {
+    /// Base64 encoded like `MessageJson::content`
+    pub content: String,
+    pub envelopes: Vec<EnvelopeJson>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<MessageVersion> for MessageVersionJson {
+    fn from(value: MessageVersion) -> Self {
+        Self {
+            content: STANDARD.encode(value.content),
+            envelopes: value.envelopes.into_iter().map(Into::into).collect(),
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvelopeJson {
+    pub device_id: String,
+    /// Base64 encoded like `MessageJson::content`
+    pub ciphertext: String,
+}
+
+impl From<Envelope> for EnvelopeJson {
+    fn from(value: Envelope) -> Self {
+        Self {
+            device_id: value.device_id.into(),
+            ciphertext: STANDARD.encode(value.ciphertext),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptJson {
+    pub user_id: String,
+    pub at: DateTime<Utc>,
+}
+
+impl From<Receipt> for ReceiptJson {
+    fn from(value: Receipt) -> Self {
+        Self {
+            user_id: value.user_id.into(),
+            at: value.at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReactionCountJson {
+    pub emoji: String,
+    pub count: usize,
+    pub users: Vec<String>,
+}
+
+impl From<ReactionCount> for ReactionCountJson {
+    fn from(value: ReactionCount) -> Self {
+        Self {
+            emoji: value.emoji.into(),
+            count: value.count,
+            users: value.users.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewJson {
+    pub width: u32,
+    pub height: u32,
+    pub blurhash: String,
+    pub thumbnails: Vec<ThumbnailJson>,
+}
+
+impl From<Preview> for PreviewJson {
+    fn from(value: Preview) -> Self {
+        Self {
+            width: value.width,
+            height: value.height,
+            blurhash: value.blurhash,
+            thumbnails: value.thumbnails.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThumbnailJson {
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl From<Thumbnail> for ThumbnailJson {
+    fn from(value: Thumbnail) -> Self {
+        Self {
+            path: value.path.into(),
+            width: value.width,
+            height: value.height,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessagesPageJson {
+    pub messages: Vec<MessageJson>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerJson {
+    pub id: String,
+    pub peer_type: String,
+    pub name: Option<String>,
+}
+
+impl From<Recipient> for PeerJson {
+    fn from(value: Recipient) -> Self {
+        match value {
+            Recipient::User(id) => Self {
+                id: id.into(),
+                peer_type: "USER".to_string(),
+                name: None,
+            },
+            Recipient::Group(group) => Self {
+                id: group.id.into(),
+                peer_type: "GROUP".to_string(),
+                name: Some(group.name),
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationJson {
+    pub peer: PeerJson,
+    pub last_message_at: DateTime<Utc>,
+    pub last_message: MessageJson,
+    pub unread_count: u64,
+}
+
+impl From<Conversation> for ConversationJson {
+    fn from(value: Conversation) -> Self {
+        Self {
+            peer: value.peer.into(),
+            last_message_at: value.last_message.created_at,
+            last_message: value.last_message.into(),
+            unread_count: value.unread_count,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationsPageJson {
+    pub conversations: Vec<ConversationJson>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageParamsJson {
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresignedUrlJson {
+    pub url: String,
+    pub method: String,
+    pub headers: HashMap<String, String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl From<PresignedUrl> for PresignedUrlJson {
+    fn from(value: PresignedUrl) -> Self {
+        Self {
+            url: value.url,
+            method: value.method,
+            headers: value.headers.into_iter().collect(),
+            expires_at: value.expires_at,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewUploadJson {
+    pub kind: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadJson {
+    pub id: Uuid,
+    pub path: String,
+    pub size: u64,
+    pub chunk_size: u64,
+    pub received_chunks: Vec<u64>,
+    pub missing_chunks: Vec<u64>,
+}
+
+impl From<Upload> for UploadJson {
+    fn from(value: Upload) -> Self {
+        Self {
+            id: value.id,
+            missing_chunks: value.missing_chunks(),
+            path: value.media_path.into(),
+            size: value.size,
+            chunk_size: value.chunk_size,
+            received_chunks: value.received_chunks,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaPathJson {
+    pub path: String,
+}
+
+impl From<MediaPath> for MediaPathJson {
+    fn from(value: MediaPath) -> Self {
+        Self { path: value.into() }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresenceSettingsJson {
+    pub hide_last_seen: bool,
+}
+
+impl From<PresenceRecord> for PresenceSettingsJson {
+    fn from(value: PresenceRecord) -> Self {
+        Self { hide_last_seen: value.hide_last_seen }
+    }
+}
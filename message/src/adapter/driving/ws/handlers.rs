@@ -0,0 +1,583 @@
+use common::{
+    adapter::state::AppState,
+    domain::{
+        protos_schemas::proto_package::{
+            proto_error::Code,
+            proto_package::Payload,
+            proto_receipt::Kind,
+            ProtoPackage,
+        },
+        types::{error::ErrorMsg, id::Id, recipient::Recipient},
+    },
+};
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use group::{get_member_group, GroupRepository};
+use uuid::Uuid;
+
+use super::utils;
+use crate::domain::message::Message;
+use crate::application::use_cases::edit_message::Changed;
+use crate::application::use_cases::{
+    add_reaction,
+    dedup_message,
+    delete_message,
+    edit_message,
+    get_pending_messages, 
+    get_pending_receipts,
+    queue_message, 
+    queue_receipt,
+    read_message,
+    received_message, 
+    refresh_node,
+    remove_reaction,
+    send_message,
+    send_typing,
+    subscribe_presence,
+    unsubscribe_presence,
+    update_presence,
+};
+
+// Adapters
+use crate::adapter::driven::{
+    block_list::BlockList,
+    contact_list::ContactList,
+    message_dedup::MessageDedup,
+    message_queue::MessageQueue,
+    receipt_queue::ReceiptQueue,
+    message_repository::MessageRepository, 
+    presence_registry::PresenceRegistry,
+    presence_repository::PresenceRepository,
+    store_media_repository::StoreMediaRepository,
+};
+
+
+/// Time between two refreshes of this node, well within the 30 seconds its
+/// connections stay counted without one
+pub const NODE_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Answer the hello opening a connection with the protocol version picked,
+/// or with the error the connection is closed with
+pub fn handle_hello_package(package: &ProtoPackage) -> ProtoPackage {
+    utils::hello_reply(package)
+}
+
+/// Error returned to `user_id` for a package clients can't send
+pub fn handle_unexpected_package(user_id: Id, package: &ProtoPackage) -> ProtoPackage {
+    utils::error_package(&package.client_id, user_id, Code::INVALID_PACKAGE, "Unexpected package".to_string())
+}
+
+/// Store a message package sent by `user_id` and return the ack or error for
+/// the sender followed by the packages delivering the message, carrying the
+/// server id and timestamp, to the recipient and, for encrypted messages, to
+/// the other devices of the sender. A message resent with the same
+/// client id is acknowledged again without being stored twice. Nothing is
+/// delivered when the recipient blocked the sender, the sender is
+/// acknowledged as if it was.
+pub async fn handle_message_package(
+    state: &AppState,
+    user_id: Id,
+    package: ProtoPackage,
+) -> Result<Vec<ProtoPackage>, String> {
+    let client_id = package.client_id.clone();
+    let packages = match send_message_package(state, user_id, package).await {
+        Ok(Sent::Stored(message)) => {
+            let ack = utils::ack_package(&client_id, user_id, message.id, message.created_at);
+            std::iter::once(ack).chain(utils::message_packages(*message)).collect()
+        },
+        Ok(Sent::Duplicate(Some((message_id, created_at)))) => {
+            vec![utils::ack_package(&client_id, user_id, message_id, created_at)]
+        },
+        // The first send answers once stored
+        Ok(Sent::Duplicate(None)) => Vec::new(),
+        Ok(Sent::Blocked(message_id, created_at)) => {
+            vec![utils::ack_package(&client_id, user_id, message_id, created_at)]
+        },
+        Err(Nack(code, error)) => vec![utils::error_package(&client_id, user_id, code, error)],
+    };
+    Ok(packages)
+}
+
+/// Outcome of a message package
+enum Sent {
+    Stored(Box<Message>),
+    /// Already sent with the same client id, with the id and creation time
+    /// of the stored message once known
+    Duplicate(Option<(Uuid, DateTime<Utc>)>),
+    /// Blocked by the recipient, with the made up id and creation time the
+    /// sender is acknowledged with
+    Blocked(Uuid, DateTime<Utc>),
+}
+
+/// Error code and description of a rejected message package
+struct Nack(Code, String);
+
+async fn send_message_package(
+    state: &AppState,
+    user_id: Id,
+    package: ProtoPackage,
+) -> Result<Sent, Nack> {
+    let invalid = |err: ErrorMsg| Nack(Code::INVALID_PACKAGE, err.to_string());
+    if package.client_id.len() > utils::MAX_CLIENT_ID_LEN {
+        return Err(Nack(Code::INVALID_PACKAGE, "Client id too long".to_string()));
+    }
+    let proto_message = utils::proto_message_from_package(&package).map_err(invalid)?.clone();
+    let mut new_message = utils::new_message_from_proto(user_id, proto_message).map_err(invalid)?;
+    // The stored message keeps the current members
+    new_message.recipient = match member_recipient(state, user_id, new_message.recipient).await {
+        Ok(recipient) => recipient,
+        Err(get_member_group::Error::DatabaseError) => {
+            return Err(Nack(Code::SERVER_ERROR, "Server error".to_string()))
+        },
+        Err(err) => return Err(Nack(Code::NOT_ALLOWED, group_error(err))),
+    };
+
+    let send = send_message::execute(
+        &state.db_document_client,
+        &MessageRepository(),
+        &state.media_store,
+        &StoreMediaRepository(),
+        &state.db_sql_pool,
+        &BlockList(),
+        send_message::Payload {
+            new_message,
+            media_limits: state.config.media_limits.clone(),
+        },
+    );
+    let sent = if package.client_id.is_empty() {
+        send.await.map_err(dedup_message::Error::Send)
+    } else {
+        dedup_message::execute(
+            &state.cache_pool,
+            &MessageDedup(),
+            dedup_message::Payload {
+                sender: user_id,
+                client_id: package.client_id,
+                window: state.config.message_dedup_window,
+            },
+            send,
+        ).await
+    };
+    match sent {
+        Ok(message) => Ok(Sent::Stored(Box::new(message))),
+        Err(dedup_message::Error::Duplicate(message)) => Ok(Sent::Duplicate(message)),
+        Err(dedup_message::Error::Blocked(message_id, created_at)) => {
+            Ok(Sent::Blocked(message_id, created_at))
+        },
+        // Without a client id it can't be retried
+        Err(dedup_message::Error::Send(send_message::Error::Blocked)) => {
+            Ok(Sent::Blocked(Uuid::new_v4(), Utc::now()))
+        },
+        Err(dedup_message::Error::Send(send_message::Error::InvalidMedia(err))) => {
+            Err(Nack(Code::INVALID_MEDIA, err.to_string()))
+        },
+        Err(_) => Err(Nack(Code::SERVER_ERROR, "Server error".to_string())),
+    }
+}
+
+/// Relay a typing package sent by `user_id` without storing it, none when
+/// the recipient blocked the sender
+pub async fn handle_typing_package(
+    state: &AppState,
+    user_id: Id,
+    package: ProtoPackage,
+) -> Result<Option<ProtoPackage>, String> {
+    let (recipient, typing) = utils::typing_from_package(&package).map_err(|err| err.to_string())?;
+    let recipient = member_recipient(state, user_id, recipient).await.map_err(group_error)?;
+
+    match send_typing::execute(
+        &state.db_sql_pool,
+        &BlockList(),
+        send_typing::Payload { sender: user_id, recipient },
+    ).await {
+        Ok(recipient) => Ok(Some(utils::typing_package(user_id, recipient, typing))),
+        Err(send_typing::Error::Blocked) => Ok(None),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Only members can write to a group, the group recipient is filled with its
+/// current members
+async fn member_recipient(
+    state: &AppState,
+    user_id: Id,
+    recipient: Recipient,
+) -> Result<Recipient, get_member_group::Error> {
+    let group = match recipient {
+        Recipient::Group(group) => group,
+        recipient => return Ok(recipient),
+    };
+    get_member_group::execute(
+        &state.db_sql_pool,
+        &GroupRepository(),
+        get_member_group::Payload { id: group.id, user_id },
+    ).await.map(|group| Recipient::Group(group.into()))
+}
+
+fn group_error(err: get_member_group::Error) -> String {
+    match err {
+        get_member_group::Error::NotFound => "Group not found".to_string(),
+        get_member_group::Error::NotMember => "User is not a member of the group".to_string(),
+        get_member_group::Error::DatabaseError => "Database error".to_string(),
+    }
+}
+
+/// Follow or stop following the presence of the user addressed by a presence
+/// subscription package sent by `user_id`. Following returns its current
+/// presence, none when the user doesn't share it with `user_id`.
+pub async fn handle_presence_subscription_package(
+    state: &AppState,
+    user_id: Id,
+    package: ProtoPackage,
+) -> Result<Option<ProtoPackage>, String> {
+    let (watched_id, subscribe) = utils::presence_subscription_from_package(&package)
+        .map_err(|err| err.to_string())?;
+    if !subscribe {
+        return unsubscribe_presence::execute(
+            &state.cache_pool,
+            &PresenceRegistry(),
+            unsubscribe_presence::Payload { subscriber: user_id, user_id: watched_id },
+        ).await.map(|_| None).map_err(|err| err.to_string());
+    }
+
+    match subscribe_presence::execute(
+        &state.db_sql_pool,
+        &PresenceRepository(),
+        &state.db_sql_pool,
+        &ContactList(),
+        &state.cache_pool,
+        &PresenceRegistry(),
+        subscribe_presence::Payload { subscriber: user_id, user_id: watched_id },
+    ).await {
+        Ok(presence) => Ok(Some(utils::presence_package(&presence, user_id))),
+        Err(subscribe_presence::Error::NotAllowed) => Ok(None),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Count a connection of `user_id` opening or closing on this node and
+/// return the presence packages for its subscribers, wherever they are
+/// connected, when the user came online or went offline
+pub async fn handle_presence_change(
+    state: &AppState,
+    user_id: Id,
+    online: bool,
+) -> Result<Vec<ProtoPackage>, String> {
+    let changed = update_presence::execute(
+        &state.db_sql_pool,
+        &PresenceRepository(),
+        &state.cache_pool,
+        &PresenceRegistry(),
+        update_presence::Payload { user_id, node_id: state.cluster.node_id, online },
+    ).await.map_err(|err| err.to_string())?;
+
+    Ok(match changed {
+        Some(changed) => changed.subscribers.into_iter()
+            .map(|subscriber| utils::presence_package(&changed.presence, subscriber))
+            .collect(),
+        None => Vec::new(),
+    })
+}
+
+/// Keep the connections of this node counted by the other nodes, has to be
+/// called every `NODE_REFRESH_INTERVAL`
+pub async fn handle_node_refresh(state: &AppState) -> Result<(), String> {
+    refresh_node::execute(
+        &state.cache_pool,
+        &PresenceRegistry(),
+        refresh_node::Payload { node_id: state.cluster.node_id },
+    ).await.map_err(|err| err.to_string())
+}
+
+/// Edit a message sent by `user_id` with the content of an edit package and
+/// return the packages for both sides of the conversation, only the sender's
+/// when the recipient blocked the sender, or the error for the sender
+pub async fn handle_edit_package(
+    state: &AppState,
+    user_id: Id,
+    package: ProtoPackage,
+) -> Result<Vec<ProtoPackage>, String> {
+    let client_id = package.client_id.clone();
+    let packages = match edit_message_package(state, user_id, package).await {
+        Ok(changed) => utils::change_packages(Payload::Edit, changed.message, changed.recipient),
+        Err(Nack(code, error)) => vec![utils::error_package(&client_id, user_id, code, error)],
+    };
+    Ok(packages)
+}
+
+async fn edit_message_package(
+    state: &AppState,
+    user_id: Id,
+    package: ProtoPackage,
+) -> Result<Changed, Nack> {
+    let invalid = |err: ErrorMsg| Nack(Code::INVALID_PACKAGE, err.to_string());
+    let message_id = utils::message_id_from_package(&package).map_err(invalid)?;
+    let proto_message = utils::proto_message_from_package(&package).map_err(invalid)?;
+    let content = proto_message.content.clone();
+    let envelopes = utils::envelopes_from_proto(proto_message.envelopes.clone()).map_err(invalid)?;
+
+    edit_message::execute(
+        &state.db_document_client,
+        &MessageRepository(),
+        &state.db_sql_pool,
+        &BlockList(),
+        edit_message::Payload {
+            message_id,
+            user_id,
+            content,
+            envelopes,
+            edit_window: state.config.message_edit_window,
+        },
+    ).await.map_err(|err| match err {
+        edit_message::Error::InvalidContent(_) => Nack(Code::INVALID_PACKAGE, err.to_string()),
+        edit_message::Error::DatabaseError(_) | edit_message::Error::ConnectionError(_) => {
+            Nack(Code::SERVER_ERROR, "Server error".to_string())
+        },
+        err => Nack(Code::NOT_ALLOWED, err.to_string()),
+    })
+}
+
+/// Delete for everyone a message sent by `user_id` and return the packages
+/// for both sides of the conversation, only the sender's when the recipient
+/// blocked the sender, or the error for the sender
+pub async fn handle_delete_package(
+    state: &AppState,
+    user_id: Id,
+    package: ProtoPackage,
+) -> Result<Vec<ProtoPackage>, String> {
+    let client_id = package.client_id.clone();
+    let packages = match delete_message_package(state, user_id, package).await {
+        Ok(changed) => utils::change_packages(Payload::Delete, changed.message, changed.recipient),
+        Err(Nack(code, error)) => vec![utils::error_package(&client_id, user_id, code, error)],
+    };
+    Ok(packages)
+}
+
+async fn delete_message_package(
+    state: &AppState,
+    user_id: Id,
+    package: ProtoPackage,
+) -> Result<Changed, Nack> {
+    let message_id = utils::message_id_from_package(&package)
+        .map_err(|err| Nack(Code::INVALID_PACKAGE, err.to_string()))?;
+
+    delete_message::execute(
+        &state.db_document_client,
+        &MessageRepository(),
+        &state.db_sql_pool,
+        &BlockList(),
+        delete_message::Payload {
+            message_id,
+            user_id,
+            edit_window: state.config.message_edit_window,
+        },
+    ).await.map_err(|err| match err {
+        delete_message::Error::DatabaseError(_) | delete_message::Error::ConnectionError(_) => {
+            Nack(Code::SERVER_ERROR, "Server error".to_string())
+        },
+        err => Nack(Code::NOT_ALLOWED, err.to_string()),
+    })
+}
+
+/// Add or remove a reaction of `user_id` to a message and return the
+/// packages for the other participants and the user's devices, or the error
+/// for the user
+pub async fn handle_reaction_package(
+    state: &AppState,
+    user_id: Id,
+    package: ProtoPackage,
+) -> Result<Vec<ProtoPackage>, String> {
+    let (message_id, emoji, added) = match utils::reaction_from_package(&package) {
+        Ok(reaction) => reaction,
+        Err(err) => {
+            let error = utils::error_package(&package.client_id, user_id, Code::INVALID_PACKAGE, err.to_string());
+            return Ok(vec![error]);
+        },
+    };
+
+    let reacted = if added {
+        add_reaction::execute(
+            &state.db_document_client,
+            &MessageRepository(),
+            &state.db_sql_pool,
+            &GroupRepository(),
+            &state.db_sql_pool,
+            &BlockList(),
+            add_reaction::Payload { message_id, user_id, emoji: emoji.clone() },
+        ).await
+    } else {
+        remove_reaction::execute(
+            &state.db_document_client,
+            &MessageRepository(),
+            &state.db_sql_pool,
+            &GroupRepository(),
+            &state.db_sql_pool,
+            &BlockList(),
+            remove_reaction::Payload { message_id, user_id, emoji: emoji.clone() },
+        ).await
+    };
+    match reacted {
+        Ok(Some(reacted)) => {
+            Ok(utils::reaction_packages(user_id, &emoji, added, &reacted.message, reacted.peers))
+        },
+        // Nothing changed, or the peer blocked the user and isn't told
+        Ok(None) | Err(add_reaction::Error::Blocked) => Ok(Vec::new()),
+        Err(add_reaction::Error::DatabaseError(_) | add_reaction::Error::ConnectionError(_)) => {
+            let error = "Server error".to_string();
+            Ok(vec![utils::error_package(&package.client_id, user_id, Code::SERVER_ERROR, error)])
+        },
+        Err(err) => {
+            Ok(vec![utils::error_package(&package.client_id, user_id, Code::NOT_ALLOWED, err.to_string())])
+        },
+    }
+}
+
+/// Copy of a package for a connection of the device `device_id`, encrypted
+/// messages only carry the envelope of the device and are not delivered to
+/// devices without one
+pub fn handle_device_package(package: &ProtoPackage, device_id: Option<Id>) -> Option<ProtoPackage> {
+    utils::device_package(package, device_id)
+}
+
+/// Split a package addressed to a group into one package per member
+pub fn handle_recipient_packages(package: ProtoPackage) -> Result<Vec<ProtoPackage>, String> {
+    utils::member_packages(package).map_err(|err| err.to_string())
+}
+
+/// Keep a message or receipt package whose recipient is not connected, so it
+/// is replayed on the next connection. Edits and deletions are not kept, the
+/// replayed and fetched messages already reflect them.
+pub async fn handle_undelivered_package(
+    state: &AppState,
+    package: ProtoPackage,
+) -> Result<(), String> {
+    let recipient = utils::recipient_from_package(&package).map_err(|err| err.to_string())?;
+    let message_id = utils::message_id_from_package(&package).map_err(|err| err.to_string())?;
+    if package.has_receipt() {
+        // Replayed from the message, the recipient being its sender
+        return queue_receipt::execute(
+            &state.cache_pool,
+            &ReceiptQueue(),
+            queue_receipt::Payload {
+                sender: recipient,
+                message_id,
+                max_pending: state.config.max_pending_messages,
+                ttl: state.config.pending_messages_ttl,
+            },
+        ).await.map_err(|err| err.to_string());
+    }
+    if !package.has_message() {
+        return Err("Only messages and receipts are kept".to_string());
+    }
+
+    queue_message::execute(
+        &state.db_document_client,
+        &MessageRepository(),
+        &state.cache_pool,
+        &MessageQueue(),
+        queue_message::Payload {
+            recipient,
+            message_id,
+            max_pending: state.config.max_pending_messages,
+            ttl: state.config.pending_messages_ttl,
+        },
+    ).await.map_err(|err| err.to_string())
+}
+
+/// Packages of the messages waiting for `user_id`, oldest first, along with
+/// the id of their message.
+pub async fn handle_pending_packages(
+    state: &AppState,
+    user_id: Id,
+) -> Result<Vec<(Uuid, ProtoPackage)>, String> {
+    let messages = get_pending_messages::execute(
+        &state.db_document_client,
+        &MessageRepository(),
+        &state.cache_pool,
+        &MessageQueue(),
+        get_pending_messages::Payload { user_id },
+    ).await.map_err(|err| err.to_string())?;
+
+    Ok(messages.into_iter()
+        // Kept for this user, whether a recipient or the sender
+        .map(|message| (message.id, utils::message_package_to(message, user_id)))
+        .collect())
+}
+
+/// Receipt packages of the messages of `user_id` whose receipts were missed
+/// while offline. Every receipt of these messages is sent, clients keep the
+/// first of each.
+pub async fn handle_pending_receipts(
+    state: &AppState,
+    user_id: Id,
+) -> Result<Vec<ProtoPackage>, String> {
+    let messages = get_pending_receipts::execute(
+        &state.db_document_client,
+        &MessageRepository(),
+        &state.cache_pool,
+        &ReceiptQueue(),
+        get_pending_receipts::Payload { user_id },
+    ).await.map_err(|err| err.to_string())?;
+
+    Ok(messages.iter()
+        .flat_map(|message| {
+            let received = message.received_by.iter()
+                .map(|receipt| utils::receipt_package(Kind::RECEIVED, message, receipt.user_id, receipt.at));
+            let read = message.read_by.iter()
+                .map(|receipt| utils::receipt_package(Kind::READ, message, receipt.user_id, receipt.at));
+            received.chain(read).collect::<Vec<_>>()
+        })
+        .collect())
+}
+
+/// Mark a message as received by `user_id` and return the receipt package
+/// for its sender, none when the sender blocked `user_id`
+pub async fn handle_received_package(
+    state: &AppState,
+    user_id: Id,
+    package: ProtoPackage,
+) -> Result<Option<ProtoPackage>, String> {
+    let message_id = utils::message_id_from_package(&package).map_err(|err| err.to_string())?;
+
+    match received_message::execute(
+        &state.db_document_client,
+        &MessageRepository(),
+        &state.cache_pool,
+        &MessageQueue(),
+        &state.db_sql_pool,
+        &BlockList(),
+        received_message::Payload { message_id, user_id },
+    ).await {
+        Ok(message) => {
+            let received_at = message.received_at(user_id).unwrap_or_else(Utc::now);
+            Ok(Some(utils::receipt_package(Kind::RECEIVED, &message, user_id, received_at)))
+        },
+        Err(received_message::Error::Blocked) => Ok(None),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Mark a message as read by `user_id` and return the receipt package for
+/// its sender, none when the sender blocked `user_id`
+pub async fn handle_read_package(
+    state: &AppState,
+    user_id: Id,
+    package: ProtoPackage,
+) -> Result<Option<ProtoPackage>, String> {
+    let message_id = utils::message_id_from_package(&package).map_err(|err| err.to_string())?;
+
+    match read_message::execute(
+        &state.db_document_client,
+        &MessageRepository(),
+        &state.db_sql_pool,
+        &BlockList(),
+        read_message::Payload { message_id, user_id },
+    ).await {
+        Ok(message) => {
+            let read_at = message.read_at(user_id).unwrap_or_else(Utc::now);
+            Ok(Some(utils::receipt_package(Kind::READ, &message, user_id, read_at)))
+        },
+        Err(read_message::Error::Blocked) => Ok(None),
+        Err(err) => Err(err.to_string()),
+    }
+}
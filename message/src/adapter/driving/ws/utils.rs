@@ -0,0 +1,863 @@
+use chrono::{DateTime, Utc};
+use protobuf::{MessageField, SpecialFields};
+use uuid::Uuid;
+
+use common::domain::{
+    protos_schemas::proto_package::{
+        proto_error::Code,
+        proto_package::{Owner, Payload},
+        proto_receipt::Kind,
+        proto_recipient::Recipient as ProtoRecipientKind,
+        ProtoAck,
+        ProtoEnvelope,
+        ProtoError,
+        ProtoHello,
+        ProtoMessage,
+        ProtoPackage,
+        ProtoPresence,
+        ProtoPreview,
+        ProtoReaction,
+        ProtoReceipt,
+        ProtoRecipient,
+        ProtoSender,
+        ProtoThumbnail,
+        ProtoTyping,
+        ProtoUuid,
+    },
+    types::{error::ErrorMsg, group::Group, id::Id, recipient::Recipient, sender_type::Sender},
+};
+use crate::domain::{
+    message::{Envelope, Message, MessageType, NewMessage},
+    presence::Presence,
+    preview::Preview,
+    types::emoji::Emoji,
+};
+
+
+/// Version of the protocol in `common/protos/proto_package.proto`
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Versions the server speaks, the handshake picks the highest one the
+/// client speaks too
+pub const SUPPORTED_VERSIONS: &[u32] = &[PROTOCOL_VERSION];
+
+/// Longest client id accepted in a package
+pub const MAX_CLIENT_ID_LEN: usize = 64;
+
+impl From<Message> for ProtoMessage {
+    fn from(message: Message) -> Self {
+        let sender: ProtoSender = message.sender.into();
+        let recipient = ProtoRecipient {
+            recipient: Some(message.recipient.into()),
+            special_fields: SpecialFields::default(),
+        };
+
+        Self {
+            id: MessageField::some(message.id.into()),
+            sender: MessageField::some(sender),
+            recipient: MessageField::some(recipient),
+            message_type: message.message_type.into(),
+            content: message.content,
+            timestamp: message.created_at.timestamp_millis(),
+            preview: MessageField::from_option(message.preview.map(Into::into)),
+            envelopes: message.envelopes.into_iter().map(Into::into).collect(),
+            special_fields: SpecialFields::default(),
+        }
+    }
+}
+
+impl From<Envelope> for ProtoEnvelope {
+    fn from(envelope: Envelope) -> Self {
+        Self {
+            device_id: MessageField::some(envelope.device_id.into()),
+            ciphertext: envelope.ciphertext,
+            special_fields: SpecialFields::default(),
+        }
+    }
+}
+
+impl TryFrom<ProtoEnvelope> for Envelope {
+    type Error = ErrorMsg;
+
+    fn try_from(envelope: ProtoEnvelope) -> Result<Self, Self::Error> {
+        let device_id = match envelope.device_id.0 {
+            Some(device_id) => (*device_id).try_into()?,
+            None => return Err(ErrorMsg("Envelope without device id".to_string())),
+        };
+        Ok(Self { device_id, ciphertext: envelope.ciphertext })
+    }
+}
+
+impl From<Preview> for ProtoPreview {
+    fn from(preview: Preview) -> Self {
+        let thumbnails = preview.thumbnails.into_iter()
+            .map(|thumbnail| ProtoThumbnail {
+                path: thumbnail.path.into(),
+                width: thumbnail.width,
+                height: thumbnail.height,
+                special_fields: SpecialFields::default(),
+            })
+            .collect();
+
+        Self {
+            width: preview.width,
+            height: preview.height,
+            blurhash: preview.blurhash,
+            thumbnails,
+            special_fields: SpecialFields::default(),
+        }
+    }
+}
+
+/// Answer to the hello opening a connection, the hello with the version
+/// picked or the error the connection is closed with
+pub fn hello_reply(package: &ProtoPackage) -> ProtoPackage {
+    let hello = match &package.payload {
+        Some(Payload::Hello(hello)) => hello,
+        _ => return unowned(error(Code::INVALID_PACKAGE, "Expected a hello".to_string())),
+    };
+    match SUPPORTED_VERSIONS.iter().filter(|version| hello.versions.contains(version)).max() {
+        Some(version) => unowned(Payload::Hello(ProtoHello {
+            versions: vec![*version],
+            special_fields: SpecialFields::default(),
+        })),
+        None => unowned(error(
+            Code::UNSUPPORTED_VERSION,
+            format!("Supported versions: {:?}", SUPPORTED_VERSIONS),
+        )),
+    }
+}
+
+/// Message carried by a message, edit or delete package
+pub fn proto_message_from_package(package: &ProtoPackage) -> Result<&ProtoMessage, ErrorMsg> {
+    match &package.payload {
+        Some(Payload::Message(message) | Payload::Edit(message) | Payload::Delete(message)) => Ok(message),
+        _ => Err(ErrorMsg("Package without message".to_string())),
+    }
+}
+
+/// Id of the message a package carries or is a receipt of
+pub fn message_id_from_package(package: &ProtoPackage) -> Result<Uuid, ErrorMsg> {
+    let id = match &package.payload {
+        Some(Payload::Receipt(receipt)) => &receipt.message_id,
+        _ => &proto_message_from_package(package)?.id,
+    };
+    match id.0.as_deref() {
+        Some(id) => {
+            let id: Id = id.clone().try_into()?;
+            Ok(id.into())
+        },
+        None => Err(ErrorMsg("Message without id".to_string())),
+    }
+}
+
+/// User the package has to be delivered to
+pub fn recipient_from_package(package: &ProtoPackage) -> Result<Id, ErrorMsg> {
+    match &package.owner {
+        Some(Owner::Recipient(recipient)) => recipient.clone().try_into(),
+        _ => Err(ErrorMsg("No recipient found".to_string())),
+    }
+}
+
+/// Recipient declared in a message
+pub fn recipient_from_proto(proto_message: &ProtoMessage) -> Result<Recipient, ErrorMsg> {
+    recipient_from_field(&proto_message.recipient)
+}
+
+/// Recipient of a typing notification and whether the sender started or
+/// stopped typing
+pub fn typing_from_package(package: &ProtoPackage) -> Result<(Recipient, bool), ErrorMsg> {
+    match &package.payload {
+        Some(Payload::Typing(typing)) => Ok((recipient_from_field(&typing.recipient)?, typing.typing)),
+        _ => Err(ErrorMsg("Package without typing notification".to_string())),
+    }
+}
+
+/// User whose presence is followed or no longer followed and whether it is
+/// followed
+pub fn presence_subscription_from_package(package: &ProtoPackage) -> Result<(Id, bool), ErrorMsg> {
+    match &package.payload {
+        Some(Payload::PresenceSubscription(subscription)) => match subscription.user.0.as_deref() {
+            Some(user) => Ok((user.clone().try_into()?, subscription.subscribe)),
+            None => Err(ErrorMsg("Subscription without user".to_string())),
+        },
+        _ => Err(ErrorMsg("Package without presence subscription".to_string())),
+    }
+}
+
+/// Message reacted to, the emoji and whether the reaction is added or removed
+pub fn reaction_from_package(package: &ProtoPackage) -> Result<(Uuid, Emoji, bool), ErrorMsg> {
+    match &package.payload {
+        Some(Payload::Reaction(reaction)) => {
+            let message_id: Id = match reaction.message_id.0.as_deref() {
+                Some(id) => id.clone().try_into()?,
+                None => return Err(ErrorMsg("Reaction without message id".to_string())),
+            };
+            let emoji = reaction.emoji.clone().try_into()?;
+            Ok((message_id.into(), emoji, reaction.added))
+        },
+        _ => Err(ErrorMsg("Package without reaction".to_string())),
+    }
+}
+
+fn recipient_from_field(recipient: &MessageField<ProtoRecipient>) -> Result<Recipient, ErrorMsg> {
+    match recipient.0.as_ref().and_then(|recipient| recipient.recipient.clone()) {
+        Some(recipient) => recipient.try_into(),
+        None => Err(ErrorMsg("Package without recipient".to_string())),
+    }
+}
+
+/// Build a `NewMessage` from a message sent by `sender`, the sender declared
+/// in the payload is ignored
+pub fn new_message_from_proto(sender: Id, proto_message: ProtoMessage) -> Result<NewMessage, ErrorMsg> {
+    let recipient = recipient_from_proto(&proto_message)?;
+
+    let message_type: MessageType = proto_message.message_type.try_into()?;
+    if message_type == MessageType::System {
+        return Err(ErrorMsg("System messages can't be sent by users".to_string()));
+    }
+    // Keys are only exchanged between the devices of two users
+    if message_type == MessageType::Encrypted && !matches!(recipient, Recipient::User(_)) {
+        return Err(ErrorMsg("Encrypted messages can only be sent to a user".to_string()));
+    }
+    // Only the devices can read encrypted messages, each one its envelope
+    let encrypted = message_type == MessageType::Encrypted;
+    if encrypted == proto_message.envelopes.is_empty() {
+        return Err(ErrorMsg("Encrypted messages and only them are sent in envelopes".to_string()));
+    }
+    if encrypted && !proto_message.content.is_empty() {
+        return Err(ErrorMsg("Encrypted messages have no content besides the envelopes".to_string()));
+    }
+    let envelopes = envelopes_from_proto(proto_message.envelopes)?;
+
+    Ok(NewMessage {
+        sender: Sender::User(sender),
+        recipient,
+        message_type,
+        content: proto_message.content,
+        envelopes,
+        preview: None,
+    })
+}
+
+/// Envelopes sealed for the devices, at most one per device
+pub fn envelopes_from_proto(envelopes: Vec<ProtoEnvelope>) -> Result<Vec<Envelope>, ErrorMsg> {
+    let envelopes = envelopes.into_iter()
+        .map(Envelope::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+    for (i, envelope) in envelopes.iter().enumerate() {
+        if envelopes[..i].iter().any(|other| other.device_id == envelope.device_id) {
+            return Err(ErrorMsg("Several envelopes for a device".to_string()));
+        }
+    }
+    Ok(envelopes)
+}
+
+/// Wrap a stored message into the package delivered to its recipient
+pub fn message_package(message: Message) -> ProtoPackage {
+    let recipient_id = recipient_id(&message.recipient);
+    message_package_to(message, recipient_id)
+}
+
+/// Wrap a stored message into the package delivered to `owner`, a user it
+/// is kept for or its recipient
+pub fn message_package_to(message: Message, owner: Id) -> ProtoPackage {
+    package(Payload::Message(message.into()), owner)
+}
+
+/// Packages delivering a stored message, the one to its recipient and, for
+/// encrypted messages, one to the sender whose other devices have envelopes
+pub fn message_packages(message: Message) -> Vec<ProtoPackage> {
+    let sender_id: Id = message.sender.clone().into();
+    let to_sender = !message.envelopes.is_empty() && recipient_id(&message.recipient) != sender_id;
+    let package = message_package(message);
+    if !to_sender {
+        return vec![package];
+    }
+    vec![
+        ProtoPackage { owner: Some(Owner::Recipient(sender_id.into())), ..package.clone() },
+        package,
+    ]
+}
+
+/// Copy of a package for a connection of the device `device_id`. Messages
+/// carrying envelopes keep the one of the device only, there is no copy when
+/// the device has none. Other packages are delivered as they are.
+pub fn device_package(package: &ProtoPackage, device_id: Option<Id>) -> Option<ProtoPackage> {
+    let message = match &package.payload {
+        Some(Payload::Message(message) | Payload::Edit(message)) if !message.envelopes.is_empty() => message,
+        _ => return Some(package.clone()),
+    };
+    let device_id: ProtoUuid = device_id?.into();
+    let envelope = message.envelopes.iter()
+        .find(|envelope| envelope.device_id.0.as_deref() == Some(&device_id))?;
+    let message = ProtoMessage { envelopes: vec![envelope.clone()], ..message.clone() };
+    let payload = match package.payload {
+        Some(Payload::Edit(_)) => Payload::Edit(message),
+        _ => Payload::Message(message),
+    };
+    Some(ProtoPackage { payload: Some(payload), ..package.clone() })
+}
+
+/// Packages carrying an edited or deleted message, `payload` is
+/// `Payload::Edit` or `Payload::Delete`. One goes to `recipient`, when there
+/// is one to tell, and one to the sender so their other devices stay in sync.
+pub fn change_packages(
+    payload: fn(ProtoMessage) -> Payload,
+    message: Message,
+    recipient: Option<Recipient>,
+) -> Vec<ProtoPackage> {
+    let sender_id: Id = message.sender.clone().into();
+    let proto_message: ProtoMessage = message.into();
+
+    let mut packages = Vec::with_capacity(2);
+    if let Some(recipient) = recipient {
+        let recipient_id = recipient_id(&recipient);
+        // Group members who blocked the sender are left out of the fan out
+        let proto_message = ProtoMessage {
+            recipient: MessageField::some(ProtoRecipient {
+                recipient: Some(recipient.into()),
+                special_fields: SpecialFields::default(),
+            }),
+            ..proto_message.clone()
+        };
+        packages.push(package(payload(proto_message), recipient_id));
+    }
+    packages.push(package(payload(proto_message), sender_id));
+    packages
+}
+
+/// Typing notification of `sender`, `typing` tells whether it started or
+/// stopped typing
+pub fn typing_package(sender: Id, recipient: Recipient, typing: bool) -> ProtoPackage {
+    let recipient_id = recipient_id(&recipient);
+    let proto_typing = ProtoTyping {
+        sender: MessageField::some(Sender::User(sender).into()),
+        recipient: MessageField::some(ProtoRecipient {
+            recipient: Some(recipient.into()),
+            special_fields: SpecialFields::default(),
+        }),
+        typing,
+        timestamp: Utc::now().timestamp_millis(),
+        special_fields: SpecialFields::default(),
+    };
+    package(Payload::Typing(proto_typing), recipient_id)
+}
+
+/// Packages announcing that `user_id` added or removed a reaction to
+/// `message`, one per user in `peers` and one to the user so their other
+/// devices stay in sync
+pub fn reaction_packages(
+    user_id: Id,
+    emoji: &Emoji,
+    added: bool,
+    message: &Message,
+    peers: Recipient,
+) -> Vec<ProtoPackage> {
+    let proto_reaction = ProtoReaction {
+        message_id: MessageField::some(message.id.into()),
+        sender: MessageField::some(Sender::User(user_id).into()),
+        recipient: MessageField::some(ProtoRecipient {
+            recipient: Some(message.recipient.clone().into()),
+            special_fields: SpecialFields::default(),
+        }),
+        emoji: emoji.clone().into(),
+        added,
+        timestamp: Utc::now().timestamp_millis(),
+        special_fields: SpecialFields::default(),
+    };
+    let owners = match peers {
+        Recipient::User(peer) => vec![peer],
+        Recipient::Group(group) => group.members,
+    };
+    owners.into_iter()
+        .chain(std::iter::once(user_id))
+        .map(|owner| package(Payload::Reaction(proto_reaction.clone()), owner))
+        .collect()
+}
+
+/// Presence of a user for one of its subscribers
+pub fn presence_package(presence: &Presence, subscriber: Id) -> ProtoPackage {
+    let proto_presence = ProtoPresence {
+        user: MessageField::some(presence.user_id.into()),
+        online: presence.online,
+        last_seen: presence.last_seen.map_or(0, |last_seen| last_seen.timestamp_millis()),
+        special_fields: SpecialFields::default(),
+    };
+    package(Payload::Presence(proto_presence), subscriber)
+}
+
+fn recipient_id(recipient: &Recipient) -> Id {
+    match recipient {
+        Recipient::User(id) => *id,
+        Recipient::Group(group) => group.id,
+    }
+}
+
+fn package(payload: Payload, owner: Id) -> ProtoPackage {
+    ProtoPackage {
+        owner: Some(Owner::Recipient(owner.into())),
+        ..unowned(payload)
+    }
+}
+
+/// Package sent straight to a connection, it isn't dispatched
+fn unowned(payload: Payload) -> ProtoPackage {
+    ProtoPackage {
+        owner: None,
+        client_id: String::new(),
+        payload: Some(payload),
+        special_fields: SpecialFields::default(),
+    }
+}
+
+/// Packages to deliver for a package, one per member other than the sender
+/// for messages and typing notifications addressed to a group, the package
+/// itself otherwise
+pub fn member_packages(package: ProtoPackage) -> Result<Vec<ProtoPackage>, ErrorMsg> {
+    let (sender, recipient) = match &package.payload {
+        Some(Payload::Message(message) | Payload::Edit(message) | Payload::Delete(message)) => {
+            (&message.sender, &message.recipient)
+        },
+        Some(Payload::Typing(typing)) => (&typing.sender, &typing.recipient),
+        _ => return Ok(vec![package]),
+    };
+    let group = match recipient.0.as_ref().and_then(|recipient| recipient.recipient.clone()) {
+        Some(ProtoRecipientKind::Group(group)) => Group::try_from(group)?,
+        _ => return Ok(vec![package]),
+    };
+    // The copy for the sender's devices is already addressed
+    if recipient_from_package(&package)? != group.id {
+        return Ok(vec![package]);
+    }
+    let sender: Id = match sender.0.as_ref().and_then(|sender| sender.user.0.as_deref()) {
+        Some(sender) => sender.clone().try_into()?,
+        None => return Err(ErrorMsg("Package without sender".to_string())),
+    };
+
+    Ok(group.members.into_iter()
+        .filter(|member| *member != sender)
+        .map(|member| ProtoPackage {
+            owner: Some(Owner::Recipient(member.into())),
+            ..package.clone()
+        })
+        .collect())
+}
+
+/// Receipt sent back to the sender of a message once `user_id`, the
+/// recipient or a member of the recipient group, received or read it
+pub fn receipt_package(kind: Kind, message: &Message, user_id: Id, at: DateTime<Utc>) -> ProtoPackage {
+    let sender: Id = message.sender.clone().into();
+    let proto_receipt = ProtoReceipt {
+        kind: kind.into(),
+        message_id: MessageField::some(message.id.into()),
+        recipient: MessageField::some(ProtoRecipient {
+            recipient: Some(message.recipient.clone().into()),
+            special_fields: SpecialFields::default(),
+        }),
+        timestamp: at.timestamp_millis(),
+        user: MessageField::some(user_id.into()),
+        special_fields: SpecialFields::default(),
+    };
+    package(Payload::Receipt(proto_receipt), sender)
+}
+
+/// Acknowledgement of a message sent by `sender` with the client id it was
+/// sent with, carrying the server id and timestamp of the stored message
+pub fn ack_package(
+    client_id: &str,
+    sender: Id,
+    message_id: Uuid,
+    created_at: DateTime<Utc>,
+) -> ProtoPackage {
+    let proto_ack = ProtoAck {
+        message_id: MessageField::some(message_id.into()),
+        timestamp: created_at.timestamp_millis(),
+        special_fields: SpecialFields::default(),
+    };
+    ProtoPackage {
+        client_id: client_id.to_string(),
+        ..package(Payload::Ack(proto_ack), sender)
+    }
+}
+
+/// Rejection of a package sent by `sender` with the client id it was sent
+/// with
+pub fn error_package(client_id: &str, sender: Id, code: Code, error_msg: String) -> ProtoPackage {
+    ProtoPackage {
+        client_id: client_id.to_string(),
+        ..package(error(code, error_msg), sender)
+    }
+}
+
+fn error(code: Code, error: String) -> Payload {
+    Payload::Error(ProtoError {
+        code: code.into(),
+        error,
+        special_fields: SpecialFields::default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use common::domain::{protos_schemas::proto_package::proto_recipient, types::media_path::MediaPath};
+    use crate::domain::preview::Thumbnail;
+    use super::*;
+
+    #[test]
+    fn test_hello_reply() {
+        let hello = |versions: Vec<u32>| ProtoPackage {
+            payload: Some(Payload::Hello(ProtoHello { versions, ..Default::default() })),
+            ..Default::default()
+        };
+        let reply = hello_reply(&hello(vec![PROTOCOL_VERSION, PROTOCOL_VERSION + 1]));
+        assert_eq!(reply.hello().versions, vec![PROTOCOL_VERSION]);
+
+        let reply = hello_reply(&hello(vec![PROTOCOL_VERSION + 1]));
+        assert_eq!(reply.error().code.enum_value(), Ok(Code::UNSUPPORTED_VERSION));
+
+        // Nothing else can open a connection
+        let reply = hello_reply(&ProtoPackage::default());
+        assert_eq!(reply.error().code.enum_value(), Ok(Code::INVALID_PACKAGE));
+    }
+
+    #[test]
+    fn test_new_message_from_proto() {
+        let sender: Id = Uuid::new_v4().try_into().unwrap();
+        let recipient: Id = Uuid::new_v4().try_into().unwrap();
+        let proto_message = ProtoMessage {
+            recipient: MessageField::some(ProtoRecipient {
+                recipient: Some(proto_recipient::Recipient::User(recipient.into())),
+                special_fields: SpecialFields::default(),
+            }),
+            message_type: "TEXT".to_string(),
+            content: b"hello".to_vec(),
+            ..Default::default()
+        };
+        let new_message = new_message_from_proto(sender, proto_message).unwrap();
+        assert!(new_message.recipient == recipient);
+        assert!(new_message.message_type == MessageType::Text);
+        assert_eq!(Id::from(new_message.sender), sender);
+
+        let proto_message = ProtoMessage {
+            message_type: "TEXT".to_string(),
+            ..Default::default()
+        };
+        assert!(new_message_from_proto(sender, proto_message).is_err());
+
+        let proto_message = ProtoMessage {
+            recipient: MessageField::some(ProtoRecipient {
+                recipient: Some(proto_recipient::Recipient::User(recipient.into())),
+                special_fields: SpecialFields::default(),
+            }),
+            message_type: "SYSTEM".to_string(),
+            ..Default::default()
+        };
+        assert!(new_message_from_proto(sender, proto_message).is_err());
+    }
+
+    #[test]
+    fn test_encrypted_message() {
+        let sender: Id = Uuid::new_v4().try_into().unwrap();
+        let recipient: Id = Uuid::new_v4().try_into().unwrap();
+        let device = || -> Id { Uuid::new_v4().try_into().unwrap() };
+        let (sender_device, recipient_phone, recipient_laptop) = (device(), device(), device());
+        // Not even valid UTF-8, relayed as sent
+        let ciphertexts = [vec![0xff, 0x00, 0x9c, 0x42], vec![0x01, 0xfe], vec![0x80; 3]];
+        let envelopes: Vec<ProtoEnvelope> = [sender_device, recipient_phone, recipient_laptop].into_iter()
+            .zip(ciphertexts.clone())
+            .map(|(device_id, ciphertext)| Envelope { device_id, ciphertext }.into())
+            .collect();
+        let encrypted = |recipient: ProtoRecipientKind, content: Vec<u8>, envelopes: Vec<ProtoEnvelope>| ProtoMessage {
+            recipient: MessageField::some(ProtoRecipient {
+                recipient: Some(recipient),
+                special_fields: SpecialFields::default(),
+            }),
+            message_type: "ENCRYPTED".to_string(),
+            content,
+            envelopes,
+            ..Default::default()
+        };
+        let to_recipient = || proto_recipient::Recipient::User(recipient.into());
+
+        let sent = new_message_from_proto(sender, encrypted(to_recipient(), Vec::new(), envelopes.clone())).unwrap();
+        assert!(sent.message_type == MessageType::Encrypted);
+        assert!(sent.content.is_empty());
+        let mut message = new_message(sender, recipient);
+        message.message_type = sent.message_type;
+        message.content = sent.content;
+        message.envelopes = sent.envelopes;
+
+        // The recipient and the other devices of the sender get a copy
+        let packages = message_packages(message);
+        let owners: Vec<Id> = packages.iter().map(|package| recipient_from_package(package).unwrap()).collect();
+        assert_eq!(owners, vec![sender, recipient]);
+        for package in &packages {
+            assert_eq!(package.message().message_type, "ENCRYPTED");
+            assert_eq!(package.message().envelopes.len(), 3);
+        }
+
+        // Every device is delivered its own envelope only, byte for byte
+        for (device_id, ciphertext) in [sender_device, recipient_phone, recipient_laptop].into_iter().zip(ciphertexts) {
+            let package = device_package(&packages[1], Some(device_id)).unwrap();
+            let envelopes = &package.message().envelopes;
+            assert_eq!(envelopes.len(), 1);
+            assert_eq!(Id::try_from((*envelopes[0].device_id).clone()).unwrap(), device_id);
+            assert_eq!(envelopes[0].ciphertext, ciphertext);
+        }
+        // Devices without an envelope get nothing they can't read
+        assert!(device_package(&packages[1], Some(device())).is_none());
+        assert!(device_package(&packages[1], None).is_none());
+        let text = message_package(new_message(sender, recipient));
+        assert_eq!(device_package(&text, None), Some(text.clone()));
+
+        // The content is only sent sealed, once per device
+        assert!(new_message_from_proto(sender, encrypted(to_recipient(), vec![0xff], Vec::new())).is_err());
+        assert!(new_message_from_proto(sender, encrypted(to_recipient(), vec![0xff], envelopes.clone())).is_err());
+        let twice = vec![envelopes[1].clone(), envelopes[1].clone()];
+        assert!(new_message_from_proto(sender, encrypted(to_recipient(), Vec::new(), twice)).is_err());
+        let text = ProtoMessage { message_type: "TEXT".to_string(), ..encrypted(to_recipient(), Vec::new(), envelopes.clone()) };
+        assert!(new_message_from_proto(sender, text).is_err());
+
+        // Group members share no keys
+        let group = proto_recipient::Recipient::Group(Group {
+            id: Uuid::new_v4().try_into().unwrap(),
+            name: "Group".to_string(),
+            members: vec![sender, recipient],
+        }.into());
+        assert!(new_message_from_proto(sender, encrypted(group, Vec::new(), envelopes)).is_err());
+    }
+
+    fn new_message(sender: Id, recipient: Id) -> Message {
+        Message {
+            id: Uuid::new_v4(),
+            sender: Sender::User(sender),
+            recipient: Recipient::User(recipient),
+            message_type: MessageType::Text,
+            content: b"hello".to_vec(),
+            envelopes: Vec::new(),
+            deleted: false,
+            edit_history: Vec::new(),
+            preview: None,
+            reactions: Vec::new(),
+            received_by: Vec::new(),
+            read_by: Vec::new(),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_message_package() {
+        let sender: Id = Uuid::new_v4().try_into().unwrap();
+        let recipient: Id = Uuid::new_v4().try_into().unwrap();
+        let message = new_message(sender, recipient);
+        let message_id = message.id;
+        let created_at = message.created_at;
+        let package = message_package(message);
+        assert!(package.has_message());
+        assert_eq!(package.owner, Some(Owner::Recipient(recipient.into())));
+
+        assert_eq!(message_id_from_package(&package).unwrap(), message_id);
+        assert_eq!(recipient_from_package(&package).unwrap(), recipient);
+
+        let proto_message = proto_message_from_package(&package).unwrap();
+        assert_eq!(proto_message.timestamp, created_at.timestamp_millis());
+        assert_eq!(proto_message.content, b"hello".to_vec());
+        assert!(proto_message.preview.is_none());
+    }
+
+    #[test]
+    fn test_preview_package() {
+        let sender: Id = Uuid::new_v4().try_into().unwrap();
+        let recipient: Id = Uuid::new_v4().try_into().unwrap();
+        let path = MediaPath::new("image", &"a".repeat(64)).unwrap();
+        let message = Message {
+            message_type: MessageType::Image,
+            preview: Some(Preview {
+                width: 1200,
+                height: 800,
+                blurhash: "LEHV6nWB2yk8pyo0adR*.7kCMdnj".to_string(),
+                thumbnails: vec![Thumbnail { path: path.clone(), width: 160, height: 107 }],
+            }),
+            ..new_message(sender, recipient)
+        };
+
+        let package = message_package(message);
+        let preview = &proto_message_from_package(&package).unwrap().preview;
+        assert_eq!(preview.width, 1200);
+        assert_eq!(preview.height, 800);
+        assert_eq!(preview.blurhash, "LEHV6nWB2yk8pyo0adR*.7kCMdnj");
+        assert_eq!(preview.thumbnails.len(), 1);
+        assert_eq!(preview.thumbnails[0].path, String::from(path));
+        assert_eq!((preview.thumbnails[0].width, preview.thumbnails[0].height), (160, 107));
+    }
+
+    #[test]
+    fn test_receipt_package() {
+        let sender: Id = Uuid::new_v4().try_into().unwrap();
+        let recipient: Id = Uuid::new_v4().try_into().unwrap();
+        let message = new_message(sender, recipient);
+        let at = chrono::Utc::now();
+        let package = receipt_package(Kind::READ, &message, recipient, at);
+        assert_eq!(package.receipt().kind.enum_value(), Ok(Kind::READ));
+        assert_eq!(package.receipt().user, MessageField::some(recipient.into()));
+        // The receipt goes back to the sender of the message
+        assert_eq!(recipient_from_package(&package).unwrap(), sender);
+        assert_eq!(message_id_from_package(&package).unwrap(), message.id);
+        assert_eq!(package.receipt().timestamp, at.timestamp_millis());
+    }
+
+    #[test]
+    fn test_member_packages() {
+        let sender: Id = Uuid::new_v4().try_into().unwrap();
+        let member: Id = Uuid::new_v4().try_into().unwrap();
+        let group = Group {
+            id: Uuid::new_v4().try_into().unwrap(),
+            name: "Group".to_string(),
+            members: vec![sender, member],
+        };
+        let mut message = new_message(sender, member);
+        message.recipient = Recipient::Group(group);
+        let message_id = message.id;
+        let packages = member_packages(message_package(message)).unwrap();
+        // Everyone but the sender gets a copy
+        assert_eq!(packages.len(), 1);
+        assert_eq!(recipient_from_package(&packages[0]).unwrap(), member);
+        assert_eq!(message_id_from_package(&packages[0]).unwrap(), message_id);
+
+        let package = message_package(new_message(sender, member));
+        assert_eq!(member_packages(package.clone()).unwrap(), vec![package]);
+    }
+
+    #[test]
+    fn test_change_packages() {
+        let sender: Id = Uuid::new_v4().try_into().unwrap();
+        let member: Id = Uuid::new_v4().try_into().unwrap();
+        let blocker: Id = Uuid::new_v4().try_into().unwrap();
+        let group = Group {
+            id: Uuid::new_v4().try_into().unwrap(),
+            name: "Group".to_string(),
+            members: vec![sender, member, blocker],
+        };
+        let mut message = new_message(sender, member);
+        message.recipient = Recipient::Group(group.clone());
+        message.content = b"edited".to_vec();
+        let message_id = message.id;
+        let recipient = Recipient::Group(Group { members: vec![sender, member], ..group });
+        let packages = change_packages(Payload::Edit, message, Some(recipient));
+        assert_eq!(packages.len(), 2);
+        assert!(packages.iter().all(|package| package.has_edit()));
+        assert_eq!(message_id_from_package(&packages[0]).unwrap(), message_id);
+        assert_eq!(proto_message_from_package(&packages[0]).unwrap().content, b"edited".to_vec());
+
+        // The group copy goes to the other members, the sender copy as is
+        let delivered: Vec<ProtoPackage> = packages.into_iter()
+            .flat_map(|package| member_packages(package).unwrap())
+            .collect();
+        assert_eq!(delivered.len(), 2);
+        assert_eq!(recipient_from_package(&delivered[0]).unwrap(), member);
+        assert_eq!(recipient_from_package(&delivered[1]).unwrap(), sender);
+
+        // Blocked by the recipient, only the sender's devices are told
+        let packages = change_packages(Payload::Delete, new_message(sender, member), None);
+        assert_eq!(packages.len(), 1);
+        assert_eq!(recipient_from_package(&packages[0]).unwrap(), sender);
+        assert!(packages[0].has_delete());
+    }
+
+    #[test]
+    fn test_typing_package() {
+        let sender: Id = Uuid::new_v4().try_into().unwrap();
+        let member: Id = Uuid::new_v4().try_into().unwrap();
+        let group = Group {
+            id: Uuid::new_v4().try_into().unwrap(),
+            name: "Group".to_string(),
+            members: vec![sender, member],
+        };
+        let package = typing_package(sender, Recipient::User(member), true);
+        assert_eq!(recipient_from_package(&package).unwrap(), member);
+        let (recipient, typing) = typing_from_package(&package).unwrap();
+        assert!(recipient == member);
+        assert!(typing);
+
+        let package = typing_package(sender, Recipient::Group(group), false);
+        let packages = member_packages(package).unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(recipient_from_package(&packages[0]).unwrap(), member);
+        assert!(!packages[0].typing().typing);
+    }
+
+    #[test]
+    fn test_reaction_packages() {
+        let sender: Id = Uuid::new_v4().try_into().unwrap();
+        let member: Id = Uuid::new_v4().try_into().unwrap();
+        let reactor: Id = Uuid::new_v4().try_into().unwrap();
+        let group = Group {
+            id: Uuid::new_v4().try_into().unwrap(),
+            name: "Group".to_string(),
+            members: vec![sender, member, reactor],
+        };
+        let mut message = new_message(sender, member);
+        message.recipient = Recipient::Group(group.clone());
+        let emoji: Emoji = "👍".to_string().try_into().unwrap();
+        let peers = Recipient::Group(Group { members: vec![sender, member], ..group });
+        let packages = reaction_packages(reactor, &emoji, true, &message, peers);
+        let owners: Vec<Id> = packages.iter()
+            .map(|package| recipient_from_package(package).unwrap())
+            .collect();
+        assert_eq!(owners, vec![sender, member, reactor]);
+        // Already addressed to each member
+        assert_eq!(member_packages(packages[0].clone()).unwrap(), vec![packages[0].clone()]);
+        let (message_id, reacted, added) = reaction_from_package(&packages[0]).unwrap();
+        assert_eq!(message_id, message.id);
+        assert_eq!(reacted, emoji);
+        assert!(added);
+
+        let message = new_message(sender, member);
+        let packages = reaction_packages(member, &emoji, false, &message, Recipient::User(sender));
+        assert_eq!(packages.len(), 2);
+        assert_eq!(recipient_from_package(&packages[0]).unwrap(), sender);
+        assert!(!packages[0].reaction().added);
+
+        let mut package = packages[0].clone();
+        package.mut_reaction().emoji = "ok".to_string();
+        assert!(reaction_from_package(&package).is_err());
+        assert!(reaction_from_package(&ProtoPackage::default()).is_err());
+    }
+
+    #[test]
+    fn test_presence_package() {
+        let user_id: Id = Uuid::new_v4().try_into().unwrap();
+        let subscriber: Id = Uuid::new_v4().try_into().unwrap();
+        let last_seen = chrono::Utc::now();
+        let presence = Presence { user_id, online: false, last_seen: Some(last_seen) };
+        let package = presence_package(&presence, subscriber);
+        assert_eq!(recipient_from_package(&package).unwrap(), subscriber);
+        assert_eq!(package.presence().user, MessageField::some(user_id.into()));
+        assert!(!package.presence().online);
+        assert_eq!(package.presence().last_seen, last_seen.timestamp_millis());
+
+        let presence = Presence { user_id, online: true, last_seen: None };
+        let package = presence_package(&presence, subscriber);
+        assert!(package.presence().online);
+        assert_eq!(package.presence().last_seen, 0);
+    }
+
+    #[test]
+    fn test_ack_package() {
+        let sender: Id = Uuid::new_v4().try_into().unwrap();
+        let message = new_message(sender, Uuid::new_v4().try_into().unwrap());
+        let package = ack_package("c1", sender, message.id, message.created_at);
+        assert_eq!(package.client_id, "c1");
+        assert_eq!(recipient_from_package(&package).unwrap(), sender);
+        let message_id: Id = package.ack().message_id.clone().unwrap().try_into().unwrap();
+        assert_eq!(Uuid::from(message_id), message.id);
+        assert_eq!(package.ack().timestamp, message.created_at.timestamp_millis());
+
+        let package = error_package("c2", sender, Code::NOT_ALLOWED, "Not allowed".to_string());
+        assert_eq!(package.client_id, "c2");
+        assert_eq!(recipient_from_package(&package).unwrap(), sender);
+        assert_eq!(package.error().code.enum_value(), Ok(Code::NOT_ALLOWED));
+    }
+}
@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+
+use common::domain::types::id::Id;
+
+
+pub enum Error {
+    Unknown(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Unknown(err) => write!(f, "Unknown error: {}", err),
+        }
+    }
+}
+
+/// Users blocked by each user in their contacts
+#[async_trait]
+pub trait BlockListTrait<T> {
+    /// Whether `user_id` blocked `blocked_id`
+    async fn is_blocked(&self, conn: &T, user_id: Id, blocked_id: Id) -> Result<bool, Error>;
+    /// Users among `user_ids` who blocked `blocked_id`
+    async fn blocked_by(&self, conn: &T, user_ids: &[Id], blocked_id: Id) -> Result<Vec<Id>, Error>;
+}
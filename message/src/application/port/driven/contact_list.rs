@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+
+use common::domain::types::id::Id;
+
+
+pub enum Error {
+    Unknown(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Unknown(err) => write!(f, "Unknown error: {}", err),
+        }
+    }
+}
+
+/// Contacts saved by each user
+#[async_trait]
+pub trait ContactListTrait<T> {
+    /// Whether `user_id` has `contact_id` in their contacts without blocking it
+    async fn is_contact(&self, conn: &T, user_id: Id, contact_id: Id) -> Result<bool, Error>;
+}
@@ -1,27 +1,20 @@
 use std::fmt;
 
-#[derive(Debug)]
-pub enum QueueAddError {
-    InvalidData(String),
-    Unknown(String),
-    Conflict(String),
-}
-
 #[derive(Debug)]
 pub enum MediaError {
     InvalidData(String),
     Unknown(String),
     Conflict(String),
+    NotFound(String),
 }
 
 impl fmt::Display for MediaError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.to_string())
+        match self {
+            MediaError::InvalidData(msg) => write!(f, "Invalid data: {}", msg),
+            MediaError::Unknown(msg) => write!(f, "Unknown error: {}", msg),
+            MediaError::Conflict(msg) => write!(f, "Conflict: {}", msg),
+            MediaError::NotFound(msg) => write!(f, "Not found: {}", msg),
+        }
     }
 }
-
-impl fmt::Display for QueueAddError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.to_string())
-    }
-}
\ No newline at end of file
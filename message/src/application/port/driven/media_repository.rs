@@ -1,4 +1,8 @@
+use std::path::Path;
+
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use common::domain::types::media_path::MediaPath;
 
 use super::errors::MediaError;
 
@@ -11,7 +15,54 @@ pub enum Media {
     File(Vec<u8>),
 }
 
+impl Media {
+    /// Kind used as the first segment of the media path
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Media::Image(_) => "image",
+            Media::Video(_) => "video",
+            Media::Audio(_) => "audio",
+            Media::File(_) => "file",
+        }
+    }
+
+    pub fn data(&self) -> &[u8] {
+        match self {
+            Media::Image(data) | Media::Video(data) | Media::Audio(data) | Media::File(data) => data,
+        }
+    }
+
+    pub fn into_data(self) -> Vec<u8> {
+        match self {
+            Media::Image(data) | Media::Video(data) | Media::Audio(data) | Media::File(data) => data,
+        }
+    }
+}
+
+/// Request the client makes straight to the storage
+pub struct PresignedUrl {
+    pub url: String,
+    pub method: String,
+    /// Headers the request has to carry for the signature to match
+    pub headers: Vec<(String, String)>,
+    pub expires_at: DateTime<Utc>,
+}
+
 #[async_trait]
 pub trait MediaRepository<T> {
-    async fn add(&self, conn: &T, media: &Media) -> Result<String, MediaError>;   
-}
\ No newline at end of file
+    /// Store a media, storing the same content again returns the same path
+    async fn add(&self, conn: &T, media: &Media) -> Result<MediaPath, MediaError>;
+    /// Store the content of `file` as `path`, its hash was already checked
+    async fn add_file(&self, conn: &T, path: &MediaPath, file: &Path) -> Result<(), MediaError>;
+    /// Content of a stored media
+    async fn get(&self, conn: &T, path: &MediaPath) -> Result<Vec<u8>, MediaError>;
+    async fn exists(&self, conn: &T, path: &MediaPath) -> Result<bool, MediaError>;
+    /// Up to `len` leading bytes of a stored media and its whole size
+    async fn head(&self, conn: &T, path: &MediaPath, len: usize) -> Result<(Vec<u8>, u64), MediaError>;
+    /// Url to upload the content of `path` to, `None` when the storage is
+    /// only reachable through the server
+    async fn upload_url(&self, conn: &T, path: &MediaPath) -> Result<Option<PresignedUrl>, MediaError>;
+    /// Url to download `path` from, `None` when the storage is only
+    /// reachable through the server
+    async fn download_url(&self, conn: &T, path: &MediaPath) -> Result<Option<PresignedUrl>, MediaError>;
+}
@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use common::domain::types::id::Id;
+
+
+pub enum Error {
+    Unknown(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Unknown(err) => write!(f, "Unknown error: {}", err),
+        }
+    }
+}
+
+/// What is known of a client message id
+pub enum Claim {
+    /// First time the id is seen, the message has to be stored
+    New,
+    /// The message is being stored by a previous send
+    Pending,
+    /// The message was already stored with this id and creation time
+    Sent(Uuid, DateTime<Utc>),
+}
+
+/// Client message ids recently used by each sender, so resent messages are
+/// not stored twice
+#[async_trait]
+pub trait MessageDedupTrait<T> {
+    /// Claim `client_id` for `sender` during `window` unless it was claimed
+    /// before
+    async fn claim(
+        &self,
+        conn: &T,
+        sender: Id,
+        client_id: &str,
+        window: Duration,
+    ) -> Result<Claim, Error>;
+    /// Remember the message stored for a claimed client id
+    async fn confirm(
+        &self,
+        conn: &T,
+        sender: Id,
+        client_id: &str,
+        message_id: Uuid,
+        created_at: DateTime<Utc>,
+        window: Duration,
+    ) -> Result<(), Error>;
+    /// Forget a claimed client id whose message was not stored, so it can be
+    /// sent again
+    async fn release(&self, conn: &T, sender: Id, client_id: &str) -> Result<(), Error>;
+}
@@ -1,10 +1,39 @@
 use async_trait::async_trait;
+use std::time::Duration;
+use uuid::Uuid;
 
+use common::domain::types::id::Id;
 use crate::domain::message::Message;
-use super::errors::QueueAddError;
 
 
+#[derive(Debug)]
+pub enum Error {
+    Unknown(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Unknown(err) => write!(f, "Unknown error: {}", err),
+        }
+    }
+}
+
+/// Queue of the messages that could not be delivered to a recipient
 #[async_trait]
-pub trait MessageQueue<T> {
-    async fn add(&self, conn: &T, message: &Message) -> Result<(), QueueAddError>;
-}
\ No newline at end of file
+pub trait MessageQueueTrait<T> {
+    /// Keep a message until the recipient acknowledges it. At most `max`
+    /// messages are kept, the oldest are dropped first, and the queue is
+    /// dropped `ttl` after the last message was added.
+    async fn add(
+        &self,
+        conn: &T,
+        recipient: Id,
+        message: &Message,
+        max: u64,
+        ttl: Duration,
+    ) -> Result<(), Error>;
+    /// Ids of the pending messages of a recipient, oldest first
+    async fn find_list(&self, conn: &T, recipient: Id) -> Result<Vec<Uuid>, Error>;
+    async fn remove(&self, conn: &T, recipient: Id, message_id: Uuid) -> Result<(), Error>;
+}
@@ -1,8 +1,14 @@
 use async_trait::async_trait;
 
-use common::domain::types::{recipient::Recipient, sender_type::Sender};
+use common::domain::types::{id::Id, media_path::MediaPath};
 use uuid::Uuid;
-use crate::domain::message::{Message, NewMessage};
+use crate::domain::{
+    conversation::Conversation,
+    message::{Envelope, Message, MessageVersion, NewMessage, Receipt},
+    preview::Preview,
+    reaction::Reaction,
+    types::{cursor::Cursor, emoji::Emoji},
+};
 
 
 pub enum Error {
@@ -21,25 +27,72 @@ impl ToString for Error {
     }
 }
 
+#[derive(Default)]
 pub struct UpdateMessage {
     pub id: Uuid,
-    pub read_at: Option<Option<chrono::DateTime<chrono::Utc>>>,
-    pub received_at: Option<Option<chrono::DateTime<chrono::Utc>>>,
+    pub content: Option<Vec<u8>>,
+    pub envelopes: Option<Vec<Envelope>>,
+    pub edit_history: Option<Vec<MessageVersion>>,
+    pub preview: Option<Option<Preview>>,
+    pub deleted: Option<bool>,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[async_trait]
 pub trait MessageRepositoryTrait<T> {
     async fn create(&self, conn: &T, new_message: NewMessage) -> Result<Message, Error>;
-    async fn find_list(
-        &self, 
-        conn: &T, 
-        sender: Sender,
-        recipient: Recipient,
-        limit: i64, 
-        offset: Option<u64>, 
-        ascending: bool,
+    /// Messages exchanged between `user_id` and `peer_id`, or sent to the
+    /// group `peer_id` while `user_id` was a member, newest first, starting
+    /// right after `before` when given.
+    async fn find_conversation(
+        &self,
+        conn: &T,
+        user_id: Id,
+        peer_id: Id,
+        limit: i64,
+        before: Option<Cursor>,
     ) -> Result<Vec<Message>, Error>;
+    /// Latest message and unread count per peer of `user_id`, most recent
+    /// first, starting right after the conversation whose last message is `before`.
+    /// Only the conversations of the `groups` `user_id` is currently a member
+    /// of are included.
+    async fn find_conversations(
+        &self,
+        conn: &T,
+        user_id: Id,
+        groups: &[Id],
+        limit: i64,
+        before: Option<Cursor>,
+    ) -> Result<Vec<Conversation>, Error>;
     async fn find_by_id(&self, conn: &T, id: Uuid) -> Result<Message, Error>;
     async fn update(&self, conn: &T, message: &UpdateMessage) -> Result<Message, Error>;
     async fn delete(&self, conn: &T, id: Uuid) -> Result<(), Error>;
+    /// Records that the user of `receipt` received the message `id`, the
+    /// first reception is kept.
+    async fn add_received(&self, conn: &T, id: Uuid, receipt: &Receipt) -> Result<Message, Error>;
+    /// Records that the user of `receipt` read the message `id`, and
+    /// received it when not recorded yet. The first read is kept.
+    async fn add_read(&self, conn: &T, id: Uuid, receipt: &Receipt) -> Result<Message, Error>;
+    /// Adds `reaction` to the message `id`, `None` when its user already
+    /// reacted with the same emoji or `MAX_REACTIONS_PER_USER` times, or the
+    /// message doesn't exist.
+    async fn add_reaction(&self, conn: &T, id: Uuid, reaction: &Reaction) -> Result<Option<Message>, Error>;
+    /// Removes the reaction of `user_id` with `emoji` from the message `id`,
+    /// `None` when there was none.
+    async fn remove_reaction(
+        &self,
+        conn: &T,
+        id: Uuid,
+        user_id: Id,
+        emoji: &Emoji,
+    ) -> Result<Option<Message>, Error>;
+    /// Whether a message sent by `user_id`, or sent to them or to a group
+    /// they were a member of, has the media `path` as content or thumbnail.
+    /// Deleted messages no longer reference their media.
+    async fn references_media(&self, conn: &T, user_id: Id, path: &MediaPath) -> Result<bool, Error>;
+    /// Record that `user_id` uploaded the media `path`, recording it again
+    /// does nothing
+    async fn add_media_owner(&self, conn: &T, user_id: Id, path: &MediaPath) -> Result<(), Error>;
+    /// Whether `user_id` uploaded the media `path`
+    async fn owns_media(&self, conn: &T, user_id: Id, path: &MediaPath) -> Result<bool, Error>;
 }
@@ -1,4 +1,11 @@
 pub mod errors;
 pub mod message_queue;
+pub mod receipt_queue;
 pub mod media_repository;
-pub mod message_repository;
\ No newline at end of file
+pub mod message_repository;
+pub mod block_list;
+pub mod upload_repository;
+pub mod presence_repository;
+pub mod contact_list;
+pub mod message_dedup;
+pub mod presence_registry;
\ No newline at end of file
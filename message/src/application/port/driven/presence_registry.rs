@@ -0,0 +1,41 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use common::domain::types::id::Id;
+
+
+#[derive(Debug)]
+pub enum Error {
+    Unknown(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Unknown(err) => write!(f, "Unknown error: {}", err),
+        }
+    }
+}
+
+/// Connections of each user on every node and the users following the
+/// presence of each user, shared by all the nodes. The connections of a node
+/// that stopped refreshing itself no longer count.
+#[async_trait]
+pub trait PresenceRegistryTrait<T> {
+    /// Count a connection of `user_id` opening on `node_id`, true when the
+    /// user had no connection on any node
+    async fn connect(&self, conn: &T, node_id: Uuid, user_id: Id) -> Result<bool, Error>;
+    /// Count a connection of `user_id` closing on `node_id`, true when it was
+    /// the last one on every node
+    async fn disconnect(&self, conn: &T, node_id: Uuid, user_id: Id) -> Result<bool, Error>;
+    /// Whether `user_id` has a connection on any node
+    async fn is_online(&self, conn: &T, user_id: Id) -> Result<bool, Error>;
+    /// Keep the connections of `node_id` counted, nodes have to refresh
+    /// themselves every few seconds
+    async fn refresh_node(&self, conn: &T, node_id: Uuid) -> Result<(), Error>;
+    async fn subscribe(&self, conn: &T, subscriber: Id, user_id: Id) -> Result<(), Error>;
+    async fn unsubscribe(&self, conn: &T, subscriber: Id, user_id: Id) -> Result<(), Error>;
+    /// Stop following the presence of every user
+    async fn unsubscribe_all(&self, conn: &T, subscriber: Id) -> Result<(), Error>;
+    async fn subscribers(&self, conn: &T, user_id: Id) -> Result<Vec<Id>, Error>;
+}
@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use common::domain::types::id::Id;
+use crate::domain::presence::PresenceRecord;
+
+
+pub enum Error {
+    NotFound(String),
+    DatabaseError(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::NotFound(err) => write!(f, "Not found: {}", err),
+            Error::DatabaseError(err) => write!(f, "Database error: {}", err),
+        }
+    }
+}
+
+/// Last seen time and privacy setting of each user, users that never
+/// disconnected nor changed the setting have no record
+#[async_trait]
+pub trait PresenceRepositoryTrait<T> {
+    async fn find(&self, conn: &T, user_id: Id) -> Result<PresenceRecord, Error>;
+    async fn set_last_seen(
+        &self,
+        conn: &T,
+        user_id: Id,
+        last_seen: DateTime<Utc>,
+    ) -> Result<PresenceRecord, Error>;
+    async fn set_hide_last_seen(
+        &self,
+        conn: &T,
+        user_id: Id,
+        hide_last_seen: bool,
+    ) -> Result<PresenceRecord, Error>;
+}
@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+use std::time::Duration;
+use uuid::Uuid;
+
+use common::domain::types::id::Id;
+
+
+#[derive(Debug)]
+pub enum Error {
+    Unknown(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Unknown(err) => write!(f, "Unknown error: {}", err),
+        }
+    }
+}
+
+/// Messages whose receipts could not be delivered to their sender
+#[async_trait]
+pub trait ReceiptQueueTrait<T> {
+    /// Keep a message until its sender connects again. At most `max`
+    /// messages are kept, the oldest are dropped first, and the queue is
+    /// dropped `ttl` after the last message was added.
+    async fn add(
+        &self,
+        conn: &T,
+        sender: Id,
+        message_id: Uuid,
+        max: u64,
+        ttl: Duration,
+    ) -> Result<(), Error>;
+    /// Ids of the messages kept for a sender, oldest first, they are no
+    /// longer kept
+    async fn take(&self, conn: &T, sender: Id) -> Result<Vec<Uuid>, Error>;
+}
@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::upload::Upload;
+
+
+#[derive(Debug)]
+pub enum Error {
+    NotFound(String),
+    Unknown(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::NotFound(err) => write!(f, "Not found: {}", err),
+            Error::Unknown(err) => write!(f, "Unknown error: {}", err),
+        }
+    }
+}
+
+/// Chunks of the uploads in progress
+#[async_trait]
+pub trait UploadRepositoryTrait<T> {
+    async fn create(&self, conn: &T, upload: &Upload) -> Result<(), Error>;
+    /// The upload along with the chunks received so far
+    async fn find_by_id(&self, conn: &T, id: Uuid) -> Result<Upload, Error>;
+    /// Store a chunk, storing it again replaces it
+    async fn add_chunk(&self, conn: &T, id: Uuid, index: u64, data: &[u8]) -> Result<(), Error>;
+    /// Join the chunks in a file, returns the file and the SHA-256 of its content in hex
+    async fn assemble(&self, conn: &T, id: Uuid) -> Result<(PathBuf, String), Error>;
+    /// Drop the upload, its chunks and the assembled file
+    async fn delete(&self, conn: &T, id: Uuid) -> Result<(), Error>;
+}
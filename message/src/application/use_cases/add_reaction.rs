@@ -0,0 +1,151 @@
+use chrono::Utc;
+use common::domain::types::{id::Id, recipient::Recipient};
+use group::{get_member_group, GroupRepositoryTrait};
+use uuid::Uuid;
+
+use super::send_message::remove_blocked;
+use crate::{
+    application::port::driven::{
+        block_list::BlockListTrait,
+        message_repository::{Error as RepositoryError, MessageRepositoryTrait},
+    },
+    domain::{
+        conversation::Conversation,
+        message::{Message, MessageType},
+        reaction::{count_by, Reaction, MAX_REACTIONS_PER_USER},
+        types::emoji::Emoji,
+    },
+};
+
+
+pub enum Error {
+    NotFound(String),
+    DatabaseError(String),
+    ConnectionError(String),
+    Unauthorized(String),
+    NotReactable(String),
+    TooManyReactions(String),
+    /// The peer blocked the user, nothing was stored
+    Blocked,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::NotFound(msg) => write!(f, "Not found: {}", msg),
+            Error::DatabaseError(msg) => write!(f, "Database error: {}", msg),
+            Error::ConnectionError(msg) => write!(f, "Connection error: {}", msg),
+            Error::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            Error::NotReactable(msg) => write!(f, "Not reactable: {}", msg),
+            Error::TooManyReactions(msg) => write!(f, "Too many reactions: {}", msg),
+            Error::Blocked => write!(f, "Blocked"),
+        }
+    }
+}
+
+pub struct Payload {
+    pub message_id: Uuid,
+    pub user_id: Id,
+    pub emoji: Emoji,
+}
+
+/// Message a reaction changed and the participants to notify besides the
+/// user who reacted
+pub struct Reacted {
+    pub message: Message,
+    pub peers: Recipient,
+}
+
+/// React to a message the user sent or received, `None` when the user had
+/// already reacted with the same emoji
+pub async fn execute<T, U, V>(
+    conn: &T,
+    message_repository: &impl MessageRepositoryTrait<T>,
+    group_conn: &U,
+    group_repository: &impl GroupRepositoryTrait<U>,
+    block_conn: &V,
+    block_list: &impl BlockListTrait<V>,
+    payload: Payload,
+) -> Result<Option<Reacted>, Error> {
+    let message = find_message(conn, message_repository, payload.message_id, payload.user_id).await?;
+    if message.deleted || message.message_type == MessageType::System {
+        return Err(Error::NotReactable("Deleted and system messages can't be reacted to".to_string()));
+    }
+    let peers = peers(group_conn, group_repository, block_conn, block_list, &message, payload.user_id).await?;
+
+    let reaction = Reaction {
+        user_id: payload.user_id,
+        emoji: payload.emoji,
+        created_at: Utc::now(),
+    };
+    match message_repository.add_reaction(conn, message.id, &reaction).await {
+        Ok(Some(message)) => Ok(Some(Reacted { message, peers })),
+        Ok(None) => {
+            // Not added because of the limit unless the emoji was already there
+            let reacted = message.reactions.iter()
+                .any(|existing| existing.user_id == reaction.user_id && existing.emoji == reaction.emoji);
+            if !reacted && count_by(&message.reactions, reaction.user_id) >= MAX_REACTIONS_PER_USER {
+                return Err(Error::TooManyReactions(
+                    format!("At most {} reactions per message", MAX_REACTIONS_PER_USER),
+                ));
+            }
+            Ok(None)
+        },
+        Err(err) => Err(Error::DatabaseError(err.to_string())),
+    }
+}
+
+/// Message `message_id` if `user_id` sent or received it
+pub(super) async fn find_message<T>(
+    conn: &T,
+    message_repository: &impl MessageRepositoryTrait<T>,
+    message_id: Uuid,
+    user_id: Id,
+) -> Result<Message, Error> {
+    let message = match message_repository.find_by_id(conn, message_id).await {
+        Ok(message) => message,
+        Err(RepositoryError::NotFound(_)) => return Err(Error::NotFound("Message not found".to_string())),
+        Err(err) => return Err(Error::DatabaseError(err.to_string())),
+    };
+    if Id::from(message.sender.clone()) != user_id && !message.recipient.includes(&user_id) {
+        return Err(Error::Unauthorized("User is not a participant of the conversation".to_string()));
+    }
+    Ok(message)
+}
+
+/// Other side of the conversation of `message` as seen by `user_id`, a
+/// group only while the user is one of its current members, who are the
+/// ones notified. The members that blocked the user are left out.
+pub(super) async fn peers<U, V>(
+    group_conn: &U,
+    group_repository: &impl GroupRepositoryTrait<U>,
+    block_conn: &V,
+    block_list: &impl BlockListTrait<V>,
+    message: &Message,
+    user_id: Id,
+) -> Result<Recipient, Error> {
+    let mut peers = match Conversation::peer_of(message, user_id) {
+        Recipient::Group(group) => {
+            let payload = get_member_group::Payload { id: group.id, user_id };
+            match get_member_group::execute(group_conn, group_repository, payload).await {
+                Ok(group) => Recipient::Group(group.into()),
+                Err(get_member_group::Error::DatabaseError) => {
+                    return Err(Error::DatabaseError("Failed to get group".to_string()))
+                },
+                Err(_) => {
+                    return Err(Error::Unauthorized("User is not a member of the group".to_string()))
+                },
+            }
+        },
+        peer => peer,
+    };
+    let reachable = remove_blocked(block_conn, block_list, user_id, &mut peers).await
+        .map_err(|err| Error::ConnectionError(err.to_string()))?;
+    if !reachable {
+        return Err(Error::Blocked);
+    }
+    if let Recipient::Group(group) = &mut peers {
+        group.members.retain(|member| *member != user_id);
+    }
+    Ok(peers)
+}
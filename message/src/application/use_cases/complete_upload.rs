@@ -0,0 +1,106 @@
+use std::path::Path;
+
+use auth::TokenData;
+use common::domain::types::{id::Id, media_path::MediaPath};
+use uuid::Uuid;
+
+use super::get_upload::{self, find_upload};
+use crate::{
+    application::port::driven::{
+        media_repository::{Media, MediaRepository},
+        message_repository::MessageRepositoryTrait,
+        upload_repository::UploadRepositoryTrait,
+    },
+    domain::{metadata, types::media::InvalidMedia},
+};
+
+
+pub enum Error {
+    Unauthorized,
+    NotFound,
+    /// Chunks still to be uploaded
+    Incomplete(Vec<u64>),
+    /// The content doesn't match the declared hash, the upload is dropped
+    ChecksumMismatch,
+    /// The upload is dropped
+    InvalidMedia(InvalidMedia),
+    ConnectionError,
+}
+
+impl From<get_upload::Error> for Error {
+    fn from(value: get_upload::Error) -> Self {
+        match value {
+            get_upload::Error::Unauthorized => Error::Unauthorized,
+            get_upload::Error::NotFound => Error::NotFound,
+            get_upload::Error::ConnectionError => Error::ConnectionError,
+        }
+    }
+}
+
+pub struct Payload {
+    pub id: Uuid,
+}
+
+/// Join the chunks of an upload into the media storage, the returned path is
+/// sent as the content of the media message. Images are stripped of their
+/// metadata so their path differs from the declared one. The user is
+/// recorded as an uploader of the path, only uploaders can send it.
+pub async fn execute<T, U, V>(
+    conn: &T,
+    upload_repository: &impl UploadRepositoryTrait<T>,
+    media_conn: &U,
+    media_repository: &impl MediaRepository<U>,
+    message_conn: &V,
+    message_repository: &impl MessageRepositoryTrait<V>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<MediaPath, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+    let upload = find_upload(conn, upload_repository, user_id, payload.id).await?;
+    let missing_chunks = upload.missing_chunks();
+    if !missing_chunks.is_empty() {
+        return Err(Error::Incomplete(missing_chunks));
+    }
+
+    let (file, hash) = upload_repository.assemble(conn, upload.id).await
+        .map_err(|_| Error::ConnectionError)?;
+    if hash != upload.media_path.hash() {
+        let _ = upload_repository.delete(conn, upload.id).await;
+        return Err(Error::ChecksumMismatch);
+    }
+    let media_path = if upload.media_path.kind() == "image" {
+        // Stored without its metadata, under the hash of what is left
+        let stripped = match stripped(&file).await {
+            Ok(data) => data,
+            Err(err) => {
+                let _ = upload_repository.delete(conn, upload.id).await;
+                return Err(err);
+            },
+        };
+        media_repository.add(media_conn, &Media::Image(stripped)).await
+            .map_err(|_| Error::ConnectionError)?
+    } else {
+        media_repository.add_file(media_conn, &upload.media_path, &file).await
+            .map_err(|_| Error::ConnectionError)?;
+        upload.media_path
+    };
+    message_repository.add_media_owner(message_conn, user_id, &media_path).await
+        .map_err(|_| Error::ConnectionError)?;
+    // Stored, the chunks are no longer needed
+    let _ = upload_repository.delete(conn, upload.id).await;
+    Ok(media_path)
+}
+
+/// Assembled image without its EXIF, XMP and IPTC metadata
+async fn stripped(file: &Path) -> Result<Vec<u8>, Error> {
+    let data = tokio::fs::read(file).await.map_err(|_| Error::ConnectionError)?;
+    match tokio::task::spawn_blocking(move || metadata::strip(data)).await {
+        Ok(Ok(data)) => Ok(data),
+        Ok(Err(_)) => Err(Error::InvalidMedia(InvalidMedia::Unreadable)),
+        Err(_) => Err(Error::ConnectionError),
+    }
+}
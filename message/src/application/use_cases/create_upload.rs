@@ -0,0 +1,74 @@
+use auth::TokenData;
+use common::{
+    adapter::config::MediaLimits,
+    domain::types::{id::Id, media_path::MediaPath},
+};
+
+use crate::{
+    application::port::driven::{
+        media_repository::MediaRepository,
+        message_repository::MessageRepositoryTrait,
+        upload_repository::UploadRepositoryTrait,
+    },
+    domain::{types::media::InvalidMedia, upload::Upload},
+};
+
+
+pub enum Error {
+    Unauthorized,
+    InvalidPath,
+    InvalidMedia(InvalidMedia),
+    /// The user already uploaded the content, it can be sent right away
+    AlreadyStored,
+    ConnectionError,
+}
+
+pub struct Payload {
+    pub kind: String,
+    /// SHA-256 of the whole content in hex
+    pub sha256: String,
+    pub size: u64,
+    pub media_limits: MediaLimits,
+}
+
+/// Start a chunked upload of a media, content stored by other users is
+/// uploaded again to show the user has it
+pub async fn execute<T, U, V>(
+    conn: &T,
+    upload_repository: &impl UploadRepositoryTrait<T>,
+    media_conn: &U,
+    media_repository: &impl MediaRepository<U>,
+    message_conn: &V,
+    message_repository: &impl MessageRepositoryTrait<V>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<Upload, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+    let media_path = MediaPath::new(&payload.kind, &payload.sha256.to_lowercase())
+        .map_err(|_| Error::InvalidPath)?;
+    // The content is checked with the first chunk
+    let max_size = payload.media_limits.max_size(media_path.kind());
+    if payload.size > max_size {
+        return Err(Error::InvalidMedia(InvalidMedia::TooLarge { max_size }));
+    }
+    match message_repository.owns_media(message_conn, user_id, &media_path).await {
+        Ok(true) => match media_repository.exists(media_conn, &media_path).await {
+            Ok(true) => return Err(Error::AlreadyStored),
+            Ok(false) => (),
+            Err(_) => return Err(Error::ConnectionError),
+        },
+        Ok(false) => (),
+        Err(_) => return Err(Error::ConnectionError),
+    }
+
+    let upload = Upload::new(user_id, media_path, payload.size)
+        .map_err(|_| Error::InvalidMedia(InvalidMedia::Empty))?;
+    match upload_repository.create(conn, &upload).await {
+        Ok(_) => Ok(upload),
+        Err(_) => Err(Error::ConnectionError),
+    }
+}
@@ -0,0 +1,187 @@
+use std::{future::Future, time::Duration};
+
+use chrono::{DateTime, Utc};
+use common::domain::types::id::Id;
+use uuid::Uuid;
+
+use crate::{
+    application::port::driven::message_dedup::{Claim, MessageDedupTrait},
+    domain::message::Message,
+};
+use super::send_message;
+
+
+pub enum Error {
+    /// The client id was already used, with the id and creation time of the
+    /// stored message once known
+    Duplicate(Option<(Uuid, DateTime<Utc>)>),
+    /// The recipient blocked the sender, with the id and creation time the
+    /// sender is acknowledged with as if the message was stored
+    Blocked(Uuid, DateTime<Utc>),
+    Send(send_message::Error),
+    ConnectionError(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Duplicate(_) => write!(f, "Duplicate message"),
+            Error::Blocked(..) => write!(f, "Blocked"),
+            Error::Send(err) => write!(f, "{}", err),
+            Error::ConnectionError(msg) => write!(f, "Connection error: {}", msg),
+        }
+    }
+}
+
+pub struct Payload {
+    pub sender: Id,
+    /// Id the client gave the message
+    pub client_id: String,
+    pub window: Duration,
+}
+
+/// Run `send` unless `sender` already sent a message with the same client id
+/// during the window, so a retried message is stored once. A failed send
+/// frees the client id for the next retry. A send blocked by the recipient
+/// keeps it with a made up message, so retries are answered the same way as
+/// for a stored one.
+pub async fn execute<U, F>(
+    cache_conn: &U,
+    message_dedup: &impl MessageDedupTrait<U>,
+    payload: Payload,
+    send: F,
+) -> Result<Message, Error>
+where
+    F: Future<Output = Result<Message, send_message::Error>>,
+{
+    let Payload { sender, client_id, window } = payload;
+    match message_dedup.claim(cache_conn, sender, &client_id, window).await {
+        Ok(Claim::New) => (),
+        Ok(Claim::Pending) => return Err(Error::Duplicate(None)),
+        Ok(Claim::Sent(message_id, created_at)) => {
+            return Err(Error::Duplicate(Some((message_id, created_at))))
+        },
+        Err(err) => return Err(Error::ConnectionError(err.to_string())),
+    }
+    match send.await {
+        Ok(message) => {
+            // Stored either way, retries are then answered as pending
+            let _ = message_dedup
+                .confirm(cache_conn, sender, &client_id, message.id, message.created_at, window)
+                .await;
+            Ok(message)
+        },
+        Err(send_message::Error::Blocked) => {
+            let message_id = Uuid::new_v4();
+            let created_at = Utc::now();
+            let _ = message_dedup
+                .confirm(cache_conn, sender, &client_id, message_id, created_at, window)
+                .await;
+            Err(Error::Blocked(message_id, created_at))
+        },
+        Err(err) => {
+            message_dedup.release(cache_conn, sender, &client_id).await
+                .map_err(|err| Error::ConnectionError(err.to_string()))?;
+            Err(Error::Send(err))
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Mutex};
+
+    use async_trait::async_trait;
+    use crate::application::port::driven::message_dedup::Error as DedupError;
+    use super::*;
+
+    /// Id and creation time of the message stored for a claim, none while
+    /// pending
+    type Stored = Option<(Uuid, DateTime<Utc>)>;
+
+    /// Claims by sender and client id
+    #[derive(Default)]
+    struct Claims(Mutex<HashMap<(Id, String), Stored>>);
+
+    struct InMemoryDedup();
+
+    #[async_trait]
+    impl MessageDedupTrait<Claims> for InMemoryDedup {
+        async fn claim(
+            &self,
+            conn: &Claims,
+            sender: Id,
+            client_id: &str,
+            _window: Duration,
+        ) -> Result<Claim, DedupError> {
+            let mut claims = conn.0.lock().unwrap();
+            match claims.get(&(sender, client_id.to_string())) {
+                Some(Some((message_id, created_at))) => Ok(Claim::Sent(*message_id, *created_at)),
+                Some(None) => Ok(Claim::Pending),
+                None => {
+                    claims.insert((sender, client_id.to_string()), None);
+                    Ok(Claim::New)
+                },
+            }
+        }
+
+        async fn confirm(
+            &self,
+            conn: &Claims,
+            sender: Id,
+            client_id: &str,
+            message_id: Uuid,
+            created_at: DateTime<Utc>,
+            _window: Duration,
+        ) -> Result<(), DedupError> {
+            conn.0.lock().unwrap()
+                .insert((sender, client_id.to_string()), Some((message_id, created_at)));
+            Ok(())
+        }
+
+        async fn release(&self, conn: &Claims, sender: Id, client_id: &str) -> Result<(), DedupError> {
+            conn.0.lock().unwrap().remove(&(sender, client_id.to_string()));
+            Ok(())
+        }
+    }
+
+    fn payload(sender: Id) -> Payload {
+        Payload {
+            sender,
+            client_id: "client-1".to_string(),
+            window: Duration::from_secs(60),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_blocked_retry() {
+        let sender: Id = Uuid::new_v4().try_into().unwrap();
+        let claims = Claims::default();
+        let blocked = async { Err(send_message::Error::Blocked) };
+        let (message_id, created_at) = match execute(&claims, &InMemoryDedup(), payload(sender), blocked).await {
+            Err(Error::Blocked(message_id, created_at)) => (message_id, created_at),
+            _ => panic!("Expected a blocked send"),
+        };
+
+        // Retries get the same answer as for a stored message
+        for _ in 0..2 {
+            let blocked = async { Err(send_message::Error::Blocked) };
+            match execute(&claims, &InMemoryDedup(), payload(sender), blocked).await {
+                Err(Error::Duplicate(Some(sent))) => assert_eq!(sent, (message_id, created_at)),
+                _ => panic!("Expected a duplicate"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_send_released() {
+        let sender: Id = Uuid::new_v4().try_into().unwrap();
+        let claims = Claims::default();
+        let failed = async { Err(send_message::Error::DatabaseError("down".to_string())) };
+        assert!(matches!(
+            execute(&claims, &InMemoryDedup(), payload(sender), failed).await,
+            Err(Error::Send(send_message::Error::DatabaseError(_))),
+        ));
+        assert!(claims.0.lock().unwrap().is_empty());
+    }
+}
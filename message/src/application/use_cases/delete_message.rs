@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use common::domain::types::id::Id;
+use uuid::Uuid;
+
+use super::edit_message::{unblocked_recipient, within_window, Changed};
+use crate::{
+    application::port::driven::{
+        block_list::BlockListTrait,
+        message_repository::{Error as RepositoryError, MessageRepositoryTrait, UpdateMessage},
+    },
+    domain::message::MessageType,
+};
+
+
+pub enum Error {
+    NotFound(String),
+    DatabaseError(String),
+    ConnectionError(String),
+    Unauthorized(String),
+    NotDeletable(String),
+    Expired(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::NotFound(msg) => write!(f, "Not found: {}", msg),
+            Error::DatabaseError(msg) => write!(f, "Database error: {}", msg),
+            Error::ConnectionError(msg) => write!(f, "Connection error: {}", msg),
+            Error::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            Error::NotDeletable(msg) => write!(f, "Not deletable: {}", msg),
+            Error::Expired(msg) => write!(f, "Expired: {}", msg),
+        }
+    }
+}
+
+pub struct Payload {
+    pub message_id: Uuid,
+    pub user_id: Id,
+    /// Time after sending during which the message can be deleted
+    pub edit_window: Duration,
+}
+
+/// Delete a message for everyone, the message is kept with its content and
+/// edit history cleared so both sides can show it was deleted. A recipient
+/// who blocked the sender is not told.
+pub async fn execute<T, V>(
+    conn: &T,
+    message_repository: &impl MessageRepositoryTrait<T>,
+    block_conn: &V,
+    block_list: &impl BlockListTrait<V>,
+    payload: Payload,
+) -> Result<Changed, Error> {
+    let message = match message_repository.find_by_id(conn, payload.message_id).await {
+        Ok(message) => message,
+        Err(RepositoryError::NotFound(_)) => return Err(Error::NotFound("Message not found".to_string())),
+        Err(err) => return Err(Error::DatabaseError(err.to_string())),
+    };
+    if Id::from(message.sender.clone()) != payload.user_id {
+        return Err(Error::Unauthorized("User is not the sender of the message".to_string()));
+    }
+    if message.message_type == MessageType::System {
+        return Err(Error::NotDeletable("System messages can't be deleted".to_string()));
+    }
+    let recipient = unblocked_recipient(block_conn, block_list, &message).await
+        .map_err(|err| Error::ConnectionError(err.to_string()))?;
+    if message.deleted {
+        return Ok(Changed { message, recipient });
+    }
+    let now = Utc::now();
+    if !within_window(&message, now, payload.edit_window) {
+        return Err(Error::Expired("The message can no longer be deleted".to_string()));
+    }
+
+    let update_message = UpdateMessage {
+        id: message.id,
+        content: Some(Vec::new()),
+        envelopes: Some(Vec::new()),
+        edit_history: Some(Vec::new()),
+        // The thumbnails show the content too
+        preview: Some(None),
+        deleted: Some(true),
+        updated_at: Some(now),
+    };
+    match message_repository.update(conn, &update_message).await {
+        Ok(message) => Ok(Changed { message, recipient }),
+        Err(err) => Err(Error::DatabaseError(err.to_string())),
+    }
+}
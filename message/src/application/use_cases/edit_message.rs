@@ -0,0 +1,172 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use common::domain::types::{id::Id, recipient::Recipient};
+use uuid::Uuid;
+
+use super::send_message::remove_blocked;
+use crate::{
+    application::port::driven::{
+        block_list::{BlockListTrait, Error as BlockListError},
+        message_repository::{Error as RepositoryError, MessageRepositoryTrait, UpdateMessage},
+    },
+    domain::message::{Envelope, Message, MessageType, MessageVersion},
+};
+
+
+pub enum Error {
+    NotFound(String),
+    DatabaseError(String),
+    ConnectionError(String),
+    Unauthorized(String),
+    NotEditable(String),
+    Expired(String),
+    InvalidContent(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::NotFound(msg) => write!(f, "Not found: {}", msg),
+            Error::DatabaseError(msg) => write!(f, "Database error: {}", msg),
+            Error::ConnectionError(msg) => write!(f, "Connection error: {}", msg),
+            Error::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            Error::NotEditable(msg) => write!(f, "Not editable: {}", msg),
+            Error::Expired(msg) => write!(f, "Expired: {}", msg),
+            Error::InvalidContent(msg) => write!(f, "Invalid content: {}", msg),
+        }
+    }
+}
+
+pub struct Payload {
+    pub message_id: Uuid,
+    pub user_id: Id,
+    pub content: Vec<u8>,
+    /// New content of an encrypted message, sealed for each device
+    pub envelopes: Vec<Envelope>,
+    /// Time after sending during which the message can be edited
+    pub edit_window: Duration,
+}
+
+/// Message changed by its sender and who to tell about it besides the sender
+pub struct Changed {
+    pub message: Message,
+    /// Recipient of the message without the members that blocked the
+    /// sender, none when the recipient is a user who blocked the sender
+    pub recipient: Option<Recipient>,
+}
+
+/// Replace the content of a text message, or the envelopes of an encrypted
+/// message, the previous content is kept in its edit history. A recipient who blocked the sender is not
+/// told, the sender can't tell the difference.
+pub async fn execute<T, V>(
+    conn: &T,
+    message_repository: &impl MessageRepositoryTrait<T>,
+    block_conn: &V,
+    block_list: &impl BlockListTrait<V>,
+    payload: Payload,
+) -> Result<Changed, Error> {
+    let message = match message_repository.find_by_id(conn, payload.message_id).await {
+        Ok(message) => message,
+        Err(RepositoryError::NotFound(_)) => return Err(Error::NotFound("Message not found".to_string())),
+        Err(err) => return Err(Error::DatabaseError(err.to_string())),
+    };
+    if Id::from(message.sender.clone()) != payload.user_id {
+        return Err(Error::Unauthorized("User is not the sender of the message".to_string()));
+    }
+    if message.deleted || !matches!(message.message_type, MessageType::Text | MessageType::Encrypted) {
+        return Err(Error::NotEditable("Only text and encrypted messages can be edited".to_string()));
+    }
+    let now = Utc::now();
+    if !within_window(&message, now, payload.edit_window) {
+        return Err(Error::Expired("The message can no longer be edited".to_string()));
+    }
+    let encrypted = message.message_type == MessageType::Encrypted;
+    if encrypted == payload.envelopes.is_empty() || (encrypted && !payload.content.is_empty()) {
+        return Err(Error::InvalidContent(
+            "Encrypted messages and only them are edited in envelopes".to_string()
+        ));
+    }
+    let recipient = unblocked_recipient(block_conn, block_list, &message).await
+        .map_err(|err| Error::ConnectionError(err.to_string()))?;
+
+    let mut edit_history = message.edit_history;
+    edit_history.push(MessageVersion {
+        content: message.content,
+        envelopes: message.envelopes,
+        created_at: message.updated_at,
+    });
+    let update_message = UpdateMessage {
+        id: message.id,
+        content: Some(payload.content),
+        envelopes: Some(payload.envelopes),
+        edit_history: Some(edit_history),
+        updated_at: Some(now),
+        ..Default::default()
+    };
+    match message_repository.update(conn, &update_message).await {
+        Ok(message) => Ok(Changed { message, recipient }),
+        Err(err) => Err(Error::DatabaseError(err.to_string())),
+    }
+}
+
+/// Recipient of `message` without the members that blocked its sender, none
+/// when the recipient is a user who blocked the sender
+pub(super) async fn unblocked_recipient<V>(
+    block_conn: &V,
+    block_list: &impl BlockListTrait<V>,
+    message: &Message,
+) -> Result<Option<Recipient>, BlockListError> {
+    let mut recipient = message.recipient.clone();
+    let sender: Id = message.sender.clone().into();
+    match remove_blocked(block_conn, block_list, sender, &mut recipient).await? {
+        true => Ok(Some(recipient)),
+        false => Ok(None),
+    }
+}
+
+/// Whether `now` is still within `window` of the message being sent
+pub(super) fn within_window(
+    message: &Message,
+    now: chrono::DateTime<Utc>,
+    window: Duration,
+) -> bool {
+    match chrono::Duration::from_std(window) {
+        Ok(window) => now - message.created_at <= window,
+        // Too large to represent, never expires
+        Err(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::domain::types::{recipient::Recipient, sender_type::Sender};
+    use super::*;
+
+    #[test]
+    fn test_within_window() {
+        let sender: Id = Uuid::new_v4().try_into().unwrap();
+        let recipient: Id = Uuid::new_v4().try_into().unwrap();
+        let now = Utc::now();
+        let message = Message {
+            id: Uuid::new_v4(),
+            sender: Sender::User(sender),
+            recipient: Recipient::User(recipient),
+            message_type: MessageType::Text,
+            content: b"hello".to_vec(),
+            envelopes: Vec::new(),
+            deleted: false,
+            edit_history: Vec::new(),
+            preview: None,
+            reactions: Vec::new(),
+            received_by: Vec::new(),
+            read_by: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        };
+        let window = Duration::from_secs(60);
+        assert!(within_window(&message, now, window));
+        assert!(within_window(&message, now + chrono::Duration::seconds(60), window));
+        assert!(!within_window(&message, now + chrono::Duration::seconds(61), window));
+    }
+}
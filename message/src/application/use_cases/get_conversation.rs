@@ -0,0 +1,60 @@
+use auth::TokenData;
+use common::domain::types::id::Id;
+
+use crate::{
+    application::port::driven::message_repository::MessageRepositoryTrait,
+    domain::{message::Message, types::cursor::Cursor},
+};
+
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 100;
+
+pub enum Error {
+    Unauthorized,
+    InvalidCursor,
+    DatabaseError,
+}
+
+pub struct Payload {
+    pub peer_id: Id,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+pub struct Page {
+    pub messages: Vec<Message>,
+    pub next_cursor: Option<Cursor>,
+}
+
+pub async fn execute<T>(
+    conn: &T,
+    repo: &impl MessageRepositoryTrait<T>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<Page, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+    let before = match payload.cursor {
+        Some(cursor) => Some(Cursor::try_from(cursor).map_err(|_| Error::InvalidCursor)?),
+        None => None,
+    };
+    let limit = payload.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    // One extra message tells whether there is a next page
+    let mut messages = repo
+        .find_conversation(conn, user_id, payload.peer_id, limit + 1, before)
+        .await
+        .map_err(|_| Error::DatabaseError)?;
+    let next_cursor = if messages.len() as i64 > limit {
+        messages.truncate(limit as usize);
+        messages.last().map(|message| Cursor { created_at: message.created_at, id: message.id })
+    } else {
+        None
+    };
+
+    Ok(Page { messages, next_cursor })
+}
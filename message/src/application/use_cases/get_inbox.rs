@@ -0,0 +1,64 @@
+use auth::TokenData;
+use common::domain::types::id::Id;
+
+use crate::{
+    application::port::driven::message_repository::MessageRepositoryTrait,
+    domain::{conversation::Conversation, types::cursor::Cursor},
+};
+
+
+const DEFAULT_LIMIT: i64 = 20;
+const MAX_LIMIT: i64 = 100;
+
+pub enum Error {
+    Unauthorized,
+    InvalidCursor,
+    DatabaseError,
+}
+
+pub struct Payload {
+    /// Groups the user is currently a member of
+    pub groups: Vec<Id>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+pub struct Page {
+    pub conversations: Vec<Conversation>,
+    pub next_cursor: Option<Cursor>,
+}
+
+pub async fn execute<T>(
+    conn: &T,
+    repo: &impl MessageRepositoryTrait<T>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<Page, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+    let before = match payload.cursor {
+        Some(cursor) => Some(Cursor::try_from(cursor).map_err(|_| Error::InvalidCursor)?),
+        None => None,
+    };
+    let limit = payload.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    // One extra conversation tells whether there is a next page
+    let mut conversations = repo
+        .find_conversations(conn, user_id, &payload.groups, limit + 1, before)
+        .await
+        .map_err(|_| Error::DatabaseError)?;
+    let next_cursor = if conversations.len() as i64 > limit {
+        conversations.truncate(limit as usize);
+        conversations.last().map(|conversation| Cursor {
+            created_at: conversation.last_message.created_at,
+            id: conversation.last_message.id,
+        })
+    } else {
+        None
+    };
+
+    Ok(Page { conversations, next_cursor })
+}
@@ -0,0 +1,61 @@
+use auth::TokenData;
+use common::domain::types::{id::Id, media_path::MediaPath};
+
+use crate::application::port::driven::{
+    errors::MediaError,
+    media_repository::{MediaRepository, PresignedUrl},
+    message_repository::MessageRepositoryTrait,
+};
+
+
+pub enum Error {
+    Unauthorized,
+    InvalidPath,
+    NotFound,
+    ConnectionError,
+}
+
+pub struct Payload {
+    pub path: String,
+}
+
+pub enum Download {
+    Data(Vec<u8>),
+    /// The storage serves the content itself
+    Url(PresignedUrl),
+}
+
+/// Content of a stored media, only the participants of a message with the
+/// media, its sender or recipients, can download it. The others can't tell
+/// it from a missing media.
+pub async fn execute<T, U>(
+    conn: &T,
+    media_repository: &impl MediaRepository<T>,
+    message_conn: &U,
+    message_repository: &impl MessageRepositoryTrait<U>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<Download, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+    let path = MediaPath::try_from(payload.path).map_err(|_| Error::InvalidPath)?;
+    match message_repository.references_media(message_conn, user_id, &path).await {
+        Ok(true) => (),
+        Ok(false) => return Err(Error::NotFound),
+        Err(_) => return Err(Error::ConnectionError),
+    }
+
+    match media_repository.download_url(conn, &path).await {
+        Ok(Some(url)) => return Ok(Download::Url(url)),
+        Ok(None) => (),
+        Err(_) => return Err(Error::ConnectionError),
+    }
+    match media_repository.get(conn, &path).await {
+        Ok(data) => Ok(Download::Data(data)),
+        Err(MediaError::NotFound(_)) => Err(Error::NotFound),
+        Err(_) => Err(Error::ConnectionError),
+    }
+}
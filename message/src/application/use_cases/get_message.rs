@@ -0,0 +1,51 @@
+use auth::TokenData;
+use common::domain::types::{id::Id, recipient::Recipient};
+use uuid::Uuid;
+
+use crate::{
+    application::port::driven::message_repository::{Error as RepositoryError, MessageRepositoryTrait},
+    domain::message::Message,
+};
+
+
+pub enum Error {
+    Unauthorized,
+    NotFound,
+    DatabaseError,
+}
+
+pub struct Payload {
+    pub peer_id: Id,
+    pub message_id: Uuid,
+}
+
+pub async fn execute<T>(
+    conn: &T,
+    repo: &impl MessageRepositoryTrait<T>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<Message, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+    let message = match repo.find_by_id(conn, payload.message_id).await {
+        Ok(message) => message,
+        Err(RepositoryError::NotFound(_)) => return Err(Error::NotFound),
+        Err(_) => return Err(Error::DatabaseError),
+    };
+
+    // Messages outside the conversation are reported as missing
+    let sender: Id = message.sender.clone().into();
+    let in_conversation = match &message.recipient {
+        Recipient::User(recipient) => (sender == user_id && *recipient == payload.peer_id)
+            || (sender == payload.peer_id && *recipient == user_id),
+        Recipient::Group(group) => group.id == payload.peer_id && group.members.contains(&user_id),
+    };
+    if !in_conversation {
+        return Err(Error::NotFound);
+    }
+
+    Ok(message)
+}
@@ -0,0 +1,56 @@
+use common::domain::types::id::Id;
+
+use crate::{
+    application::port::driven::{
+        message_queue::MessageQueueTrait, 
+        message_repository::{Error as RepositoryError, MessageRepositoryTrait},
+    },
+    domain::message::Message,
+};
+
+
+pub enum Error {
+    DatabaseError(String),
+    ConnectionError(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::DatabaseError(msg) => write!(f, "Database error: {}", msg),
+            Error::ConnectionError(msg) => write!(f, "Connection error: {}", msg),
+        }
+    }
+}
+
+pub struct Payload {
+    pub user_id: Id,
+}
+
+/// Messages waiting for the user, in the order they were created
+pub async fn execute<T, U>(
+    conn: &T,
+    message_repository: &impl MessageRepositoryTrait<T>,
+    cache_conn: &U,
+    message_queue: &impl MessageQueueTrait<U>,
+    payload: Payload,
+) -> Result<Vec<Message>, Error> {
+    let ids = match message_queue.find_list(cache_conn, payload.user_id).await {
+        Ok(ids) => ids,
+        Err(err) => return Err(Error::ConnectionError(err.to_string())),
+    };
+    let mut messages = Vec::with_capacity(ids.len());
+    for id in ids {
+        match message_repository.find_by_id(conn, id).await {
+            Ok(message) if !message.deleted => messages.push(message),
+            // The message no longer exists or was deleted, nothing to deliver
+            Ok(_) | Err(RepositoryError::NotFound(_)) => {
+                if let Err(err) = message_queue.remove(cache_conn, payload.user_id, id).await {
+                    return Err(Error::ConnectionError(err.to_string()));
+                }
+            },
+            Err(err) => return Err(Error::DatabaseError(err.to_string())),
+        }
+    }
+    Ok(messages)
+}
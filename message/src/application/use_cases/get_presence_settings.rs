@@ -0,0 +1,29 @@
+use auth::TokenData;
+use common::domain::types::id::Id;
+
+use crate::{
+    application::port::driven::presence_repository::PresenceRepositoryTrait,
+    domain::presence::PresenceRecord,
+};
+use super::update_presence::find_record;
+
+
+pub enum Error {
+    Unauthorized,
+    DatabaseError,
+}
+
+/// Presence privacy settings of the user
+pub async fn execute<T>(
+    conn: &T,
+    presence_repository: &impl PresenceRepositoryTrait<T>,
+    secret: &[u8],
+    token: &String,
+) -> Result<PresenceRecord, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+    find_record(conn, presence_repository, user_id).await
+        .map_err(|_| Error::DatabaseError)
+}
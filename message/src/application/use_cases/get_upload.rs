@@ -0,0 +1,48 @@
+use auth::TokenData;
+use common::domain::types::id::Id;
+use uuid::Uuid;
+
+use crate::{
+    application::port::driven::upload_repository::{Error as RepositoryError, UploadRepositoryTrait},
+    domain::upload::Upload,
+};
+
+
+pub enum Error {
+    Unauthorized,
+    NotFound,
+    ConnectionError,
+}
+
+pub struct Payload {
+    pub id: Uuid,
+}
+
+/// Upload of the user with the chunks received so far, to resume it
+pub async fn execute<T>(
+    conn: &T,
+    upload_repository: &impl UploadRepositoryTrait<T>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<Upload, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+    find_upload(conn, upload_repository, user_id, payload.id).await
+}
+
+/// Upload `id` when it belongs to `user_id`, others are reported as missing
+pub(super) async fn find_upload<T>(
+    conn: &T,
+    upload_repository: &impl UploadRepositoryTrait<T>,
+    user_id: Id,
+    id: Uuid,
+) -> Result<Upload, Error> {
+    match upload_repository.find_by_id(conn, id).await {
+        Ok(upload) if upload.user_id == user_id => Ok(upload),
+        Ok(_) | Err(RepositoryError::NotFound(_)) => Err(Error::NotFound),
+        Err(_) => Err(Error::ConnectionError),
+    }
+}
@@ -0,0 +1,67 @@
+use auth::TokenData;
+use common::domain::types::{id::Id, media_path::MediaPath};
+
+use crate::application::port::driven::{
+    media_repository::{MediaRepository, PresignedUrl},
+    message_repository::MessageRepositoryTrait,
+};
+
+
+pub enum Error {
+    Unauthorized,
+    InvalidPath,
+    /// The user already uploaded the content, it can be sent right away
+    AlreadyStored,
+    /// Uploads go through the server with this storage or for this kind, or
+    /// for content another user stored
+    Unsupported,
+    ConnectionError,
+}
+
+pub struct Payload {
+    pub path: String,
+}
+
+/// Url the client uploads a media to before sending its path as the content
+/// of a media message. The user is recorded as an uploader of the path when
+/// the url is handed out, the storage only accepts the declared content.
+pub async fn execute<T, U>(
+    conn: &T,
+    media_repository: &impl MediaRepository<T>,
+    message_conn: &U,
+    message_repository: &impl MessageRepositoryTrait<U>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<PresignedUrl, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+    let path = MediaPath::try_from(payload.path).map_err(|_| Error::InvalidPath)?;
+    // Images are stripped of their metadata on the way in, they can't skip the server
+    if path.kind() == "image" {
+        return Err(Error::Unsupported);
+    }
+
+    match media_repository.exists(conn, &path).await {
+        // Knowing the path is not having the content, it is checked by the
+        // server on a chunked upload
+        Ok(true) => match message_repository.owns_media(message_conn, user_id, &path).await {
+            Ok(true) => return Err(Error::AlreadyStored),
+            Ok(false) => return Err(Error::Unsupported),
+            Err(_) => return Err(Error::ConnectionError),
+        },
+        Ok(false) => (),
+        Err(_) => return Err(Error::ConnectionError),
+    }
+    let url = match media_repository.upload_url(conn, &path).await {
+        Ok(Some(url)) => url,
+        Ok(None) => return Err(Error::Unsupported),
+        Err(_) => return Err(Error::ConnectionError),
+    };
+    match message_repository.add_media_owner(message_conn, user_id, &path).await {
+        Ok(_) => Ok(url),
+        Err(_) => Err(Error::ConnectionError),
+    }
+}
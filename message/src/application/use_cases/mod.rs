@@ -3,4 +3,30 @@
 
 pub mod send_message;
 pub mod received_message;
-pub mod read_message;
\ No newline at end of file
+pub mod read_message;
+pub mod queue_message;
+pub mod get_pending_messages;
+pub mod queue_receipt;
+pub mod get_pending_receipts;
+pub mod get_conversation;
+pub mod get_message;
+pub mod get_inbox;
+pub mod edit_message;
+pub mod delete_message;
+pub mod add_reaction;
+pub mod remove_reaction;
+pub mod get_media;
+pub mod get_upload_url;
+pub mod create_upload;
+pub mod get_upload;
+pub mod upload_chunk;
+pub mod complete_upload;
+
+pub mod send_typing;
+pub mod update_presence;
+pub mod subscribe_presence;
+pub mod unsubscribe_presence;
+pub mod refresh_node;
+pub mod get_presence_settings;
+pub mod update_presence_settings;
+pub mod dedup_message;
\ No newline at end of file
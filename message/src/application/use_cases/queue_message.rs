@@ -0,0 +1,57 @@
+use common::domain::types::id::Id;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::application::port::driven::{
+    message_queue::MessageQueueTrait, 
+    message_repository::MessageRepositoryTrait,
+};
+
+
+pub enum Error {
+    NotFound(String),
+    ConnectionError(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::NotFound(msg) => write!(f, "Not found: {}", msg),
+            Error::ConnectionError(msg) => write!(f, "Connection error: {}", msg),
+        }
+    }
+}
+
+pub struct Payload {
+    pub recipient: Id,
+    pub message_id: Uuid,
+    /// Most messages kept for the recipient
+    pub max_pending: u64,
+    /// Time the messages are kept after the last one was queued
+    pub ttl: Duration,
+}
+
+/// Keep a message that could not be delivered until the recipient
+/// acknowledges it
+pub async fn execute<T, U>(
+    conn: &T,
+    message_repository: &impl MessageRepositoryTrait<T>,
+    cache_conn: &U,
+    message_queue: &impl MessageQueueTrait<U>,
+    payload: Payload,
+) -> Result<(), Error> {
+    let message = match message_repository.find_by_id(conn, payload.message_id).await {
+        Ok(message) => message,
+        Err(err) => return Err(Error::NotFound(err.to_string())),
+    };
+    match message_queue.add(
+        cache_conn,
+        payload.recipient,
+        &message,
+        payload.max_pending,
+        payload.ttl,
+    ).await {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::ConnectionError(err.to_string())),
+    }
+}
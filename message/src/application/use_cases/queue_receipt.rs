@@ -0,0 +1,46 @@
+use common::domain::types::id::Id;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::application::port::driven::receipt_queue::ReceiptQueueTrait;
+
+
+pub enum Error {
+    ConnectionError(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::ConnectionError(msg) => write!(f, "Connection error: {}", msg),
+        }
+    }
+}
+
+pub struct Payload {
+    pub sender: Id,
+    pub message_id: Uuid,
+    /// Most messages kept for the sender
+    pub max_pending: u64,
+    /// Time the messages are kept after the last one was queued
+    pub ttl: Duration,
+}
+
+/// Keep a message whose receipt could not be delivered to its sender, its
+/// receipts are replayed when the sender connects again
+pub async fn execute<T>(
+    cache_conn: &T,
+    receipt_queue: &impl ReceiptQueueTrait<T>,
+    payload: Payload,
+) -> Result<(), Error> {
+    match receipt_queue.add(
+        cache_conn,
+        payload.sender,
+        payload.message_id,
+        payload.max_pending,
+        payload.ttl,
+    ).await {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::ConnectionError(err.to_string())),
+    }
+}
@@ -2,7 +2,14 @@ use chrono::Utc;
 use common::domain::types::id::Id;
 use uuid::Uuid;
 
-use crate::application::port::driven::message_repository::{MessageRepositoryTrait, UpdateMessage};
+use crate::{
+    application::port::driven::{
+        block_list::BlockListTrait,
+        message_queue::MessageQueueTrait, 
+        message_repository::MessageRepositoryTrait,
+    },
+    domain::message::{Message, Receipt},
+};
 
 
 pub enum Error {
@@ -10,6 +17,20 @@ pub enum Error {
     DatabaseError(String),
     ConnectionError(String),
     Unauthorized(String),
+    /// The sender blocked the user, the receipt is dropped
+    Blocked,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::NotFound(msg) => write!(f, "Not found: {}", msg),
+            Error::DatabaseError(msg) => write!(f, "Database error: {}", msg),
+            Error::ConnectionError(msg) => write!(f, "Connection error: {}", msg),
+            Error::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            Error::Blocked => write!(f, "Blocked"),
+        }
+    }
 }
 
 pub struct Payload {
@@ -17,25 +38,41 @@ pub struct Payload {
     pub user_id: Id,
 }
 
-pub async fn execute<T, U>(
+pub async fn execute<T, U, V>(
     conn: &T,
     message_repository: &impl MessageRepositoryTrait<T>,
+    cache_conn: &U,
+    message_queue: &impl MessageQueueTrait<U>,
+    block_conn: &V,
+    block_list: &impl BlockListTrait<V>,
     payload: Payload,
-) -> Result<Uuid, Error> {
+) -> Result<Message, Error> {
     let message = match message_repository.find_by_id(conn, payload.message_id).await {
         Ok(message) => message,
         Err(err) => return Err(Error::DatabaseError(err.to_string())),
     };
-    if message.recipient != payload.user_id {
+    if !message.is_recipient(payload.user_id) {
         return Err(Error::Unauthorized("User is not the recipient of the message".to_string()));
     }
-    let update_message = UpdateMessage {
-        id: message.id,
-        read_at: None,
-        received_at: Some(Some(Utc::now())),
+    let sender: Id = message.sender.clone().into();
+    let blocked = match block_list.is_blocked(block_conn, sender, payload.user_id).await {
+        Ok(blocked) => blocked,
+        Err(err) => return Err(Error::ConnectionError(err.to_string())),
+    };
+    // Keep the first reception time when the message is acknowledged again
+    let message = if message.received_at(payload.user_id).is_none() && !blocked {
+        let receipt = Receipt { user_id: payload.user_id, at: Utc::now() };
+        match message_repository.add_received(conn, message.id, &receipt).await {
+            Ok(message) => message,
+            Err(err) => return Err(Error::DatabaseError(err.to_string())),
+        }
+    } else {
+        message
     };
-    match message_repository.update(conn, &update_message).await {
-        Ok(message) => Ok(message.id),
-        Err(err) => Err(Error::DatabaseError(err.to_string())),
+    // The message is delivered, stop replaying it
+    match message_queue.remove(cache_conn, payload.user_id, message.id).await {
+        Ok(_) if blocked => Err(Error::Blocked),
+        Ok(_) => Ok(message),
+        Err(err) => Err(Error::ConnectionError(err.to_string())),
     }
 }
@@ -0,0 +1,31 @@
+use uuid::Uuid;
+
+use crate::application::port::driven::presence_registry::PresenceRegistryTrait;
+
+
+pub enum Error {
+    ConnectionError(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::ConnectionError(msg) => write!(f, "Connection error: {}", msg),
+        }
+    }
+}
+
+pub struct Payload {
+    pub node_id: Uuid,
+}
+
+/// Keep counting the connections held by a node, they are dropped once it
+/// stops refreshing itself
+pub async fn execute<V>(
+    cache_conn: &V,
+    presence_registry: &impl PresenceRegistryTrait<V>,
+    payload: Payload,
+) -> Result<(), Error> {
+    presence_registry.refresh_node(cache_conn, payload.node_id).await
+        .map_err(|err| Error::ConnectionError(err.to_string()))
+}
@@ -0,0 +1,40 @@
+use common::domain::types::id::Id;
+use group::GroupRepositoryTrait;
+use uuid::Uuid;
+
+use super::add_reaction::{find_message, peers, Error, Reacted};
+use crate::{
+    application::port::driven::{
+        block_list::BlockListTrait,
+        message_repository::MessageRepositoryTrait,
+    },
+    domain::types::emoji::Emoji,
+};
+
+
+pub struct Payload {
+    pub message_id: Uuid,
+    pub user_id: Id,
+    pub emoji: Emoji,
+}
+
+/// Withdraw a reaction of the user, `None` when the user hadn't reacted with
+/// that emoji
+pub async fn execute<T, U, V>(
+    conn: &T,
+    message_repository: &impl MessageRepositoryTrait<T>,
+    group_conn: &U,
+    group_repository: &impl GroupRepositoryTrait<U>,
+    block_conn: &V,
+    block_list: &impl BlockListTrait<V>,
+    payload: Payload,
+) -> Result<Option<Reacted>, Error> {
+    let message = find_message(conn, message_repository, payload.message_id, payload.user_id).await?;
+    let peers = peers(group_conn, group_repository, block_conn, block_list, &message, payload.user_id).await?;
+
+    match message_repository.remove_reaction(conn, message.id, payload.user_id, &payload.emoji).await {
+        Ok(Some(message)) => Ok(Some(Reacted { message, peers })),
+        Ok(None) => Ok(None),
+        Err(err) => Err(Error::DatabaseError(err.to_string())),
+    }
+}
@@ -1,8 +1,27 @@
-use uuid::Uuid;
+use common::{
+    adapter::config::MediaLimits,
+    domain::types::{id::Id, media_path::MediaPath, recipient::Recipient},
+};
 
 use crate::{
-    application::port::driven::{media_repository::{Media, MediaRepository}, message_repository::MessageRepositoryTrait}, 
-    domain::message::{MessageType, NewMessage}
+    application::port::driven::{
+        block_list::{BlockListTrait, Error as BlockListError},
+        errors::MediaError,
+        media_repository::{Media, MediaRepository},
+        message_repository::MessageRepositoryTrait,
+    }, 
+    domain::{
+        message::{Message, MessageType, NewMessage},
+        metadata,
+        preview::{self, Preview, Thumbnail},
+        types::{
+            audio::Audio,
+            file::File,
+            image::Image,
+            media::{self, InvalidMedia, SNIFF_LEN},
+            video::Video,
+        },
+    },
 };
 
 
@@ -10,49 +29,192 @@ pub enum Error {
     NotFound(String),
     DatabaseError(String),
     ConnectionError(String),
+    /// The recipient blocked the sender, nothing was stored
+    Blocked,
+    InvalidMedia(InvalidMedia),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::NotFound(msg) => write!(f, "Not found: {}", msg),
+            Error::DatabaseError(msg) => write!(f, "Database error: {}", msg),
+            Error::ConnectionError(msg) => write!(f, "Connection error: {}", msg),
+            Error::Blocked => write!(f, "Blocked"),
+            Error::InvalidMedia(err) => write!(f, "Invalid media: {}", err),
+        }
+    }
 }
 
 pub struct Payload {
     pub new_message: NewMessage,
+    pub media_limits: MediaLimits,
 }
 
-pub async fn execute<T, U>(
+/// Store a message, members of a group that blocked the sender are left out
+/// of the recipients
+pub async fn execute<T, U, V>(
     conn: &T,
     message_repository: &impl MessageRepositoryTrait<T>,
     conn_media: &U,
     media_repository: &impl MediaRepository<U>,
+    block_conn: &V,
+    block_list: &impl BlockListTrait<V>,
     payload: Payload,
-) -> Result<Uuid, Error> {
-    let new_message = match payload.new_message.message_type {
-        MessageType::Text => NewMessage {
-            sender: payload.new_message.sender,
-            recipient: payload.new_message.recipient,
-            message_type: MessageType::Text,
-            content: payload.new_message.content,
-        },
+) -> Result<Message, Error> {
+    let mut new_message = payload.new_message;
+    let sender: Id = new_message.sender.clone().into();
+    let reachable = remove_blocked(block_conn, block_list, sender, &mut new_message.recipient).await
+        .map_err(|err| Error::ConnectionError(err.to_string()))?;
+    if !reachable {
+        return Err(Error::Blocked);
+    }
+
+    let new_message = match new_message.message_type {
+        // Ciphertext is stored as sent, it is never inspected
+        MessageType::Text | MessageType::System | MessageType::Encrypted => new_message,
         _ => {
-            let media = match payload.new_message.message_type {
-                MessageType::Image => Media::Image(payload.new_message.content),
-                MessageType::Video => Media::Video(payload.new_message.content),
-                MessageType::Audio => Media::Audio(payload.new_message.content),
-                MessageType::File => Media::File(payload.new_message.content),
+            let limits = &payload.media_limits;
+            let content = new_message.content;
+            let media = match new_message.message_type {
+                MessageType::Image => Media::Image(content),
+                MessageType::Video => Media::Video(content),
+                MessageType::Audio => Media::Audio(content),
+                MessageType::File => Media::File(content),
                 _ => panic!("Invalid message type"),
             };
-            let media_url = media_repository.add(conn_media, &media).await;
-            let media_url = match media_url {
-                Ok(media_url) => media_url,
-                Err(err) => return Err(Error::ConnectionError(err.to_string())),
+            let uploaded = match uploaded_path(&media) {
+                Some(path) => match message_repository.owns_media(conn, sender, &path).await {
+                    Ok(owned) => owned.then_some(path),
+                    Err(err) => return Err(Error::DatabaseError(err.to_string())),
+                },
+                None => None,
+            };
+            let uploaded = match uploaded {
+                // Uploaded straight to the storage, only checked now
+                Some(path) => match media_repository.head(conn_media, &path, SNIFF_LEN).await {
+                    Ok((head, size)) => {
+                        let max_size = limits.max_size(path.kind());
+                        media::validate(path.kind(), &head, size, max_size)
+                            .map_err(Error::InvalidMedia)?;
+                        Some(path)
+                    },
+                    Err(MediaError::NotFound(_)) => None,
+                    Err(err) => return Err(Error::ConnectionError(err.to_string())),
+                },
+                None => None,
+            };
+            let (media_path, media) = match uploaded {
+                Some(path) => (path, None),
+                None => {
+                    let media = validated(media, limits).map_err(Error::InvalidMedia)?;
+                    let media = match media {
+                        Media::Image(data) => Media::Image(stripped(data).await?),
+                        media => media,
+                    };
+                    match media_repository.add(conn_media, &media).await {
+                        Ok(media_path) => (media_path, Some(media)),
+                        Err(err) => return Err(Error::ConnectionError(err.to_string())),
+                    }
+                },
+            };
+            let preview = match new_message.message_type {
+                MessageType::Image => {
+                    let data = match media {
+                        Some(media) => media.into_data(),
+                        None => media_repository.get(conn_media, &media_path).await
+                            .map_err(|err| Error::ConnectionError(err.to_string()))?,
+                    };
+                    image_preview(conn_media, media_repository, data).await?
+                },
+                _ => None,
             };
             NewMessage {
-                sender: payload.new_message.sender,
-                recipient: payload.new_message.recipient,
-                message_type: payload.new_message.message_type,
-                content: media_url.as_bytes().to_vec(),
+                sender: new_message.sender,
+                recipient: new_message.recipient,
+                message_type: new_message.message_type,
+                content: String::from(media_path).into_bytes(),
+                envelopes: Vec::new(),
+                preview,
             }
         }
     };
     match message_repository.create(conn, new_message).await {
-        Ok(message) => Ok(message.id),
+        Ok(message) => Ok(message),
         Err(err) => Err(Error::DatabaseError(err.to_string())),
     }
 }
+
+/// Leave out of `recipient` the group members that blocked `sender`, false
+/// when the recipient is a user that blocked `sender`
+pub(super) async fn remove_blocked<V>(
+    block_conn: &V,
+    block_list: &impl BlockListTrait<V>,
+    sender: Id,
+    recipient: &mut Recipient,
+) -> Result<bool, BlockListError> {
+    match recipient {
+        Recipient::User(recipient) => {
+            Ok(!block_list.is_blocked(block_conn, *recipient, sender).await?)
+        },
+        Recipient::Group(group) => {
+            let blocking = block_list.blocked_by(block_conn, &group.members, sender).await?;
+            group.members.retain(|member| !blocking.contains(member));
+            Ok(true)
+        },
+    }
+}
+
+/// Path of a media the client already uploaded to the storage, sent in place
+/// of its content. Only its uploaders can send it.
+fn uploaded_path(media: &Media) -> Option<MediaPath> {
+    let path = std::str::from_utf8(media.data()).ok()?;
+    MediaPath::try_from(path.to_string()).ok()
+        .filter(|path| path.kind() == media.kind())
+}
+
+/// Image without its EXIF, XMP and IPTC metadata, location included
+async fn stripped(data: Vec<u8>) -> Result<Vec<u8>, Error> {
+    match tokio::task::spawn_blocking(move || metadata::strip(data)).await {
+        Ok(Ok(data)) => Ok(data),
+        Ok(Err(_)) => Err(Error::InvalidMedia(InvalidMedia::Unreadable)),
+        Err(err) => Err(Error::ConnectionError(err.to_string())),
+    }
+}
+
+/// Render and store the thumbnails of an image, formats that can't be
+/// decoded are sent without preview
+async fn image_preview<U>(
+    conn_media: &U,
+    media_repository: &impl MediaRepository<U>,
+    data: Vec<u8>,
+) -> Result<Option<Preview>, Error> {
+    let rendered = match tokio::task::spawn_blocking(move || preview::render(&data)).await {
+        Ok(Ok(rendered)) => rendered,
+        Ok(Err(_)) => return Ok(None),
+        Err(err) => return Err(Error::ConnectionError(err.to_string())),
+    };
+    let mut thumbnails = Vec::with_capacity(rendered.thumbnails.len());
+    for (width, height, content) in rendered.thumbnails {
+        // Stored like any other image, next to the original
+        let path = media_repository.add(conn_media, &Media::Image(content)).await
+            .map_err(|err| Error::ConnectionError(err.to_string()))?;
+        thumbnails.push(Thumbnail { path, width, height });
+    }
+    Ok(Some(Preview {
+        width: rendered.width,
+        height: rendered.height,
+        blurhash: rendered.blurhash,
+        thumbnails,
+    }))
+}
+
+/// Media sent inline, checked against its declared type and size limit
+fn validated(media: Media, limits: &MediaLimits) -> Result<Media, InvalidMedia> {
+    Ok(match media {
+        Media::Image(data) => Media::Image(Image::new(data, limits.image)?.into()),
+        Media::Video(data) => Media::Video(Video::new(data, limits.video)?.into()),
+        Media::Audio(data) => Media::Audio(Audio::new(data, limits.audio)?.into()),
+        Media::File(data) => Media::File(File::new(data, limits.file)?.into()),
+    })
+}
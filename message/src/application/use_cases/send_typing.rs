@@ -0,0 +1,42 @@
+use common::domain::types::{id::Id, recipient::Recipient};
+
+use crate::application::port::driven::block_list::BlockListTrait;
+use super::send_message::remove_blocked;
+
+
+pub enum Error {
+    ConnectionError(String),
+    /// The recipient blocked the sender, the notification is dropped
+    Blocked,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::ConnectionError(msg) => write!(f, "Connection error: {}", msg),
+            Error::Blocked => write!(f, "Blocked"),
+        }
+    }
+}
+
+pub struct Payload {
+    pub sender: Id,
+    pub recipient: Recipient,
+}
+
+/// Recipient of a typing notification, nothing is stored and members of a
+/// group that blocked the sender are left out
+pub async fn execute<V>(
+    block_conn: &V,
+    block_list: &impl BlockListTrait<V>,
+    payload: Payload,
+) -> Result<Recipient, Error> {
+    let mut recipient = payload.recipient;
+    let reachable = remove_blocked(block_conn, block_list, payload.sender, &mut recipient).await
+        .map_err(|err| Error::ConnectionError(err.to_string()))?;
+    if reachable {
+        Ok(recipient)
+    } else {
+        Err(Error::Blocked)
+    }
+}
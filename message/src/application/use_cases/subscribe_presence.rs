@@ -0,0 +1,63 @@
+use common::domain::types::id::Id;
+
+use crate::{
+    application::port::driven::{
+        contact_list::ContactListTrait,
+        presence_registry::PresenceRegistryTrait,
+        presence_repository::PresenceRepositoryTrait,
+    },
+    domain::presence::Presence,
+};
+use super::update_presence::{self, find_record};
+
+
+pub enum Error {
+    /// The user doesn't have the subscriber in their contacts
+    NotAllowed,
+    DatabaseError(String),
+    ConnectionError(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::NotAllowed => write!(f, "Not allowed"),
+            Error::DatabaseError(msg) => write!(f, "Database error: {}", msg),
+            Error::ConnectionError(msg) => write!(f, "Connection error: {}", msg),
+        }
+    }
+}
+
+pub struct Payload {
+    pub subscriber: Id,
+    pub user_id: Id,
+}
+
+/// Follow the presence of `user_id` and return its current presence, only
+/// users having the subscriber in their contacts, without blocking it, share
+/// their presence
+pub async fn execute<T, U, V>(
+    conn: &T,
+    presence_repository: &impl PresenceRepositoryTrait<T>,
+    contact_conn: &U,
+    contact_list: &impl ContactListTrait<U>,
+    cache_conn: &V,
+    presence_registry: &impl PresenceRegistryTrait<V>,
+    payload: Payload,
+) -> Result<Presence, Error> {
+    let allowed = contact_list.is_contact(contact_conn, payload.user_id, payload.subscriber).await
+        .map_err(|err| Error::ConnectionError(err.to_string()))?;
+    if !allowed {
+        return Err(Error::NotAllowed);
+    }
+    let record = match find_record(conn, presence_repository, payload.user_id).await {
+        Ok(record) => record,
+        Err(update_presence::Error::DatabaseError(msg)) => return Err(Error::DatabaseError(msg)),
+        Err(update_presence::Error::ConnectionError(msg)) => return Err(Error::ConnectionError(msg)),
+    };
+    let online = presence_registry.is_online(cache_conn, payload.user_id).await
+        .map_err(|err| Error::ConnectionError(err.to_string()))?;
+    presence_registry.subscribe(cache_conn, payload.subscriber, payload.user_id).await
+        .map_err(|err| Error::ConnectionError(err.to_string()))?;
+    Ok(record.visible(online))
+}
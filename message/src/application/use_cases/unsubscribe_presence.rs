@@ -0,0 +1,31 @@
+use common::domain::types::id::Id;
+
+use crate::application::port::driven::presence_registry::PresenceRegistryTrait;
+
+
+pub enum Error {
+    ConnectionError(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::ConnectionError(msg) => write!(f, "Connection error: {}", msg),
+        }
+    }
+}
+
+pub struct Payload {
+    pub subscriber: Id,
+    pub user_id: Id,
+}
+
+/// Stop following the presence of `user_id`
+pub async fn execute<V>(
+    cache_conn: &V,
+    presence_registry: &impl PresenceRegistryTrait<V>,
+    payload: Payload,
+) -> Result<(), Error> {
+    presence_registry.unsubscribe(cache_conn, payload.subscriber, payload.user_id).await
+        .map_err(|err| Error::ConnectionError(err.to_string()))
+}
@@ -0,0 +1,87 @@
+use chrono::Utc;
+use common::domain::types::id::Id;
+use uuid::Uuid;
+
+use crate::{
+    application::port::driven::{
+        presence_registry::PresenceRegistryTrait,
+        presence_repository::{Error as RepositoryError, PresenceRepositoryTrait},
+    },
+    domain::presence::{Presence, PresenceRecord},
+};
+
+
+pub enum Error {
+    DatabaseError(String),
+    ConnectionError(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::DatabaseError(msg) => write!(f, "Database error: {}", msg),
+            Error::ConnectionError(msg) => write!(f, "Connection error: {}", msg),
+        }
+    }
+}
+
+pub struct Payload {
+    pub user_id: Id,
+    /// Node holding the connection
+    pub node_id: Uuid,
+    /// Whether a connection of the user opened or closed
+    pub online: bool,
+}
+
+/// Presence shown to other users and the users following it
+pub struct Changed {
+    pub presence: Presence,
+    pub subscribers: Vec<Id>,
+}
+
+/// Count a connection of a user opening or closing. The user comes online
+/// with its first connection on any node and goes offline when the last one
+/// closes, the last seen time is then recorded and the user stops following
+/// the presence of others. Returns none when the presence didn't change.
+pub async fn execute<T, U>(
+    conn: &T,
+    presence_repository: &impl PresenceRepositoryTrait<T>,
+    cache_conn: &U,
+    presence_registry: &impl PresenceRegistryTrait<U>,
+    payload: Payload,
+) -> Result<Option<Changed>, Error> {
+    let Payload { user_id, node_id, online } = payload;
+    let changed = if online {
+        presence_registry.connect(cache_conn, node_id, user_id).await
+    } else {
+        presence_registry.disconnect(cache_conn, node_id, user_id).await
+    };
+    if !changed.map_err(|err| Error::ConnectionError(err.to_string()))? {
+        return Ok(None);
+    }
+
+    let record = if online {
+        find_record(conn, presence_repository, user_id).await?
+    } else {
+        presence_registry.unsubscribe_all(cache_conn, user_id).await
+            .map_err(|err| Error::ConnectionError(err.to_string()))?;
+        presence_repository.set_last_seen(conn, user_id, Utc::now()).await
+            .map_err(|err| Error::DatabaseError(err.to_string()))?
+    };
+    let subscribers = presence_registry.subscribers(cache_conn, user_id).await
+        .map_err(|err| Error::ConnectionError(err.to_string()))?;
+    Ok(Some(Changed { presence: record.visible(online), subscribers }))
+}
+
+/// Record of `user_id`, users without one never disconnected
+pub(super) async fn find_record<T>(
+    conn: &T,
+    presence_repository: &impl PresenceRepositoryTrait<T>,
+    user_id: Id,
+) -> Result<PresenceRecord, Error> {
+    match presence_repository.find(conn, user_id).await {
+        Ok(record) => Ok(record),
+        Err(RepositoryError::NotFound(_)) => Ok(PresenceRecord::new(user_id)),
+        Err(err) => Err(Error::DatabaseError(err.to_string())),
+    }
+}
@@ -0,0 +1,35 @@
+use auth::TokenData;
+use common::domain::types::id::Id;
+
+use crate::{
+    application::port::driven::presence_repository::PresenceRepositoryTrait,
+    domain::presence::PresenceRecord,
+};
+
+
+pub enum Error {
+    Unauthorized,
+    DatabaseError,
+}
+
+pub struct Payload {
+    /// Keep the last seen time from the users following the presence
+    pub hide_last_seen: bool,
+}
+
+/// Change the presence privacy settings of the user, applied to the next
+/// presence updates
+pub async fn execute<T>(
+    conn: &T,
+    presence_repository: &impl PresenceRepositoryTrait<T>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<PresenceRecord, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+    presence_repository.set_hide_last_seen(conn, user_id, payload.hide_last_seen).await
+        .map_err(|_| Error::DatabaseError)
+}
@@ -0,0 +1,75 @@
+use auth::TokenData;
+use common::{adapter::config::MediaLimits, domain::types::id::Id};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use super::get_upload::{self, find_upload};
+use crate::{
+    application::port::driven::upload_repository::UploadRepositoryTrait,
+    domain::{types::media::{self, InvalidMedia}, upload::Upload},
+};
+
+
+pub enum Error {
+    Unauthorized,
+    NotFound,
+    InvalidChunk,
+    ChecksumMismatch,
+    InvalidMedia(InvalidMedia),
+    ConnectionError,
+}
+
+impl From<get_upload::Error> for Error {
+    fn from(value: get_upload::Error) -> Self {
+        match value {
+            get_upload::Error::Unauthorized => Error::Unauthorized,
+            get_upload::Error::NotFound => Error::NotFound,
+            get_upload::Error::ConnectionError => Error::ConnectionError,
+        }
+    }
+}
+
+pub struct Payload {
+    pub id: Uuid,
+    pub index: u64,
+    pub data: Vec<u8>,
+    /// SHA-256 of the chunk in hex
+    pub sha256: String,
+    pub media_limits: MediaLimits,
+}
+
+/// Store a chunk of an upload, sending a chunk again replaces it
+pub async fn execute<T>(
+    conn: &T,
+    upload_repository: &impl UploadRepositoryTrait<T>,
+    secret: &[u8],
+    token: &String,
+    payload: Payload,
+) -> Result<Upload, Error> {
+    let user_id: Id = match TokenData::from_token(token, secret) {
+        Ok(auth) => auth.id.try_into().map_err(|_| Error::Unauthorized)?,
+        Err(_) => return Err(Error::Unauthorized),
+    };
+    let mut upload = find_upload(conn, upload_repository, user_id, payload.id).await?;
+    if upload.chunk_len(payload.index) != Some(payload.data.len() as u64) {
+        return Err(Error::InvalidChunk);
+    }
+    if hex::encode(Sha256::digest(&payload.data)) != payload.sha256.to_lowercase() {
+        return Err(Error::ChecksumMismatch);
+    }
+    // The first chunk starts with the bytes telling the type of the content
+    if payload.index == 0 {
+        let kind = upload.media_path.kind();
+        let max_size = payload.media_limits.max_size(kind);
+        media::validate(kind, &payload.data, upload.size, max_size)
+            .map_err(Error::InvalidMedia)?;
+    }
+
+    if upload_repository.add_chunk(conn, upload.id, payload.index, &payload.data).await.is_err() {
+        return Err(Error::ConnectionError);
+    }
+    if let Err(index) = upload.received_chunks.binary_search(&payload.index) {
+        upload.received_chunks.insert(index, payload.index);
+    }
+    Ok(upload)
+}
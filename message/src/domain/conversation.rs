@@ -0,0 +1,69 @@
+use common::domain::types::{id::Id, recipient::Recipient};
+
+use super::message::Message;
+
+
+/// Inbox entry, the latest message exchanged with a user or group.
+pub struct Conversation {
+    pub peer: Recipient,
+    pub last_message: Message,
+    pub unread_count: u64,
+}
+
+impl Conversation {
+    /// The other side of `message` as seen by `user_id`.
+    pub fn peer_of(message: &Message, user_id: Id) -> Recipient {
+        match &message.recipient {
+            Recipient::Group(group) => Recipient::Group(group.clone()),
+            Recipient::User(recipient) if *recipient != user_id => Recipient::User(*recipient),
+            Recipient::User(_) => Recipient::User(message.sender.clone().into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use common::domain::types::{group::Group, sender_type::Sender};
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::domain::message::MessageType;
+
+    fn new_message(sender: Id, recipient: Recipient) -> Message {
+        Message {
+            id: Uuid::new_v4(),
+            sender: Sender::User(sender),
+            recipient,
+            message_type: MessageType::Text,
+            content: b"hi".to_vec(),
+            envelopes: Vec::new(),
+            deleted: false,
+            edit_history: Vec::new(),
+            preview: None,
+            reactions: Vec::new(),
+            received_by: Vec::new(),
+            read_by: Vec::new(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_peer_of() {
+        let user_a = Id::try_from(Uuid::new_v4()).unwrap();
+        let user_b = Id::try_from(Uuid::new_v4()).unwrap();
+
+        let message = new_message(user_a, Recipient::User(user_b));
+        assert!(Conversation::peer_of(&message, user_a) == Recipient::User(user_b));
+        assert!(Conversation::peer_of(&message, user_b) == Recipient::User(user_a));
+
+        let group = Group {
+            id: Id::try_from(Uuid::new_v4()).unwrap(),
+            name: "Group".to_string(),
+            members: vec![user_a, user_b],
+        };
+        let message = new_message(user_a, Recipient::Group(group.clone()));
+        assert!(Conversation::peer_of(&message, user_b) == Recipient::Group(group));
+    }
+}
@@ -1,8 +1,10 @@
 use chrono::{DateTime, Utc};
-use common::domain::types::{recipient::Recipient, sender_type::Sender};
+use common::domain::types::{error::ErrorMsg, id::Id, recipient::Recipient, sender_type::Sender};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::{preview::Preview, reaction::Reaction};
+
 
 #[derive(PartialEq, Serialize, Deserialize)]
 pub enum MessageType {
@@ -11,26 +13,175 @@ pub enum MessageType {
     Video,
     Audio,
     File,
+    /// Written by the server, e.g. group role changes
+    System,
+    /// Ciphertext only the devices of the peers can read, relayed as sent
+    Encrypted,
+}
+
+impl TryFrom<String> for MessageType {
+    type Error = ErrorMsg;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "TEXT" => Ok(Self::Text),
+            "IMAGE" => Ok(Self::Image),
+            "VIDEO" => Ok(Self::Video),
+            "AUDIO" => Ok(Self::Audio),
+            "FILE" => Ok(Self::File),
+            "SYSTEM" => Ok(Self::System),
+            "ENCRYPTED" => Ok(Self::Encrypted),
+            _ => Err(ErrorMsg("Invalid message type".to_string())),
+        }
+    }
+}
+
+impl From<MessageType> for String {
+    fn from(value: MessageType) -> Self {
+        match value {
+            MessageType::Text => "TEXT",
+            MessageType::Image => "IMAGE",
+            MessageType::Video => "VIDEO",
+            MessageType::Audio => "AUDIO",
+            MessageType::File => "FILE",
+            MessageType::System => "SYSTEM",
+            MessageType::Encrypted => "ENCRYPTED",
+        }.to_string()
+    }
+}
+
+/// Content of an encrypted message sealed for one device, only that device
+/// can read it
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    pub device_id: Id,
+    pub ciphertext: Vec<u8>,
+}
+
+/// Content a message had before an edit, `created_at` is when it was written
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MessageVersion {
+    pub content: Vec<u8>,
+    #[serde(default)]
+    pub envelopes: Vec<Envelope>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// When a recipient received or read a message
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Receipt {
+    pub user_id: Id,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub at: DateTime<Utc>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct Message {
+    #[serde(with = "bson::serde_helpers::uuid_1_as_binary")]
     pub id: Uuid,
     pub sender: Sender,
     pub recipient: Recipient,
     pub message_type: MessageType,
     pub content: Vec<u8>,
+    /// Content of encrypted messages, one per device of the peers, whose
+    /// `content` is empty
+    #[serde(default)]
+    pub envelopes: Vec<Envelope>,
     pub deleted: bool,
-    pub received_at: Option<DateTime<Utc>>,
-    pub read_at: Option<DateTime<Utc>>,
+    /// Previous contents, oldest first
+    #[serde(default)]
+    pub edit_history: Vec<MessageVersion>,
+    /// Thumbnails and placeholder of image messages
+    #[serde(default)]
+    pub preview: Option<Preview>,
+    /// One per user and emoji, oldest first
+    #[serde(default)]
+    pub reactions: Vec<Reaction>,
+    /// One per recipient, each member of a group receives and reads it on
+    /// its own
+    #[serde(default)]
+    pub received_by: Vec<Receipt>,
+    #[serde(default)]
+    pub read_by: Vec<Receipt>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
     pub created_at: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
     pub updated_at: DateTime<Utc>,
 }
 
+impl Message {
+    /// Whether `user_id` received the message from someone else, the sender
+    /// is a member of the groups it writes to
+    pub fn is_recipient(&self, user_id: Id) -> bool {
+        Id::from(self.sender.clone()) != user_id && self.recipient.includes(&user_id)
+    }
+
+    /// When `user_id` received the message
+    pub fn received_at(&self, user_id: Id) -> Option<DateTime<Utc>> {
+        receipt_of(&self.received_by, user_id)
+    }
+
+    /// When `user_id` read the message
+    pub fn read_at(&self, user_id: Id) -> Option<DateTime<Utc>> {
+        receipt_of(&self.read_by, user_id)
+    }
+}
+
+fn receipt_of(receipts: &[Receipt], user_id: Id) -> Option<DateTime<Utc>> {
+    receipts.iter().find(|receipt| receipt.user_id == user_id).map(|receipt| receipt.at)
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct NewMessage {
     pub sender: Sender,
     pub recipient: Recipient,
     pub message_type: MessageType,
     pub content: Vec<u8>,
-}
\ No newline at end of file
+    pub envelopes: Vec<Envelope>,
+    pub preview: Option<Preview>,
+}
+
+#[cfg(test)]
+mod tests {
+    use common::domain::types::group::Group;
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn test_receipts() {
+        let sender: Id = Uuid::new_v4().try_into().unwrap();
+        let member: Id = Uuid::new_v4().try_into().unwrap();
+        let other: Id = Uuid::new_v4().try_into().unwrap();
+        let now = Utc::now();
+        let message = Message {
+            id: Uuid::new_v4(),
+            sender: Sender::User(sender),
+            recipient: Recipient::Group(Group {
+                id: Uuid::new_v4().try_into().unwrap(),
+                name: "Group".to_string(),
+                members: vec![sender, member, other],
+            }),
+            message_type: MessageType::Text,
+            content: b"hello".to_vec(),
+            envelopes: Vec::new(),
+            deleted: false,
+            edit_history: Vec::new(),
+            preview: None,
+            reactions: Vec::new(),
+            received_by: vec![Receipt { user_id: member, at: now }],
+            read_by: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        };
+        // The sender is a member but doesn't receive its own message
+        assert!(!message.is_recipient(sender));
+        assert!(message.is_recipient(member));
+        assert!(!message.is_recipient(Uuid::new_v4().try_into().unwrap()));
+
+        assert_eq!(message.received_at(member), Some(now));
+        assert_eq!(message.received_at(other), None);
+        assert_eq!(message.read_at(member), None);
+    }
+}
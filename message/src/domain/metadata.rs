@@ -0,0 +1,332 @@
+use std::io::Cursor;
+
+use common::domain::types::error::ErrorMsg;
+use exif::{In, Tag};
+use image::{
+    codecs::{jpeg::JpegEncoder, png::PngEncoder, webp::WebPEncoder},
+    DynamicImage,
+};
+
+use super::preview;
+
+
+/// Quality of JPEG images encoded again to apply their orientation
+const JPEG_QUALITY: u8 = 90;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// PNG chunks holding EXIF, text (XMP included) and the modification time
+const PNG_METADATA_CHUNKS: [&[u8; 4]; 5] = [b"eXIf", b"tEXt", b"zTXt", b"iTXt", b"tIME"];
+
+/// VP8X flags telling the WebP has EXIF and XMP chunks
+const WEBP_METADATA_FLAGS: u8 = 0b0000_1100;
+
+#[derive(Clone, Copy)]
+enum Format {
+    Jpeg,
+    Png,
+    WebP,
+}
+
+/// Remove EXIF, XMP and IPTC metadata from a JPEG, PNG or WebP image, other
+/// content is returned as is. Images rotated by their EXIF orientation are
+/// encoded again upright since the tag goes away with the rest.
+pub fn strip(data: Vec<u8>) -> Result<Vec<u8>, ErrorMsg> {
+    let format = match infer::get(&data).map(|kind| kind.mime_type()) {
+        Some("image/jpeg") => Format::Jpeg,
+        Some("image/png") => Format::Png,
+        Some("image/webp") => Format::WebP,
+        _ => return Ok(data),
+    };
+    match orientation(&data) {
+        1 => match format {
+            Format::Jpeg => strip_jpeg(&data),
+            Format::Png => strip_png(&data),
+            Format::WebP => strip_webp(&data),
+        },
+        orientation => upright(&data, format, orientation),
+    }
+}
+
+/// EXIF orientation, 1 when missing or invalid
+fn orientation(data: &[u8]) -> u32 {
+    exif::Reader::new()
+        .read_from_container(&mut Cursor::new(data))
+        .ok()
+        .and_then(|exif| {
+            exif.get_field(Tag::Orientation, In::PRIMARY)?.value.get_uint(0)
+        })
+        .filter(|orientation| (1..=8).contains(orientation))
+        .unwrap_or(1)
+}
+
+/// Image rotated as its orientation says, encoders write no metadata
+fn upright(data: &[u8], format: Format, orientation: u32) -> Result<Vec<u8>, ErrorMsg> {
+    let image = preview::decode(data)?;
+    let image = match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    };
+    let mut out = Vec::new();
+    let encoded = match format {
+        Format::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8())
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY)),
+        Format::Png => image.write_with_encoder(PngEncoder::new(&mut out)),
+        Format::WebP => DynamicImage::ImageRgba8(image.to_rgba8())
+            .write_with_encoder(WebPEncoder::new_lossless(&mut out)),
+    };
+    encoded.map_err(|err| ErrorMsg(err.to_string()))?;
+    Ok(out)
+}
+
+fn invalid(format: &str) -> ErrorMsg {
+    ErrorMsg(format!("Invalid {} image", format))
+}
+
+/// Drop APP1 (EXIF and XMP), APP2 MPF, APP13 (IPTC) and comments, and
+/// anything after the end of the image, where phones append other images
+/// with their own metadata
+fn strip_jpeg(data: &[u8]) -> Result<Vec<u8>, ErrorMsg> {
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(&data[..2]);
+    let mut pos = 2;
+    loop {
+        if data.get(pos) != Some(&0xFF) {
+            return Err(invalid("JPEG"));
+        }
+        // Markers may be padded with fill bytes
+        while data.get(pos + 1) == Some(&0xFF) {
+            pos += 1;
+        }
+        let marker = *data.get(pos + 1).ok_or_else(|| invalid("JPEG"))?;
+        match marker {
+            // Start of scan, the metadata segments are all before it
+            0xDA => {
+                let end = end_of_image(data, pos).ok_or_else(|| invalid("JPEG"))?;
+                out.extend_from_slice(&data[pos..end]);
+                return Ok(out);
+            },
+            0xD9 => {
+                out.extend_from_slice(&data[pos..pos + 2]);
+                return Ok(out);
+            },
+            // Markers without length
+            0x01 | 0xD0..=0xD7 => {
+                out.extend_from_slice(&data[pos..pos + 2]);
+                pos += 2;
+            },
+            _ => {
+                let len = data.get(pos + 2..pos + 4).ok_or_else(|| invalid("JPEG"))?;
+                let end = pos + 2 + u16::from_be_bytes([len[0], len[1]]) as usize;
+                let contents = data.get(pos + 4..end).ok_or_else(|| invalid("JPEG"))?;
+                let metadata = matches!(marker, 0xE1 | 0xED | 0xFE)
+                    || (marker == 0xE2 && contents.starts_with(b"MPF\0"));
+                if !metadata {
+                    out.extend_from_slice(&data[pos..end]);
+                }
+                pos = end;
+            },
+        }
+    }
+}
+
+/// Position right after the end of image marker, entropy coded data never
+/// holds `FF D9` since every `FF` in it is followed by `00` or a restart
+fn end_of_image(data: &[u8], from: usize) -> Option<usize> {
+    data[from..].windows(2)
+        .position(|bytes| bytes == [0xFF, 0xD9])
+        .map(|pos| from + pos + 2)
+}
+
+fn strip_png(data: &[u8]) -> Result<Vec<u8>, ErrorMsg> {
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(PNG_SIGNATURE);
+    let mut pos = PNG_SIGNATURE.len();
+    loop {
+        let header = data.get(pos..pos + 8).ok_or_else(|| invalid("PNG"))?;
+        let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let kind = &header[4..8];
+        // Length, type, contents and CRC
+        let end = pos + 12 + len;
+        let chunk = data.get(pos..end).ok_or_else(|| invalid("PNG"))?;
+        if !PNG_METADATA_CHUNKS.iter().any(|metadata| metadata.as_slice() == kind) {
+            out.extend_from_slice(chunk);
+        }
+        if kind == b"IEND" {
+            return Ok(out);
+        }
+        pos = end;
+    }
+}
+
+fn strip_webp(data: &[u8]) -> Result<Vec<u8>, ErrorMsg> {
+    let mut chunks = Vec::with_capacity(data.len());
+    // RIFF header, its size is written once the chunks are known
+    let mut pos = 12;
+    while pos < data.len() {
+        let header = data.get(pos..pos + 8).ok_or_else(|| invalid("WebP"))?;
+        let id = &header[..4];
+        let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        // Chunks are padded to an even size
+        let end = (pos + 8 + len + len % 2).min(data.len());
+        let chunk = data.get(pos..end).ok_or_else(|| invalid("WebP"))?;
+        match id {
+            b"EXIF" | b"XMP " => (),
+            b"VP8X" if chunk.len() > 8 => {
+                chunks.extend_from_slice(chunk);
+                let flags = chunks.len() - chunk.len() + 8;
+                chunks[flags] &= !WEBP_METADATA_FLAGS;
+            },
+            _ => chunks.extend_from_slice(chunk),
+        }
+        pos = end;
+    }
+
+    let mut out = Vec::with_capacity(chunks.len() + 12);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(chunks.len() as u32 + 4).to_le_bytes());
+    out.extend_from_slice(b"WEBP");
+    out.extend_from_slice(&chunks);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{ImageOutputFormat, RgbImage};
+    use super::*;
+
+    /// Little endian TIFF with an orientation and a GPS latitude
+    fn exif(orientation: u16) -> Vec<u8> {
+        let mut tiff = b"II*\0\x08\0\0\0".to_vec();
+        // IFD0 at 8 with 2 entries, then the GPS IFD at 38
+        tiff.extend_from_slice(&2u16.to_le_bytes());
+        tiff.extend_from_slice(&[0x12, 0x01, 3, 0, 1, 0, 0, 0]);
+        tiff.extend_from_slice(&orientation.to_le_bytes());
+        tiff.extend_from_slice(&[0, 0]);
+        tiff.extend_from_slice(&[0x25, 0x88, 4, 0, 1, 0, 0, 0]);
+        tiff.extend_from_slice(&38u32.to_le_bytes());
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        // GPSLatitudeRef "N"
+        tiff.extend_from_slice(&1u16.to_le_bytes());
+        tiff.extend_from_slice(&[0x01, 0x00, 2, 0, 2, 0, 0, 0, b'N', 0, 0, 0]);
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        tiff
+    }
+
+    fn encoded(format: ImageOutputFormat) -> Vec<u8> {
+        let image = RgbImage::from_fn(4, 2, |x, y| image::Rgb([x as u8 * 60, y as u8 * 120, 0]));
+        let mut data = Cursor::new(Vec::new());
+        image.write_to(&mut data, format).unwrap();
+        data.into_inner()
+    }
+
+    fn jpeg_with_exif(orientation: u16) -> Vec<u8> {
+        let jpeg = encoded(ImageOutputFormat::Jpeg(90));
+        let mut contents = b"Exif\0\0".to_vec();
+        contents.extend_from_slice(&exif(orientation));
+        let mut data = jpeg[..2].to_vec();
+        data.extend_from_slice(&[0xFF, 0xE1]);
+        data.extend_from_slice(&(contents.len() as u16 + 2).to_be_bytes());
+        data.extend_from_slice(&contents);
+        data.extend_from_slice(&jpeg[2..]);
+        data
+    }
+
+    fn png_chunk(kind: &[u8], contents: &[u8]) -> Vec<u8> {
+        let mut chunk = (contents.len() as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(kind);
+        chunk.extend_from_slice(contents);
+        // Not checked when stripping
+        chunk.extend_from_slice(&[0; 4]);
+        chunk
+    }
+
+    fn has_gps(data: &[u8]) -> bool {
+        exif::Reader::new()
+            .read_from_container(&mut Cursor::new(data))
+            .map(|exif| exif.fields().any(|field| field.ifd_num == In::PRIMARY && field.tag == Tag::GPSLatitudeRef))
+            .unwrap_or(false)
+    }
+
+    #[test]
+    fn test_strip_jpeg() {
+        let data = jpeg_with_exif(1);
+        assert!(has_gps(&data));
+        let stripped = strip(data).unwrap();
+        assert!(!has_gps(&stripped));
+        assert!(!stripped.windows(4).any(|bytes| bytes == b"Exif"));
+        assert_eq!(stripped, encoded(ImageOutputFormat::Jpeg(90)));
+
+        // Trailing data after the end of image goes away too
+        let mut data = jpeg_with_exif(1);
+        data.extend_from_slice(&jpeg_with_exif(1));
+        assert_eq!(strip(data).unwrap(), encoded(ImageOutputFormat::Jpeg(90)));
+    }
+
+    #[test]
+    fn test_strip_rotated_jpeg() {
+        // Rotated 90 degrees clockwise to be shown
+        let data = jpeg_with_exif(6);
+        let stripped = strip(data).unwrap();
+        assert!(!has_gps(&stripped));
+        assert_eq!(orientation(&stripped), 1);
+        let image = image::load_from_memory(&stripped).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 4));
+    }
+
+    #[test]
+    fn test_strip_png() {
+        let png = encoded(ImageOutputFormat::Png);
+        // Metadata chunks right after IHDR
+        let ihdr_end = PNG_SIGNATURE.len() + 25;
+        let mut data = png[..ihdr_end].to_vec();
+        data.extend_from_slice(&png_chunk(b"eXIf", &exif(1)));
+        data.extend_from_slice(&png_chunk(b"iTXt", b"XML:com.adobe.xmp\0\0\0\0\0<x:xmpmeta/>"));
+        data.extend_from_slice(&png[ihdr_end..]);
+        assert!(has_gps(&data));
+
+        let stripped = strip(data).unwrap();
+        assert!(!has_gps(&stripped));
+        assert_eq!(stripped, png);
+    }
+
+    #[test]
+    fn test_strip_webp() {
+        let mut webp = Vec::new();
+        DynamicImage::ImageRgba8(image::RgbaImage::new(4, 2))
+            .write_with_encoder(WebPEncoder::new_lossless(&mut webp))
+            .unwrap();
+        let mut vp8x = b"VP8X\x0a\0\0\0".to_vec();
+        vp8x.extend_from_slice(&[WEBP_METADATA_FLAGS, 0, 0, 0, 3, 0, 0, 1, 0, 0]);
+        let exif = exif(1);
+        let mut chunks = vp8x;
+        chunks.extend_from_slice(&webp[12..]);
+        chunks.extend_from_slice(b"EXIF");
+        chunks.extend_from_slice(&(exif.len() as u32).to_le_bytes());
+        chunks.extend_from_slice(&exif);
+        chunks.extend_from_slice(b"XMP \x0c\0\0\0<x:xmpmeta/>");
+        let mut data = b"RIFF".to_vec();
+        data.extend_from_slice(&(chunks.len() as u32 + 4).to_le_bytes());
+        data.extend_from_slice(b"WEBP");
+        data.extend_from_slice(&chunks);
+        assert!(has_gps(&data));
+
+        let stripped = strip(data).unwrap();
+        assert!(!has_gps(&stripped));
+        assert!(!stripped.windows(4).any(|bytes| bytes == b"XMP "));
+        assert_eq!(stripped[20] & WEBP_METADATA_FLAGS, 0);
+        let image = image::load_from_memory(&stripped).unwrap();
+        assert_eq!((image.width(), image.height()), (4, 2));
+    }
+
+    #[test]
+    fn test_strip_other() {
+        assert_eq!(strip(b"plain text".to_vec()).unwrap(), b"plain text".to_vec());
+    }
+}
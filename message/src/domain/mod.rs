@@ -1,2 +1,8 @@
 pub mod types;
-pub mod message;
\ No newline at end of file
+pub mod message;
+pub mod conversation;
+pub mod upload;
+pub mod preview;
+pub mod metadata;
+pub mod presence;
+pub mod reaction;
\ No newline at end of file
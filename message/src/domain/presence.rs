@@ -0,0 +1,56 @@
+use chrono::{DateTime, Utc};
+use common::domain::types::id::Id;
+
+
+/// Whether a user is connected, as shown to the users following it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Presence {
+    pub user_id: Id,
+    pub online: bool,
+    /// When the last connection of the user closed, `None` when unknown or
+    /// hidden by the user
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+/// Presence stored for a user along with its privacy setting
+#[derive(Clone, Debug)]
+pub struct PresenceRecord {
+    pub user_id: Id,
+    pub last_seen: Option<DateTime<Utc>>,
+    pub hide_last_seen: bool,
+}
+
+impl PresenceRecord {
+    /// Record of a user that never disconnected
+    pub fn new(user_id: Id) -> Self {
+        Self { user_id, last_seen: None, hide_last_seen: false }
+    }
+
+    /// Presence shown to other users
+    pub fn visible(&self, online: bool) -> Presence {
+        Presence {
+            user_id: self.user_id,
+            online,
+            last_seen: if self.hide_last_seen { None } else { self.last_seen },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn test_visible() {
+        let user_id: Id = Uuid::new_v4().try_into().unwrap();
+        let last_seen = Utc::now();
+        let mut record = PresenceRecord { last_seen: Some(last_seen), ..PresenceRecord::new(user_id) };
+        assert_eq!(record.visible(false).last_seen, Some(last_seen));
+        assert!(record.visible(true).online);
+
+        record.hide_last_seen = true;
+        assert_eq!(record.visible(false), Presence { user_id, online: false, last_seen: None });
+    }
+}
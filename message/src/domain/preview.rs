@@ -0,0 +1,127 @@
+use std::io::Cursor;
+
+use common::domain::types::{error::ErrorMsg, media_path::MediaPath};
+use image::{codecs::jpeg::JpegEncoder, io::{Limits, Reader}, DynamicImage};
+use serde::{Deserialize, Serialize};
+
+
+/// Longest side of the thumbnails generated for an image, sizes the image
+/// doesn't exceed are skipped
+pub const THUMBNAIL_SIZES: [u32; 2] = [160, 640];
+
+const THUMBNAIL_QUALITY: u8 = 80;
+
+/// Images with a larger side are not decoded
+const MAX_DIMENSION: u32 = 16_384;
+
+/// Side of the copy the blurhash is computed from, the hash keeps far less
+/// detail than that
+const BLURHASH_SOURCE_SIZE: u32 = 32;
+
+/// Blurhash components, more look closer to the image but make a longer hash
+const BLURHASH_COMPONENTS: (u32, u32) = (4, 3);
+
+/// Downscaled copy of an image stored in the media repository
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Thumbnail {
+    pub path: MediaPath,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// What clients show of an image message before downloading it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Preview {
+    /// Size of the original image
+    pub width: u32,
+    pub height: u32,
+    pub blurhash: String,
+    /// Smallest first
+    pub thumbnails: Vec<Thumbnail>,
+}
+
+/// Preview of an image with the thumbnails encoded as JPEG, yet to be stored
+pub struct RenderedPreview {
+    pub width: u32,
+    pub height: u32,
+    pub blurhash: String,
+    /// Width, height and content of each thumbnail, smallest first
+    pub thumbnails: Vec<(u32, u32, Vec<u8>)>,
+}
+
+/// Decode an image and render its preview, CPU bound
+pub fn render(data: &[u8]) -> Result<RenderedPreview, ErrorMsg> {
+    let image = decode(data)?;
+    let (width, height) = (image.width(), image.height());
+
+    let mut thumbnails = Vec::new();
+    for size in THUMBNAIL_SIZES {
+        if width.max(height) <= size {
+            break;
+        }
+        let thumbnail = image.thumbnail(size, size).to_rgb8();
+        let mut content = Vec::new();
+        JpegEncoder::new_with_quality(&mut content, THUMBNAIL_QUALITY)
+            .encode_image(&thumbnail)
+            .map_err(|err| ErrorMsg(err.to_string()))?;
+        thumbnails.push((thumbnail.width(), thumbnail.height(), content));
+    }
+
+    let source = image.thumbnail(BLURHASH_SOURCE_SIZE, BLURHASH_SOURCE_SIZE).to_rgba8();
+    let (components_x, components_y) = BLURHASH_COMPONENTS;
+    let blurhash = blurhash::encode(
+        components_x,
+        components_y,
+        source.width(),
+        source.height(),
+        source.as_raw(),
+    ).map_err(|err| ErrorMsg(err.to_string()))?;
+
+    Ok(RenderedPreview { width, height, blurhash, thumbnails })
+}
+
+pub(crate) fn decode(data: &[u8]) -> Result<DynamicImage, ErrorMsg> {
+    let mut reader = Reader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|err| ErrorMsg(err.to_string()))?;
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    reader.limits(limits);
+    reader.decode().map_err(|err| ErrorMsg(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{ImageOutputFormat, RgbImage};
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image = RgbImage::from_fn(width, height, |x, y| {
+            image::Rgb([(x % 256) as u8, (y % 256) as u8, 128])
+        });
+        let mut data = Cursor::new(Vec::new());
+        image.write_to(&mut data, ImageOutputFormat::Png).unwrap();
+        data.into_inner()
+    }
+
+    #[test]
+    fn test_render() {
+        let preview = render(&png(1000, 500)).unwrap();
+        assert_eq!((preview.width, preview.height), (1000, 500));
+        let sizes: Vec<(u32, u32)> = preview.thumbnails.iter()
+            .map(|(width, height, _)| (*width, *height))
+            .collect();
+        assert_eq!(sizes, vec![(160, 80), (640, 320)]);
+        let thumbnail = image::load_from_memory(&preview.thumbnails[0].2).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (160, 80));
+        assert_eq!(preview.blurhash.len(), 28);
+
+        // Small enough to be shown as is
+        let preview = render(&png(120, 90)).unwrap();
+        assert!(preview.thumbnails.is_empty());
+        assert!(!preview.blurhash.is_empty());
+
+        assert!(render(b"not an image").is_err());
+    }
+}
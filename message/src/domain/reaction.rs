@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+use common::domain::types::id::Id;
+use serde::{Deserialize, Serialize};
+
+use super::types::emoji::Emoji;
+
+
+/// Most reactions a user can leave on a single message
+pub const MAX_REACTIONS_PER_USER: usize = 20;
+
+/// Reaction of a user to a message, a user reacts at most once with each
+/// emoji and with at most `MAX_REACTIONS_PER_USER` emojis
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Reaction {
+    pub user_id: Id,
+    pub emoji: Emoji,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Reactions to a message with the same emoji, `users` in the order they
+/// reacted
+#[derive(Debug, PartialEq)]
+pub struct ReactionCount {
+    pub emoji: Emoji,
+    pub count: usize,
+    pub users: Vec<Id>,
+}
+
+/// Reactions grouped by emoji, in the order each emoji was first used
+pub fn counts(reactions: &[Reaction]) -> Vec<ReactionCount> {
+    let mut counts: Vec<ReactionCount> = Vec::new();
+    for reaction in reactions {
+        match counts.iter_mut().find(|count| count.emoji == reaction.emoji) {
+            Some(count) => {
+                count.count += 1;
+                count.users.push(reaction.user_id);
+            },
+            None => counts.push(ReactionCount {
+                emoji: reaction.emoji.clone(),
+                count: 1,
+                users: vec![reaction.user_id],
+            }),
+        }
+    }
+    counts
+}
+
+/// Number of reactions of `user_id` among `reactions`
+pub fn count_by(reactions: &[Reaction], user_id: Id) -> usize {
+    reactions.iter().filter(|reaction| reaction.user_id == user_id).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+    use super::*;
+
+    fn reaction(user_id: Id, emoji: &str) -> Reaction {
+        Reaction {
+            user_id,
+            emoji: emoji.to_string().try_into().unwrap(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_counts() {
+        let user_a = Id::try_from(Uuid::new_v4()).unwrap();
+        let user_b = Id::try_from(Uuid::new_v4()).unwrap();
+        let reactions = vec![
+            reaction(user_a, "👍"),
+            reaction(user_a, "❤️"),
+            reaction(user_b, "👍"),
+        ];
+        let counts = counts(&reactions);
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[0].emoji, "👍".to_string().try_into().unwrap());
+        assert_eq!(counts[0].count, 2);
+        assert_eq!(counts[0].users, vec![user_a, user_b]);
+        assert_eq!(counts[1].count, 1);
+        assert_eq!(counts[1].users, vec![user_a]);
+
+        assert!(super::counts(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_count_by() {
+        let user_a = Id::try_from(Uuid::new_v4()).unwrap();
+        let user_b = Id::try_from(Uuid::new_v4()).unwrap();
+        let reactions = vec![
+            reaction(user_a, "👍"),
+            reaction(user_a, "❤️"),
+            reaction(user_b, "👍"),
+        ];
+        assert_eq!(count_by(&reactions, user_a), 2);
+        assert_eq!(count_by(&reactions, user_b), 1);
+        assert_eq!(count_by(&[], user_a), 0);
+    }
+}
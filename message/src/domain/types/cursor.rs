@@ -0,0 +1,52 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, TimeZone, Utc};
+use common::domain::types::error::ErrorMsg;
+use uuid::Uuid;
+
+
+/// Position of a message inside a conversation, handed to clients as an
+/// opaque string so the ordering keys can change without breaking them.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Cursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl TryFrom<String> for Cursor {
+    type Error = ErrorMsg;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || ErrorMsg("Invalid cursor".to_string());
+        let decoded = URL_SAFE_NO_PAD.decode(value).map_err(|_| invalid())?;
+        let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+        let (created_at, id) = decoded.split_once(':').ok_or_else(invalid)?;
+        let created_at = created_at.parse::<i64>().map_err(|_| invalid())?;
+        Ok(Self {
+            created_at: Utc.timestamp_millis_opt(created_at).single().ok_or_else(invalid)?,
+            id: Uuid::parse_str(id).map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl From<Cursor> for String {
+    fn from(value: Cursor) -> Self {
+        URL_SAFE_NO_PAD.encode(format!("{}:{}", value.created_at.timestamp_millis(), value.id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor() {
+        let cursor = Cursor {
+            created_at: Utc.timestamp_millis_opt(1_700_000_000_123).unwrap(),
+            id: Uuid::new_v4(),
+        };
+        let encoded: String = cursor.into();
+        assert_eq!(Cursor::try_from(encoded).unwrap(), cursor);
+        assert!(Cursor::try_from("not a cursor".to_string()).is_err());
+        assert!(Cursor::try_from(URL_SAFE_NO_PAD.encode("123")).is_err());
+    }
+}
@@ -0,0 +1,82 @@
+use common::domain::types::error::ErrorMsg;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+
+/// Longest emoji accepted, in chars. Sequences joined by zero width joiners
+/// like families take several.
+pub const MAX_EMOJI_LEN: usize = 16;
+
+/// Emoji a user reacts with
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct Emoji(String);
+
+impl TryFrom<String> for Emoji {
+    type Error = ErrorMsg;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || ErrorMsg("Invalid emoji".to_string());
+        if value.is_empty() || value.chars().count() > MAX_EMOJI_LEN {
+            return Err(invalid());
+        }
+        // One emoji or several joined by zero width joiners. Each is a flag, a
+        // keycap, or a pictograph other than half a flag with its optional
+        // variation selector, skin tone and subdivision tags.
+        let element = r"(?:\p{Regional_Indicator}{2}|[0-9#*]\x{FE0F}?\x{20E3}|[\p{Extended_Pictographic}\p{Emoji_Presentation}--\p{Regional_Indicator}][\x{FE0E}\x{FE0F}]?\p{Emoji_Modifier}?(?:[\x{E0020}-\x{E007E}]+\x{E007F})?)";
+        let sequence = format!(r"^{element}(?:\x{{200D}}{element})*$");
+        if !Regex::new(&sequence).unwrap().is_match(&value) {
+            return Err(invalid());
+        }
+        Ok(Emoji(value))
+    }
+}
+
+impl From<Emoji> for String {
+    fn from(emoji: Emoji) -> Self {
+        emoji.0
+    }
+}
+
+impl Serialize for Emoji {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Emoji {
+    fn deserialize<D>(deserializer: D) -> Result<Emoji, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Emoji::try_from(s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emoji() {
+        assert!(Emoji::try_from("👍".to_string()).is_ok());
+        assert!(Emoji::try_from("👍🏽".to_string()).is_ok());
+        assert!(Emoji::try_from("1️⃣".to_string()).is_ok());
+        assert!(Emoji::try_from("👨‍👩‍👧‍👦".to_string()).is_ok());
+        assert!(Emoji::try_from("🇲🇽".to_string()).is_ok());
+        assert!(Emoji::try_from("❤️".to_string()).is_ok());
+        assert!(Emoji::try_from("🧑🏻‍🤝‍🧑🏿".to_string()).is_ok());
+        assert!(Emoji::try_from("🏴\u{E0067}\u{E0062}\u{E0065}\u{E006E}\u{E0067}\u{E007F}".to_string()).is_ok());
+
+        assert!(Emoji::try_from(String::new()).is_err());
+        assert!(Emoji::try_from("ok".to_string()).is_err());
+        assert!(Emoji::try_from("👍 👍".to_string()).is_err());
+        assert!(Emoji::try_from("é".to_string()).is_err());
+        assert!(Emoji::try_from("中".to_string()).is_err());
+        assert!(Emoji::try_from("👍a".to_string()).is_err());
+        assert!(Emoji::try_from("\u{200D}👍".to_string()).is_err());
+        assert!(Emoji::try_from("🏽".to_string()).is_ok());
+        assert!(Emoji::try_from("🇲".to_string()).is_err());
+        assert!(Emoji::try_from("👍".repeat(MAX_EMOJI_LEN + 1)).is_err());
+    }
+}
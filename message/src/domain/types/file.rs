@@ -1,17 +1,17 @@
-use common::domain::types::error::ErrorMsg;
+use super::media::{self, InvalidMedia};
 
 
 pub struct File(Vec<u8>);
 
+impl File {
+    pub fn new(value: Vec<u8>, max_size: u64) -> Result<Self, InvalidMedia> {
+        media::validate("file", &value, value.len() as u64, max_size)?;
+        Ok(Self(value))
+    }
+}
 
-impl TryFrom<Vec<u8>> for File {
-    type Error = ErrorMsg;
-
-    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
-        if value.len() > 0 {
-            Ok(Self(value))
-        } else {
-            Err(ErrorMsg("Invalid text".to_string()))
-        }
+impl From<File> for Vec<u8> {
+    fn from(value: File) -> Self {
+        value.0
     }
-}
\ No newline at end of file
+}
@@ -1,16 +1,17 @@
-use common::domain::types::error::ErrorMsg;
+use super::media::{self, InvalidMedia};
 
 
 pub struct Image(Vec<u8>);
 
-impl TryFrom<Vec<u8>> for Image {
-    type Error = ErrorMsg;
+impl Image {
+    pub fn new(value: Vec<u8>, max_size: u64) -> Result<Self, InvalidMedia> {
+        media::validate("image", &value, value.len() as u64, max_size)?;
+        Ok(Self(value))
+    }
+}
 
-    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
-        if value.len() > 0 {
-            Ok(Self(value))
-        } else {
-            Err(ErrorMsg("Invalid text".to_string()))
-        }
+impl From<Image> for Vec<u8> {
+    fn from(value: Image) -> Self {
+        value.0
     }
-}
\ No newline at end of file
+}
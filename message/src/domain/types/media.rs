@@ -0,0 +1,131 @@
+use std::fmt;
+
+use infer::MatcherType;
+
+
+/// Leading bytes enough to recognize the type of a media
+pub const SNIFF_LEN: usize = 8 * 1024;
+
+/// Why a media was rejected, `code` tells the client which one
+#[derive(Debug, PartialEq)]
+pub enum InvalidMedia {
+    Empty,
+    TooLarge { max_size: u64 },
+    /// The content is not of the declared kind, `detected` is its MIME type
+    /// when it was recognized
+    Mismatch { kind: String, detected: Option<&'static str> },
+    /// Executables and scripts are never accepted
+    Disallowed { mime: &'static str },
+    /// Recognized but malformed, e.g. an image that can't be cleaned
+    Unreadable,
+}
+
+impl InvalidMedia {
+    pub fn code(&self) -> u32 {
+        match self {
+            InvalidMedia::Empty => 1,
+            InvalidMedia::TooLarge { .. } => 2,
+            InvalidMedia::Mismatch { .. } => 3,
+            InvalidMedia::Disallowed { .. } => 4,
+            InvalidMedia::Unreadable => 5,
+        }
+    }
+}
+
+impl fmt::Display for InvalidMedia {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidMedia::Empty => write!(f, "The media is empty"),
+            InvalidMedia::TooLarge { max_size } => {
+                write!(f, "The media is larger than {} bytes", max_size)
+            },
+            InvalidMedia::Mismatch { kind, detected: Some(mime) } => {
+                write!(f, "The content is {}, not {}", mime, kind)
+            },
+            InvalidMedia::Mismatch { kind, detected: None } => {
+                write!(f, "The content is not a known {} format", kind)
+            },
+            InvalidMedia::Disallowed { mime } => write!(f, "{} is not allowed", mime),
+            InvalidMedia::Unreadable => write!(f, "The media could not be read"),
+        }
+    }
+}
+
+/// Check a media of `kind` (a media path kind) from its leading bytes and
+/// its whole size
+pub fn validate(kind: &str, head: &[u8], size: u64, max_size: u64) -> Result<(), InvalidMedia> {
+    if size == 0 || head.is_empty() {
+        return Err(InvalidMedia::Empty);
+    }
+    if size > max_size {
+        return Err(InvalidMedia::TooLarge { max_size });
+    }
+
+    let detected = infer::get(head);
+    if let Some(mime) = disallowed(detected.as_ref()) {
+        return Err(InvalidMedia::Disallowed { mime });
+    }
+    let expected = match kind {
+        "image" => MatcherType::Image,
+        "video" => MatcherType::Video,
+        "audio" => MatcherType::Audio,
+        // Any other content, unknown formats included
+        "file" => return Ok(()),
+        _ => return Err(InvalidMedia::Mismatch { kind: kind.to_string(), detected: None }),
+    };
+    match detected {
+        Some(detected) if detected.matcher_type() == expected => Ok(()),
+        detected => Err(InvalidMedia::Mismatch {
+            kind: kind.to_string(),
+            detected: detected.map(|detected| detected.mime_type()),
+        }),
+    }
+}
+
+/// MIME type of executable content, binaries and scripts
+fn disallowed(detected: Option<&infer::Type>) -> Option<&'static str> {
+    detected
+        .filter(|detected| {
+            detected.matcher_type() == MatcherType::App
+                || detected.mime_type() == "text/x-shellscript"
+        })
+        .map(|detected| detected.mime_type())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(validate("image", PNG, 16, 1024), Ok(()));
+        assert_eq!(validate("file", PNG, 16, 1024), Ok(()));
+        assert_eq!(validate("file", b"plain text", 10, 1024), Ok(()));
+
+        assert_eq!(validate("image", b"", 0, 1024), Err(InvalidMedia::Empty));
+        assert_eq!(
+            validate("image", PNG, 2048, 1024),
+            Err(InvalidMedia::TooLarge { max_size: 1024 }),
+        );
+        assert_eq!(
+            validate("video", PNG, 16, 1024),
+            Err(InvalidMedia::Mismatch { kind: "video".to_string(), detected: Some("image/png") }),
+        );
+        assert_eq!(
+            validate("audio", b"plain text", 10, 1024),
+            Err(InvalidMedia::Mismatch { kind: "audio".to_string(), detected: None }),
+        );
+        let mut elf = b"\x7fELF\x02\x01\x01".to_vec();
+        elf.resize(64, 0);
+        assert_eq!(
+            validate("file", &elf, 64, 1024),
+            Err(InvalidMedia::Disallowed { mime: "application/x-executable" }),
+        );
+        assert!(matches!(
+            validate("file", b"#!/usr/bin/env python\n", 22, 1024),
+            Err(InvalidMedia::Disallowed { .. })
+        ));
+    }
+}
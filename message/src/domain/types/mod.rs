@@ -2,4 +2,7 @@ pub mod file;
 pub mod audio;
 pub mod video;
 pub mod image;
-pub mod user_contact_data;
\ No newline at end of file
+pub mod user_contact_data;
+pub mod cursor;
+pub mod media;
+pub mod emoji;
\ No newline at end of file
@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use common::domain::types::{error::ErrorMsg, id::Id, media_path::MediaPath};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+
+/// Size of every chunk but the last one, kept under the default request body limit
+pub const CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// Media being uploaded in chunks, it is moved to the media storage once
+/// every chunk arrived
+#[derive(Serialize, Deserialize)]
+pub struct Upload {
+    pub id: Uuid,
+    pub user_id: Id,
+    pub media_path: MediaPath,
+    pub size: u64,
+    pub chunk_size: u64,
+    /// Indexes of the chunks stored so far, in order
+    #[serde(skip)]
+    pub received_chunks: Vec<u64>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Upload {
+    pub fn new(user_id: Id, media_path: MediaPath, size: u64) -> Result<Self, ErrorMsg> {
+        if size == 0 {
+            return Err(ErrorMsg("Empty upload".to_string()));
+        }
+        Ok(Self {
+            id: Uuid::new_v4(),
+            user_id,
+            media_path,
+            size,
+            chunk_size: CHUNK_SIZE,
+            received_chunks: Vec::new(),
+            created_at: Utc::now(),
+        })
+    }
+
+    pub fn chunk_count(&self) -> u64 {
+        self.size.div_ceil(self.chunk_size)
+    }
+
+    /// Expected length of a chunk, `None` when the upload has no such chunk
+    pub fn chunk_len(&self, index: u64) -> Option<u64> {
+        if index >= self.chunk_count() {
+            return None;
+        }
+        Some(self.chunk_size.min(self.size - index * self.chunk_size))
+    }
+
+    /// Chunks still to be uploaded, in order
+    pub fn missing_chunks(&self) -> Vec<u64> {
+        (0..self.chunk_count())
+            .filter(|index| self.received_chunks.binary_search(index).is_err())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks() {
+        let user_id: Id = Uuid::new_v4().try_into().unwrap();
+        let media_path = MediaPath::new("video", &"a".repeat(64)).unwrap();
+        assert!(Upload::new(user_id, media_path.clone(), 0).is_err());
+
+        let mut upload = Upload::new(user_id, media_path, CHUNK_SIZE * 2 + 10).unwrap();
+        assert_eq!(upload.chunk_count(), 3);
+        assert_eq!(upload.chunk_len(0), Some(CHUNK_SIZE));
+        assert_eq!(upload.chunk_len(2), Some(10));
+        assert_eq!(upload.chunk_len(3), None);
+
+        upload.received_chunks = vec![0, 2];
+        assert_eq!(upload.missing_chunks(), vec![1]);
+        upload.received_chunks = vec![0, 1, 2];
+        assert!(upload.missing_chunks().is_empty());
+    }
+}
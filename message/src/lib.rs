@@ -5,5 +5,7 @@ mod application;
 mod adapter;
 
 pub use adapter::driving::web::handlers;
+pub use adapter::driving::ws::handlers as ws_handlers;
+pub use adapter::driven::group_history::GroupHistory;
 
 // pub static MIGRATOR: Migrator = sqlx::migrate!("src/adapter/driven/persistence/sqlx/migrations");
\ No newline at end of file